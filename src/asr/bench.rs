@@ -0,0 +1,363 @@
+//! ASR benchmark (`tuxtalks bench-asr`)
+//!
+//! Replays a corpus of recordings with known transcripts through one or more
+//! engines and reports word error rate, how often the recognized text still
+//! triggers the intended command, and per-utterance latency.
+//!
+//! A corpus is a directory of WAV/FLAC files, each with a sidecar `.txt`
+//! holding the expected transcript (`gear.wav` + `gear.txt`).
+
+use super::offline::{normalize_transcript, transcribe_clip, word_errors};
+use super::AsrEngine;
+use crate::audio::file_source::{collect_files, decode_file};
+use crate::audio::SAMPLE_RATE;
+use crate::commands::CommandProcessor;
+use crate::config::Config;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fmt::Write;
+use std::path::Path;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// One recording and what should be heard in it
+#[derive(Debug, Clone)]
+pub struct CorpusEntry {
+    /// File name, for reports
+    pub file: String,
+    pub expected: String,
+    /// 16 kHz mono samples
+    pub audio: Vec<i16>,
+}
+
+/// Load every recording in `dir` that has a sidecar transcript
+pub fn load_corpus(dir: &Path) -> Result<Vec<CorpusEntry>> {
+    if !dir.is_dir() {
+        anyhow::bail!("Corpus must be a directory: {}", dir.display());
+    }
+
+    let mut corpus = Vec::new();
+    for path in collect_files(dir)? {
+        let transcript = path.with_extension("txt");
+        let expected = match std::fs::read_to_string(&transcript) {
+            Ok(text) => text.trim().to_string(),
+            Err(_) => {
+                warn!("⚠️ No transcript for {}, skipping", path.display());
+                continue;
+            }
+        };
+        let audio = match decode_file(&path) {
+            Ok(audio) => audio,
+            Err(e) => {
+                warn!("⚠️ Skipping {}: {:#}", path.display(), e);
+                continue;
+            }
+        };
+        corpus.push(CorpusEntry {
+            file: path
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            expected,
+            audio,
+        });
+    }
+
+    if corpus.is_empty() {
+        anyhow::bail!("No transcribed recordings in {}", dir.display());
+    }
+    Ok(corpus)
+}
+
+/// Build an engine from a `--engine` spec
+///
+/// `vosk` and `wyoming` use the configured model/server; `vosk=PATH` and
+/// `wyoming=HOST:PORT` override them.
+pub fn engine_from_spec(spec: &str, config: &Config) -> Result<Box<dyn AsrEngine>> {
+    let (kind, target) = match spec.split_once('=') {
+        Some((kind, target)) => (kind, Some(target)),
+        None => (spec, None),
+    };
+
+    match kind {
+        "vosk" => {
+            let mut config = config.clone();
+            if let Some(path) = target {
+                config.vosk_model_path = path.to_string();
+            }
+            Ok(Box::new(super::VoskAsr::new(&config)?))
+        }
+        "wyoming" => {
+            let (host, port) = match target {
+                Some(target) => {
+                    let (host, port) = target
+                        .rsplit_once(':')
+                        .with_context(|| format!("Expected HOST:PORT in '{}'", spec))?;
+                    let port = port
+                        .parse()
+                        .with_context(|| format!("Invalid port in '{}'", spec))?;
+                    (host.to_string(), port)
+                }
+                None => (config.wyoming_host.clone(), config.wyoming_port),
+            };
+            Ok(Box::new(super::WyomingClient::new(&host, port)))
+        }
+        other => anyhow::bail!("Unknown ASR engine '{}' (expected vosk or wyoming)", other),
+    }
+}
+
+/// Outcome for one recording
+#[derive(Debug, Clone, Serialize)]
+pub struct UtteranceResult {
+    pub file: String,
+    pub expected: String,
+    pub heard: String,
+    pub word_errors: usize,
+    pub reference_words: usize,
+    /// Command the expected transcript triggers (None = not a command)
+    pub expected_command: Option<String>,
+    /// Command the recognized text triggers
+    pub matched_command: Option<String>,
+    /// From the end of the audio to the final result
+    pub latency_ms: f64,
+    /// Processing time divided by audio duration
+    pub real_time_factor: f64,
+}
+
+impl UtteranceResult {
+    /// Whether the recognized text triggers the intended command
+    pub fn command_matched(&self) -> bool {
+        self.expected_command.is_some() && self.expected_command == self.matched_command
+    }
+}
+
+/// Aggregate results for one engine
+#[derive(Debug, Clone, Serialize)]
+pub struct EngineReport {
+    pub engine: String,
+    /// Word error rate over the whole corpus
+    pub wer: f64,
+    /// Share of command utterances that still triggered the right command
+    /// (None if no expected transcript is a command)
+    pub command_match_rate: Option<f64>,
+    pub mean_latency_ms: f64,
+    pub p95_latency_ms: f64,
+    pub real_time_factor: f64,
+    pub utterances: Vec<UtteranceResult>,
+}
+
+impl EngineReport {
+    fn from_utterances(engine: &str, utterances: Vec<UtteranceResult>) -> Self {
+        let errors: usize = utterances.iter().map(|u| u.word_errors).sum();
+        let words: usize = utterances.iter().map(|u| u.reference_words).sum();
+
+        let commands: Vec<_> = utterances
+            .iter()
+            .filter(|u| u.expected_command.is_some())
+            .collect();
+        let command_match_rate = (!commands.is_empty()).then(|| {
+            commands.iter().filter(|u| u.command_matched()).count() as f64 / commands.len() as f64
+        });
+
+        let mut latencies: Vec<f64> = utterances.iter().map(|u| u.latency_ms).collect();
+        latencies.sort_by(f64::total_cmp);
+        let mean = |values: &[f64]| {
+            if values.is_empty() {
+                0.0
+            } else {
+                values.iter().sum::<f64>() / values.len() as f64
+            }
+        };
+        let p95 = match latencies.len() {
+            0 => 0.0,
+            n => latencies[(n * 95).div_ceil(100) - 1],
+        };
+        let rtfs: Vec<f64> = utterances.iter().map(|u| u.real_time_factor).collect();
+
+        Self {
+            engine: engine.to_string(),
+            wer: if words == 0 {
+                0.0
+            } else {
+                errors as f64 / words as f64
+            },
+            command_match_rate,
+            mean_latency_ms: mean(&latencies),
+            p95_latency_ms: p95,
+            real_time_factor: mean(&rtfs),
+            utterances,
+        }
+    }
+}
+
+/// Run the corpus through one engine
+///
+/// `processor` holds the commands matched against both the expected and the
+/// recognized transcript.
+pub async fn run(
+    name: &str,
+    engine: &mut dyn AsrEngine,
+    corpus: &[CorpusEntry],
+    processor: &CommandProcessor,
+) -> Result<EngineReport> {
+    info!("⏱️ Benchmarking '{}' on {} recordings", name, corpus.len());
+
+    let mut utterances = Vec::with_capacity(corpus.len());
+    for entry in corpus {
+        let result = transcribe_clip(engine, &entry.audio).await?;
+        let duration = Duration::from_secs_f64(entry.audio.len() as f64 / SAMPLE_RATE as f64);
+
+        utterances.push(UtteranceResult {
+            file: entry.file.clone(),
+            expected: entry.expected.clone(),
+            word_errors: word_errors(&entry.expected, &result.text),
+            reference_words: normalize_transcript(&entry.expected)
+                .split_whitespace()
+                .count(),
+            expected_command: processor
+                .match_command(&normalize_transcript(&entry.expected))
+                .map(|c| c.name().to_string()),
+            matched_command: processor
+                .match_command(&normalize_transcript(&result.text))
+                .map(|c| c.name().to_string()),
+            latency_ms: result.latency.as_secs_f64() * 1000.0,
+            real_time_factor: if duration.is_zero() {
+                0.0
+            } else {
+                result.processing.as_secs_f64() / duration.as_secs_f64()
+            },
+            heard: result.text,
+        });
+    }
+
+    Ok(EngineReport::from_utterances(name, utterances))
+}
+
+/// Human-readable summary, followed by every misrecognized utterance
+pub fn format_table(reports: &[EngineReport]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "{:<28} {:>6} {:>7} {:>9} {:>10} {:>10} {:>6}",
+        "ENGINE", "CLIPS", "WER", "COMMANDS", "LAT MEAN", "LAT P95", "RTF"
+    );
+    for report in reports {
+        let commands = report
+            .command_match_rate
+            .map_or_else(|| "-".to_string(), |r| format!("{:.1}%", r * 100.0));
+        let _ = writeln!(
+            out,
+            "{:<28} {:>6} {:>6.1}% {:>9} {:>8.0}ms {:>8.0}ms {:>6.2}",
+            report.engine,
+            report.utterances.len(),
+            report.wer * 100.0,
+            commands,
+            report.mean_latency_ms,
+            report.p95_latency_ms,
+            report.real_time_factor
+        );
+    }
+
+    for report in reports {
+        let misses: Vec<_> = report
+            .utterances
+            .iter()
+            .filter(|u| u.word_errors > 0)
+            .collect();
+        if misses.is_empty() {
+            continue;
+        }
+        let _ = writeln!(out, "\n{}:", report.engine);
+        for u in misses {
+            let command = match (&u.expected_command, u.command_matched()) {
+                (Some(_), true) => " (command ok)",
+                (Some(_), false) => " (command missed)",
+                (None, _) => "",
+            };
+            let _ = writeln!(
+                out,
+                "  {}: expected '{}', heard '{}'{}",
+                u.file, u.expected, u.heard, command
+            );
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asr::{AsrEvent, AsrResult};
+    use crate::audio::file_source::write_wav;
+    use crate::commands::Command;
+
+    /// Engine that hears "deploy landing beer" in every clip
+    struct MishearingEngine {
+        voiced: bool,
+    }
+
+    impl AsrEngine for MishearingEngine {
+        fn process(&mut self, samples: &[i16]) -> Result<Option<AsrEvent>> {
+            if samples.iter().any(|&s| s != 0) {
+                self.voiced = true;
+                return Ok(None);
+            }
+            if std::mem::take(&mut self.voiced) {
+                return Ok(Some(AsrEvent::Final(AsrResult::new(
+                    "deploy landing beer",
+                    1.0,
+                ))));
+            }
+            Ok(None)
+        }
+
+        fn reset(&mut self) {
+            self.voiced = false;
+        }
+
+        fn name(&self) -> &str {
+            "mishearing"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_bench_reports_wer_and_command_matches() {
+        let dir = tempfile::tempdir().unwrap();
+        let tone: Vec<i16> = (0..8000).map(|i| ((i % 50) * 400) as i16).collect();
+        for (name, transcript) in [("gear", "Deploy landing gear."), ("chat", "hello there")] {
+            write_wav(&dir.path().join(format!("{}.wav", name)), &tone).unwrap();
+            std::fs::write(dir.path().join(format!("{}.txt", name)), transcript).unwrap();
+        }
+        // No transcript: ignored
+        write_wav(&dir.path().join("stray.wav"), &tone).unwrap();
+
+        let corpus = load_corpus(dir.path()).unwrap();
+        assert_eq!(corpus.len(), 2);
+
+        let mut processor = CommandProcessor::new().unwrap();
+        processor.add_command(Command::Action {
+            name: "landing_gear".into(),
+            triggers: vec!["landing gear".into(), "deploy landing".into()],
+            key: "L".into(),
+            modifiers: vec![],
+        });
+
+        let mut engine = MishearingEngine { voiced: false };
+        let report = run("mishearing", &mut engine, &corpus, &processor)
+            .await
+            .unwrap();
+
+        // Sorted by file name; "chat" gets two substitutions and an insertion
+        let chat = &report.utterances[0];
+        assert_eq!(chat.file, "chat.wav");
+        assert_eq!((chat.word_errors, chat.reference_words), (3, 2));
+        assert_eq!(chat.expected_command, None);
+        let gear = &report.utterances[1];
+        assert_eq!((gear.word_errors, gear.reference_words), (1, 3));
+        assert!(gear.command_matched());
+
+        assert!((report.wer - 4.0 / 5.0).abs() < 1e-9);
+        assert_eq!(report.command_match_rate, Some(1.0));
+        assert!(format_table(&[report]).contains("expected 'hello there'"));
+    }
+}
@@ -0,0 +1,199 @@
+//! ASR failover chain
+//!
+//! Wraps an ordered list of engines (e.g. Wyoming first, Vosk as the offline
+//! fallback) and routes audio to the first healthy one. Health is re-checked
+//! periodically, so recognition moves back up the chain once a preferred
+//! engine recovers.
+
+use super::{AsrEngine, AsrEvent};
+use anyhow::Result;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// How often engine health is re-evaluated
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Engines in priority order, with audio routed to the first healthy one
+pub struct FailoverEngine {
+    engines: Vec<Box<dyn AsrEngine>>,
+    active: usize,
+    last_check: Option<Instant>,
+    paused: bool,
+}
+
+impl FailoverEngine {
+    pub fn new(engines: Vec<Box<dyn AsrEngine>>) -> Self {
+        assert!(
+            !engines.is_empty(),
+            "FailoverEngine needs at least one engine"
+        );
+        let names: Vec<&str> = engines.iter().map(|e| e.name()).collect();
+        info!("🔀 ASR failover chain: {}", names.join(" → "));
+
+        Self {
+            engines,
+            active: 0,
+            last_check: None,
+            paused: false,
+        }
+    }
+
+    /// Name of the engine currently receiving audio
+    pub fn active_name(&self) -> &str {
+        self.engines[self.active].name()
+    }
+
+    /// Re-evaluate health if due, switching to the best healthy engine
+    fn check_health(&mut self, force: bool) {
+        let due = self
+            .last_check
+            .is_none_or(|t| t.elapsed() >= HEALTH_CHECK_INTERVAL);
+        if !force && !due {
+            return;
+        }
+        self.last_check = Some(Instant::now());
+
+        // Fall back to the last engine if nothing reports healthy
+        let last = self.engines.len() - 1;
+        let best = self
+            .engines
+            .iter_mut()
+            .position(|e| e.is_healthy())
+            .unwrap_or(last);
+        if best == self.active {
+            return;
+        }
+
+        let from = self.engines[self.active].name().to_string();
+        let to = self.engines[best].name().to_string();
+        if best < self.active {
+            info!(
+                "🔀 ASR engine '{}' recovered, switching back from '{}'",
+                to, from
+            );
+        } else {
+            warn!("🔀 ASR failover: {} → {}", from, to);
+        }
+
+        // Whatever the old engine heard of the current utterance is lost
+        self.engines[self.active].reset();
+        self.engines[best].reset();
+        self.active = best;
+    }
+}
+
+impl AsrEngine for FailoverEngine {
+    fn process(&mut self, samples: &[i16]) -> Result<Option<AsrEvent>> {
+        self.check_health(false);
+
+        match self.engines[self.active].process(samples) {
+            Ok(event) => Ok(event),
+            Err(e) => {
+                warn!("⚠️ ASR engine '{}' failed: {}", self.active_name(), e);
+                self.check_health(true);
+                Ok(None)
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        for engine in &mut self.engines {
+            engine.reset();
+        }
+    }
+
+    fn pause(&mut self) {
+        self.paused = true;
+        for engine in &mut self.engines {
+            engine.pause();
+        }
+    }
+
+    fn resume(&mut self) {
+        self.paused = false;
+        for engine in &mut self.engines {
+            engine.resume();
+        }
+    }
+
+    fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    fn name(&self) -> &str {
+        self.active_name()
+    }
+
+    fn is_healthy(&mut self) -> bool {
+        self.engines.iter_mut().any(|e| e.is_healthy())
+    }
+
+    fn set_grammar(&mut self, phrases: &[String]) -> Result<()> {
+        for engine in &mut self.engines {
+            if let Err(e) = engine.set_grammar(phrases) {
+                warn!("⚠️ Failed to set grammar on '{}': {}", engine.name(), e);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    struct StubEngine {
+        name: &'static str,
+        healthy: Arc<AtomicBool>,
+    }
+
+    impl AsrEngine for StubEngine {
+        fn process(&mut self, _samples: &[i16]) -> Result<Option<AsrEvent>> {
+            Ok(Some(AsrEvent::Partial(self.name.to_string())))
+        }
+
+        fn reset(&mut self) {}
+
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn is_healthy(&mut self) -> bool {
+            self.healthy.load(Ordering::Relaxed)
+        }
+    }
+
+    fn heard(engine: &mut FailoverEngine) -> String {
+        match engine.process(&[0; 10]).unwrap() {
+            Some(AsrEvent::Partial(name)) => name,
+            other => panic!("unexpected event {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_failover_and_recovery() {
+        let wyoming_up = Arc::new(AtomicBool::new(true));
+        let mut engine = FailoverEngine::new(vec![
+            Box::new(StubEngine {
+                name: "wyoming",
+                healthy: wyoming_up.clone(),
+            }),
+            Box::new(StubEngine {
+                name: "vosk",
+                healthy: Arc::new(AtomicBool::new(true)),
+            }),
+        ]);
+        assert_eq!(heard(&mut engine), "wyoming");
+
+        wyoming_up.store(false, Ordering::Relaxed);
+        engine.check_health(true);
+        assert_eq!(engine.name(), "vosk");
+        assert_eq!(heard(&mut engine), "vosk");
+
+        wyoming_up.store(true, Ordering::Relaxed);
+        engine.check_health(true);
+        assert_eq!(heard(&mut engine), "wyoming");
+    }
+}
@@ -0,0 +1,145 @@
+//! Recognizer grammar generation
+//!
+//! Builds the phrase list that constrains Vosk to what TuxTalks can act on:
+//! the active game profile's triggers, media keywords and requests ("play
+//! album ..."), the music library's artist, album, genre and playlist names,
+//! prompt answers, command joiners and modifiers ("and", "five times"), the
+//! wake word and the user's custom vocabulary.
+
+use crate::commands::{
+    ABORT_PHRASES, CONJUNCTIONS, FAST_KEYWORD_PHRASES, PROMPT_PHRASES, REPEAT_PHRASES, UNDO_PHRASES,
+};
+use crate::config::Config;
+use crate::core::command_modifiers;
+use crate::games::GameProfile;
+
+/// Vosk's catch-all token, so out-of-grammar speech isn't forced onto a phrase
+pub const UNKNOWN_TOKEN: &str = "[unk]";
+
+/// Words the media request parser understands around a library name
+const MEDIA_REQUEST_WORDS: &[&str] = &[
+    "play",
+    "artist",
+    "album",
+    "playlist",
+    "smartlist",
+    "from",
+    "whatever",
+    "random",
+    "anything",
+    "some",
+    "the",
+    "a",
+    "an",
+    "of",
+    "by",
+];
+
+/// Build the grammar for the given profile
+///
+/// `library` holds music library names, so requests like "play some jazz"
+/// stay recognizable while a profile constrains the vocabulary. Returns an
+/// empty list (open vocabulary) when there is neither an active profile nor
+/// any custom vocabulary.
+pub fn build_grammar(
+    config: &Config,
+    profile: Option<&GameProfile>,
+    library: &[String],
+) -> Vec<String> {
+    if profile.is_none() && config.custom_vocabulary.is_empty() {
+        return Vec::new();
+    }
+
+    let mut phrases: Vec<String> = config
+        .custom_vocabulary
+        .iter()
+        .map(|v| v.to_lowercase())
+        .collect();
+
+    if !config.wake_word.is_empty() {
+        phrases.push(config.wake_word.to_lowercase());
+    }
+    if let Some(profile) = profile {
+        phrases.extend(profile.voice_phrases());
+    }
+    phrases.extend(FAST_KEYWORD_PHRASES.iter().map(|p| p.to_string()));
+    phrases.extend(MEDIA_REQUEST_WORDS.iter().map(|p| p.to_string()));
+    phrases.extend(library.iter().map(|name| spoken_form(name)));
+    phrases.extend(PROMPT_PHRASES.iter().map(|p| p.to_string()));
+    phrases.extend(
+        UNDO_PHRASES
+            .iter()
+            .chain(REPEAT_PHRASES)
+            .chain(ABORT_PHRASES)
+            .map(|p| p.to_string()),
+    );
+    // "next target five times and boost"
+    phrases.extend(CONJUNCTIONS.iter().map(|p| p.to_string()));
+    phrases.extend(command_modifiers::phrases());
+
+    let mut seen = std::collections::HashSet::new();
+    phrases.retain(|p| !p.trim().is_empty() && seen.insert(p.clone()));
+    phrases.push(UNKNOWN_TOKEN.to_string());
+    phrases
+}
+
+/// Lowercase `name` and drop punctuation the recognizer never outputs
+fn spoken_form(name: &str) -> String {
+    let cleaned: String = name
+        .to_lowercase()
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '\'' {
+                c
+            } else {
+                ' '
+            }
+        })
+        .collect();
+    cleaned.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::GameType;
+
+    #[test]
+    fn test_open_vocabulary_without_profile() {
+        let config = Config::default();
+        assert!(build_grammar(&config, None, &["Miles Davis".to_string()]).is_empty());
+    }
+
+    #[test]
+    fn test_grammar_from_profile() {
+        let config = Config::default();
+        let mut profile = GameProfile::new("Test", GameType::Generic);
+        profile.voice_commands.insert(
+            "Landing Gear".to_string(),
+            vec!["Landing Gear".to_string(), "gear".to_string()],
+        );
+        profile.macros.push(crate::commands::Macro {
+            name: "Dock".to_string(),
+            triggers: vec!["request docking".to_string(), "gear".to_string()],
+            steps: Vec::new(),
+            ..Default::default()
+        });
+
+        let library = vec!["Jazz".to_string(), "AC/DC".to_string()];
+        let grammar = build_grammar(&config, Some(&profile), &library);
+        assert!(grammar.contains(&"landing gear".to_string()));
+        assert!(grammar.contains(&"request docking".to_string()));
+        assert!(grammar.contains(&"tuxtalks".to_string()));
+        assert!(grammar.contains(&"next track".to_string()));
+        assert!(grammar.contains(&"play".to_string()));
+        assert!(grammar.contains(&"jazz".to_string()));
+        assert!(grammar.contains(&"ac dc".to_string()));
+        assert!(grammar.contains(&"confirm".to_string()));
+        assert!(grammar.contains(&"five times".to_string()));
+        assert!(grammar.contains(&"then".to_string()));
+        assert!(grammar.contains(&"cancel that".to_string()));
+        assert!(grammar.contains(&"belay that".to_string()));
+        assert_eq!(grammar.iter().filter(|p| *p == "gear").count(), 1);
+        assert_eq!(grammar.last().map(String::as_str), Some(UNKNOWN_TOKEN));
+    }
+}
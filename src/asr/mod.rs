@@ -3,16 +3,25 @@
 //! Provides multiple ASR backends:
 //! - Vosk: Local offline recognition
 //! - Wyoming: Remote ASR protocol (e.g., faster-whisper)
+//!
+//! Several engines can be chained (`asr_engines`) for automatic failover.
 
+pub mod bench;
+pub mod failover;
+pub mod grammar;
+pub mod offline;
 pub mod vosk;
+pub mod wake;
 pub mod wyoming;
 pub mod wyoming_manager;
 
 use crate::config::Config;
 use anyhow::Result;
 use async_trait::async_trait;
+use tracing::warn;
 
 // Re-export main types
+pub use failover::FailoverEngine;
 pub use vosk::VoskAsr;
 pub use wyoming::WyomingClient;
 
@@ -21,6 +30,45 @@ pub use wyoming::WyomingClient;
 pub struct AsrResult {
     pub text: String,
     pub confidence: f32,
+    /// Runner-up hypotheses, most likely first (empty if the engine has no n-best)
+    pub alternatives: Vec<AsrAlternative>,
+}
+
+/// One entry of an n-best list
+#[derive(Debug, Clone, PartialEq)]
+pub struct AsrAlternative {
+    pub text: String,
+    pub confidence: f32,
+}
+
+impl AsrResult {
+    /// Result with a single hypothesis
+    pub fn new(text: impl Into<String>, confidence: f32) -> Self {
+        Self {
+            text: text.into(),
+            confidence,
+            alternatives: Vec::new(),
+        }
+    }
+
+    /// All hypotheses, best first (the top result followed by the alternatives)
+    pub fn hypotheses(&self) -> Vec<AsrAlternative> {
+        std::iter::once(AsrAlternative {
+            text: self.text.clone(),
+            confidence: self.confidence,
+        })
+        .chain(self.alternatives.iter().cloned())
+        .collect()
+    }
+}
+
+/// Output of an ASR engine for one chunk of audio
+#[derive(Debug, Clone)]
+pub enum AsrEvent {
+    /// Interim hypothesis for the utterance in progress (may still change)
+    Partial(String),
+    /// Finalized result for a complete utterance
+    Final(AsrResult),
 }
 
 /// Minimum confidence threshold (below this, results are discarded)
@@ -29,9 +77,9 @@ pub const MIN_CONFIDENCE: f32 = 0.5;
 /// Trait for ASR engines
 #[async_trait]
 pub trait AsrEngine: Send + Sync {
-    /// Process audio samples and return recognized text with confidence (if final)
-    /// Results below MIN_CONFIDENCE should be filtered out internally
-    fn process(&mut self, samples: &[i16]) -> Result<Option<AsrResult>>;
+    /// Process audio samples and return an interim hypothesis or a final result
+    /// Final results below MIN_CONFIDENCE should be filtered out internally
+    fn process(&mut self, samples: &[i16]) -> Result<Option<AsrEvent>>;
 
     /// Reset the recognizer state
     fn reset(&mut self);
@@ -48,16 +96,59 @@ pub trait AsrEngine: Send + Sync {
     fn is_paused(&self) -> bool {
         false
     }
+
+    /// Short engine name for logs and status reports (e.g. "vosk")
+    fn name(&self) -> &str;
+
+    /// Whether the engine can currently produce results
+    /// Default implementation assumes local engines are always available
+    fn is_healthy(&mut self) -> bool {
+        true
+    }
+
+    /// Constrain recognition to these phrases (empty = open vocabulary)
+    /// Default implementation does nothing (engines without grammar support)
+    fn set_grammar(&mut self, _phrases: &[String]) -> Result<()> {
+        Ok(())
+    }
 }
 
 /// Factory to create the configured ASR engine
+///
+/// With more than one engine in `asr_engines`, returns a [`FailoverEngine`]
+/// over every engine that could be created.
 pub fn create_engine(config: Config) -> Result<Box<dyn AsrEngine>> {
-    match config.asr_engine.as_str() {
-        "vosk" => Ok(Box::new(vosk::VoskAsr::new(&config)?)),
-        "wyoming" => Ok(Box::new(wyoming::WyomingClient::new(
-            &config.wyoming_host,
-            config.wyoming_port,
-        ))),
-        _ => Ok(Box::new(vosk::VoskAsr::new(&config)?)),
+    let mut engines: Vec<Box<dyn AsrEngine>> = Vec::new();
+    let mut last_error = None;
+
+    for name in config.asr_engine_chain() {
+        let engine: Result<Box<dyn AsrEngine>> = match name.as_str() {
+            "vosk" => vosk::VoskAsr::new(&config).map(|e| Box::new(e) as Box<dyn AsrEngine>),
+            "wyoming" => Ok(Box::new(wyoming::WyomingClient::new(
+                &config.wyoming_host,
+                config.wyoming_port,
+            ))),
+            other => {
+                warn!("⚠️ Unknown ASR engine '{}', skipping", other);
+                continue;
+            }
+        };
+        match engine {
+            Ok(engine) => engines.push(engine),
+            Err(e) => {
+                warn!("⚠️ Failed to create ASR engine '{}': {}", name, e);
+                last_error = Some(e);
+            }
+        }
+    }
+
+    match engines.len() {
+        0 => match last_error {
+            Some(e) => Err(e),
+            // Only unknown names: keep the historical Vosk default
+            None => Ok(Box::new(vosk::VoskAsr::new(&config)?)),
+        },
+        1 => Ok(engines.remove(0)),
+        _ => Ok(Box::new(FailoverEngine::new(engines))),
     }
 }
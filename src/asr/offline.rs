@@ -0,0 +1,116 @@
+//! Offline transcription of recorded clips
+//!
+//! Feeds a whole 16 kHz mono recording through an [`AsrEngine`] the way live
+//! capture would, then waits for the final result. Shared by voice training
+//! evaluation and the `bench-asr` command.
+
+use super::{AsrEngine, AsrEvent};
+use anyhow::Result;
+use std::time::{Duration, Instant};
+
+/// Chunk size used when replaying clips (matches live capture)
+const CHUNK_SIZE: usize = 1024;
+
+/// Silence appended to each clip (~1.5 s) so the engine finalizes it
+const TRAILING_SILENCE_CHUNKS: usize = 24;
+
+/// How long to wait for a remote engine's final result
+const RESULT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// What an engine made of one clip
+#[derive(Debug, Clone, Default)]
+pub struct Transcription {
+    /// All finalized text, in order
+    pub text: String,
+    /// From the end of the clip's audio to the last final result
+    pub latency: Duration,
+    /// Wall time spent on the whole clip
+    pub processing: Duration,
+}
+
+/// Feed a clip through the engine and return everything it finalized
+pub async fn transcribe_clip(engine: &mut dyn AsrEngine, audio: &[i16]) -> Result<Transcription> {
+    engine.reset();
+    let started = Instant::now();
+    let silence = vec![0i16; CHUNK_SIZE];
+    let mut finals = Vec::new();
+    let mut last_final = None;
+
+    for chunk in audio.chunks(CHUNK_SIZE) {
+        if let Some(AsrEvent::Final(result)) = engine.process(chunk)? {
+            finals.push(result.text);
+        }
+    }
+    let audio_done = Instant::now();
+
+    for _ in 0..TRAILING_SILENCE_CHUNKS {
+        if let Some(AsrEvent::Final(result)) = engine.process(&silence)? {
+            finals.push(result.text);
+            last_final = Some(Instant::now());
+        }
+    }
+
+    // Remote engines deliver the final result asynchronously
+    let deadline = Instant::now() + RESULT_TIMEOUT;
+    while finals.is_empty() && Instant::now() < deadline {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        if let Some(AsrEvent::Final(result)) = engine.process(&silence)? {
+            finals.push(result.text);
+            last_final = Some(Instant::now());
+        }
+    }
+
+    Ok(Transcription {
+        text: finals.join(" "),
+        latency: last_final.map_or(Duration::ZERO, |t| t.duration_since(audio_done)),
+        processing: started.elapsed(),
+    })
+}
+
+/// Lowercase words without punctuation, for comparing transcripts
+pub fn normalize_transcript(text: &str) -> String {
+    text.to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Word-level edit distance (substitutions + insertions + deletions) between
+/// a reference and a hypothesis, after normalization
+pub fn word_errors(reference: &str, hypothesis: &str) -> usize {
+    let reference = normalize_transcript(reference);
+    let hypothesis = normalize_transcript(hypothesis);
+    let reference: Vec<&str> = reference.split_whitespace().collect();
+    let hypothesis: Vec<&str> = hypothesis.split_whitespace().collect();
+
+    let mut previous: Vec<usize> = (0..=hypothesis.len()).collect();
+    for (i, r) in reference.iter().enumerate() {
+        let mut current = vec![i + 1; hypothesis.len() + 1];
+        for (j, h) in hypothesis.iter().enumerate() {
+            let substitution = previous[j] + usize::from(r != h);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[hypothesis.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_word_errors() {
+        assert_eq!(
+            word_errors("Deploy the landing gear.", "deploy the landing gear"),
+            0
+        );
+        assert_eq!(word_errors("deploy landing gear", "deploy landing beer"), 1);
+        assert_eq!(word_errors("deploy landing gear", "landing gear"), 1);
+        assert_eq!(word_errors("gear", "the landing gear up"), 3);
+        assert_eq!(word_errors("", "noise"), 1);
+    }
+}
@@ -1,16 +1,43 @@
 //! ASR (Automatic Speech Recognition) module using Vosk
 
+use crate::audio::{VadEvent, VoiceActivityDetector};
 use crate::config::Config;
 use anyhow::{Context, Result};
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, Weak};
 use tracing::{debug, info};
 use vosk::{Model, Recognizer};
 
 const SAMPLE_RATE: f32 = 16000.0;
 
+lazy_static! {
+    /// Loaded models by path, so the wake spotter and the recognizer share one
+    static ref MODELS: Mutex<HashMap<String, Weak<Model>>> = Mutex::new(HashMap::new());
+}
+
+/// Load a Vosk model, reusing it if another engine already holds it
+pub(super) fn load_model(path: &str) -> Result<Arc<Model>> {
+    let mut models = MODELS.lock().expect("Model cache mutex poisoned");
+    if let Some(model) = models.get(path).and_then(Weak::upgrade) {
+        debug!("Reusing loaded Vosk model {}", path);
+        return Ok(model);
+    }
+    let model = Arc::new(Model::new(path).context("Failed to load Vosk model")?);
+    models.insert(path.to_string(), Arc::downgrade(&model));
+    Ok(model)
+}
+
 /// Vosk-based ASR engine
 pub struct VoskAsr {
+    /// Kept so the recognizer can be rebuilt with a new grammar
+    model: Arc<Model>,
     recognizer: Recognizer,
+    /// Runner-up hypotheses requested (0 = word confidences instead of n-best)
+    max_alternatives: u16,
     paused: bool,
+    /// Keeps silence away from the recognizer and marks utterance ends
+    vad: VoiceActivityDetector,
 }
 
 impl VoskAsr {
@@ -34,93 +61,139 @@ impl VoskAsr {
             )
         })?;
 
-        let model = Model::new(model_str).context("Failed to load Vosk model")?;
-
-        let mut grammar = config.custom_vocabulary.clone();
-        if !grammar.is_empty() && !config.wake_word.is_empty() {
-            let wake_lower = config.wake_word.to_lowercase();
-            if !grammar.iter().any(|v| v.to_lowercase() == wake_lower) {
-                grammar.push(config.wake_word.clone());
-                info!(
-                    "📢 Dynamically added wake word '{}' to ASR grammar",
-                    config.wake_word
-                );
-            }
-        }
+        let model = load_model(model_str)?;
 
-        let recognizer = if grammar.is_empty() {
-            Recognizer::new(&model, SAMPLE_RATE).context("Failed to create Vosk recognizer")?
-        } else {
-            info!("⚙️ Using custom grammar ({} words)", grammar.len());
-            Recognizer::new_with_grammar(&model, SAMPLE_RATE, &grammar)
-                .context("Failed to create Vosk recognizer with grammar")?
-        };
+        let grammar = super::grammar::build_grammar(config, None, &[]);
+        let max_alternatives = config.asr_max_alternatives;
+        let recognizer = build_recognizer(&model, &grammar, max_alternatives)?;
+        if max_alternatives > 0 {
+            info!("⚙️ Requesting {} ASR alternatives", max_alternatives);
+        }
 
         Ok(Self {
+            model,
             recognizer,
+            max_alternatives,
             paused: false,
+            vad: VoiceActivityDetector::default(),
+        })
+    }
+
+    /// Take the recognizer's final result, applying the confidence filter
+    fn take_final(&mut self) -> Option<super::AsrResult> {
+        let result = if self.max_alternatives > 0 {
+            self.take_final_nbest()?
+        } else {
+            let result = self.recognizer.final_result();
+            let single = result.single()?;
+            let text = extract_text(single.text)?;
+
+            // Calculate average word confidence
+            let avg_confidence = if single.result.is_empty() {
+                1.0f32 // Default if no word-level info
+            } else {
+                let sum: f32 = single.result.iter().map(|w| w.conf).sum();
+                sum / single.result.len() as f32
+            };
+            super::AsrResult::new(text, avg_confidence)
+        };
+
+        // Apply confidence filter (Chisholm guardrail)
+        if result.confidence < super::MIN_CONFIDENCE {
+            info!(
+                "🔇 Rejecting low-confidence ASR ({:.2}): '{}'",
+                result.confidence, result.text
+            );
+            return None;
+        }
+
+        Some(result)
+    }
+
+    /// Final result in n-best mode
+    ///
+    /// Vosk scores alternatives with unnormalised log-likelihoods, so they are
+    /// converted into posteriors over the returned list (see [`merge_nbest`]).
+    fn take_final_nbest(&mut self) -> Option<super::AsrResult> {
+        let result = self.recognizer.final_result();
+        let multiple = result.multiple()?;
+
+        let scored: Vec<(String, f32)> = multiple
+            .alternatives
+            .iter()
+            .filter_map(|alt| Some((extract_text(alt.text)?, alt.confidence)))
+            .collect();
+        let mut hypotheses = merge_nbest(scored).into_iter();
+        let top = hypotheses.next()?;
+        let alternatives: Vec<super::AsrAlternative> = hypotheses.collect();
+        if !alternatives.is_empty() {
+            debug!(
+                "N-best for '{}': {:?}",
+                top.text,
+                alternatives.iter().map(|a| &a.text).collect::<Vec<_>>()
+            );
+        }
+
+        Some(super::AsrResult {
+            text: top.text,
+            confidence: top.confidence,
+            alternatives,
         })
     }
+
+    /// Feed audio to the recognizer, returning the current partial or a final
+    /// result if Vosk endpointed on its own
+    fn accept(&mut self, samples: &[i16]) -> Option<super::AsrEvent> {
+        match self.recognizer.accept_waveform(samples) {
+            vosk::DecodingState::Finalized => self.take_final().map(super::AsrEvent::Final),
+            vosk::DecodingState::Running => {
+                let partial = extract_text(self.recognizer.partial_result().partial)?;
+                debug!("Partial: {}", partial);
+                Some(super::AsrEvent::Partial(partial))
+            }
+            vosk::DecodingState::Failed => {
+                debug!("Decoding failed for this chunk");
+                None
+            }
+        }
+    }
 }
 
 #[async_trait::async_trait]
 impl super::AsrEngine for VoskAsr {
-    fn process(&mut self, samples: &[i16]) -> Result<Option<super::AsrResult>> {
+    fn process(&mut self, samples: &[i16]) -> Result<Option<super::AsrEvent>> {
         // Discard audio when paused
         if self.paused {
             return Ok(None);
         }
 
-        let state = self.recognizer.accept_waveform(samples);
-
-        match state {
-            vosk::DecodingState::Finalized => {
-                let result = self.recognizer.final_result();
-                if let Some(single) = result.single() {
-                    if let Some(text) = extract_text(single.text) {
-                        // Calculate average word confidence
-                        let avg_confidence = if single.result.is_empty() {
-                            1.0f32 // Default if no word-level info
-                        } else {
-                            let sum: f32 = single.result.iter().map(|w| w.conf).sum();
-                            sum / single.result.len() as f32
-                        };
-
-                        // Apply confidence filter (Chisholm guardrail)
-                        if avg_confidence < super::MIN_CONFIDENCE {
-                            info!(
-                                "🔇 Rejecting low-confidence ASR ({:.2}): '{}'",
-                                avg_confidence, text
-                            );
-                            return Ok(None);
-                        }
-
-                        return Ok(Some(super::AsrResult {
-                            text,
-                            confidence: avg_confidence,
-                        }));
-                    }
+        match self.vad.process(samples) {
+            VadEvent::Silence => Ok(None),
+            VadEvent::UtteranceStart(audio) => Ok(self.accept(&audio)),
+            VadEvent::Speech => Ok(self.accept(samples)),
+            VadEvent::UtteranceEnd { .. } => {
+                // Finalize now instead of waiting for Vosk's own endpointer
+                if let Some(event @ super::AsrEvent::Final(_)) = self.accept(samples) {
+                    return Ok(Some(event));
                 }
+                Ok(self.take_final().map(super::AsrEvent::Final))
             }
-            vosk::DecodingState::Running => {
-                // Partial result - could log for debugging
-                debug!("Partial: {}", self.recognizer.partial_result().partial);
-            }
-            vosk::DecodingState::Failed => {
-                debug!("Decoding failed for this chunk");
+            VadEvent::Discarded => {
+                self.recognizer.reset();
+                Ok(None)
             }
         }
-
-        Ok(None)
     }
 
     fn reset(&mut self) {
         self.recognizer.reset();
+        self.vad.reset();
     }
 
     fn pause(&mut self) {
         self.paused = true;
         self.recognizer.reset(); // Clear any partial recognition
+        self.vad.reset();
         debug!("🔇 ASR paused");
     }
 
@@ -133,16 +206,78 @@ impl super::AsrEngine for VoskAsr {
     fn is_paused(&self) -> bool {
         self.paused
     }
+
+    fn name(&self) -> &str {
+        "vosk"
+    }
+
+    fn set_grammar(&mut self, phrases: &[String]) -> Result<()> {
+        self.recognizer = build_recognizer(&self.model, phrases, self.max_alternatives)?;
+        self.vad.reset();
+        Ok(())
+    }
+}
+
+/// Create a recognizer, constrained to `grammar` unless it is empty
+fn build_recognizer(
+    model: &Model,
+    grammar: &[String],
+    max_alternatives: u16,
+) -> Result<Recognizer> {
+    let mut recognizer = if grammar.is_empty() {
+        info!("⚙️ Using open vocabulary");
+        Recognizer::new(model, SAMPLE_RATE).context("Failed to create Vosk recognizer")?
+    } else {
+        info!("⚙️ Using custom grammar ({} phrases)", grammar.len());
+        Recognizer::new_with_grammar(model, SAMPLE_RATE, grammar)
+            .context("Failed to create Vosk recognizer with grammar")?
+    };
+
+    if max_alternatives > 0 {
+        // Vosk counts the top result as one of the alternatives
+        recognizer.set_max_alternatives(max_alternatives + 1);
+    }
+
+    Ok(recognizer)
 }
 
 /// Extract text from Vosk result, filtering empty results
+///
+/// Any out-of-grammar word ([unk]) discards the whole result: dropping just the
+/// token would turn "don't deploy gear" into "deploy gear".
 fn extract_text(text: &str) -> Option<String> {
-    let trimmed = text.trim();
-    if trimmed.is_empty() {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    if words.is_empty() || words.contains(&super::grammar::UNKNOWN_TOKEN) {
         None
     } else {
-        Some(trimmed.to_string())
+        Some(words.join(" "))
+    }
+}
+
+/// Posterior per distinct text, best first
+///
+/// Vosk repeats a text when alternatives differ only in timing; those share one
+/// hypothesis, so their probability is summed rather than split.
+fn merge_nbest(scored: Vec<(String, f32)>) -> Vec<super::AsrAlternative> {
+    let scores: Vec<f32> = scored.iter().map(|(_, s)| *s).collect();
+    let mut merged: Vec<super::AsrAlternative> = Vec::new();
+    for ((text, _), confidence) in scored.into_iter().zip(posteriors(&scores)) {
+        let text = text.to_lowercase();
+        match merged.iter_mut().find(|a| a.text == text) {
+            Some(existing) => existing.confidence += confidence,
+            None => merged.push(super::AsrAlternative { text, confidence }),
+        }
     }
+    merged.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    merged
+}
+
+/// Softmax over log-likelihood scores
+fn posteriors(scores: &[f32]) -> Vec<f32> {
+    let max = scores.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let weights: Vec<f32> = scores.iter().map(|s| (s - max).exp()).collect();
+    let total: f32 = weights.iter().sum();
+    weights.into_iter().map(|w| w / total).collect()
 }
 
 #[cfg(test)]
@@ -155,5 +290,35 @@ mod tests {
         assert_eq!(extract_text("  "), None);
         assert_eq!(extract_text("hello"), Some("hello".to_string()));
         assert_eq!(extract_text("  hello  "), Some("hello".to_string()));
+        assert_eq!(extract_text("[unk]"), None);
+        assert_eq!(extract_text("gear [unk] down"), None);
+        assert_eq!(extract_text("[unk] deploy gear"), None);
+    }
+
+    #[test]
+    fn test_posteriors() {
+        let p = posteriors(&[240.0, 240.0 - 2f32.ln()]);
+        assert!((p[0] - 2.0 / 3.0).abs() < 1e-4);
+        assert!((p[1] - 1.0 / 3.0).abs() < 1e-4);
+
+        let p = posteriors(&[100.0]);
+        assert_eq!(p, vec![1.0]);
+    }
+
+    #[test]
+    fn test_merge_nbest_sums_duplicate_texts() {
+        // Same words with three different timings, plus one real alternative
+        let merged = merge_nbest(vec![
+            ("landing gear".to_string(), 240.0),
+            ("landing gear".to_string(), 240.0),
+            ("landing gear".to_string(), 239.5),
+            ("landing here".to_string(), 238.0),
+        ]);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].text, "landing gear");
+        // Split three ways it would be ~0.33 each and fail the gate
+        assert!(merged[0].confidence > 0.9);
+        assert!(merged[0].confidence > crate::asr::MIN_CONFIDENCE);
+        assert!((merged[0].confidence + merged[1].confidence - 1.0).abs() < 1e-4);
     }
 }
@@ -0,0 +1,327 @@
+//! Wake word spotting
+//!
+//! A lightweight keyword spotter that runs on every audio chunk while the
+//! assistant is idle, so the full ASR engine only sees audio after a wake word.
+//! Two backends:
+//! - Vosk with a tiny grammar of just the wake phrases
+//! - A Wyoming wake service (e.g. wyoming-openwakeword) via `detect`/`detection`
+
+use super::wyoming::{read_event, samples_to_bytes, write_event};
+use crate::audio::{VadEvent, VoiceActivityDetector};
+use crate::config::Config;
+use anyhow::{Context, Result};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
+use vosk::Recognizer;
+
+const SAMPLE_RATE: u32 = 16000;
+
+/// Delay before reconnecting to a Wyoming wake service that went away
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// A keyword spotter fed with 16 kHz mono audio
+pub trait WakeWordSpotter: Send {
+    /// Feed one chunk, returning the wake phrase (as configured) once detected
+    fn process(&mut self, samples: &[i16]) -> Result<Option<String>>;
+
+    /// Forget any partially heard phrase
+    fn reset(&mut self);
+}
+
+/// Create the configured spotter
+///
+/// Returns `None` for `wake_engine = "asr"` (wake words matched in full transcripts)
+/// or when there are no wake words.
+pub fn create_spotter(config: &Config) -> Result<Option<Box<dyn WakeWordSpotter>>> {
+    let phrases: Vec<String> = config
+        .wake_word_bindings()
+        .into_iter()
+        .map(|b| b.phrase)
+        .collect();
+    if phrases.is_empty() {
+        return Ok(None);
+    }
+
+    match config.wake_engine.as_str() {
+        "asr" => Ok(None),
+        "wyoming" => Ok(Some(Box::new(WyomingWakeSpotter::new(
+            &config.wyoming_host,
+            config.wyoming_wake_port,
+            phrases,
+        )))),
+        _ => Ok(Some(Box::new(VoskWakeSpotter::new(
+            &config.vosk_model_path,
+            phrases,
+        )?))),
+    }
+}
+
+/// Find which wake phrase starts `text` (whole words, case-insensitive)
+///
+/// Only the start of the utterance counts, so "my computer is slow" doesn't
+/// wake the assistant.
+fn find_phrase(phrases: &[String], text: &str) -> Option<String> {
+    let padded = format!("{} ", text.trim().to_lowercase());
+    phrases
+        .iter()
+        .find(|p| padded.starts_with(&format!("{} ", p.to_lowercase())))
+        .cloned()
+}
+
+/// Vosk recognizer restricted to the wake phrases
+pub struct VoskWakeSpotter {
+    recognizer: Recognizer,
+    phrases: Vec<String>,
+    /// Skips silence so the recognizer idles most of the time
+    vad: VoiceActivityDetector,
+}
+
+impl VoskWakeSpotter {
+    pub fn new(model_path: &str, phrases: Vec<String>) -> Result<Self> {
+        if !std::path::Path::new(model_path).exists() {
+            anyhow::bail!("Vosk model not found at {}", model_path);
+        }
+        // Shared with the main recognizer rather than loaded twice
+        let model = super::vosk::load_model(model_path)?;
+
+        let mut grammar: Vec<String> = phrases.iter().map(|p| p.to_lowercase()).collect();
+        grammar.push(super::grammar::UNKNOWN_TOKEN.to_string());
+        let recognizer = Recognizer::new_with_grammar(&model, SAMPLE_RATE as f32, &grammar)
+            .context("Failed to create wake word recognizer")?;
+
+        info!("👂 Vosk wake word spotter ready ({:?})", phrases);
+        Ok(Self {
+            recognizer,
+            phrases,
+            vad: VoiceActivityDetector::default(),
+        })
+    }
+
+    fn accept(&mut self, samples: &[i16]) -> Option<String> {
+        let text = match self.recognizer.accept_waveform(samples) {
+            vosk::DecodingState::Finalized => self
+                .recognizer
+                .result()
+                .single()
+                .map(|r| r.text.to_string())
+                .unwrap_or_default(),
+            // Partials let us react before the phrase is endpointed
+            vosk::DecodingState::Running => self.recognizer.partial_result().partial.to_string(),
+            vosk::DecodingState::Failed => String::new(),
+        };
+
+        let detected = find_phrase(&self.phrases, &text)?;
+        self.recognizer.reset();
+        self.vad.reset();
+        Some(detected)
+    }
+}
+
+impl WakeWordSpotter for VoskWakeSpotter {
+    fn process(&mut self, samples: &[i16]) -> Result<Option<String>> {
+        Ok(match self.vad.process(samples) {
+            VadEvent::Silence => None,
+            VadEvent::UtteranceStart(audio) => self.accept(&audio),
+            VadEvent::Speech => self.accept(samples),
+            VadEvent::UtteranceEnd { .. } | VadEvent::Discarded => {
+                let detected = self.accept(samples);
+                self.recognizer.reset();
+                detected
+            }
+        })
+    }
+
+    fn reset(&mut self) {
+        self.recognizer.reset();
+        self.vad.reset();
+    }
+}
+
+/// Client for a Wyoming wake word service
+///
+/// Audio is streamed continuously by a background worker; `detection` events
+/// are picked up on later `process()` calls.
+pub struct WyomingWakeSpotter {
+    audio: mpsc::UnboundedSender<Vec<i16>>,
+    detections: mpsc::UnboundedReceiver<String>,
+}
+
+impl WyomingWakeSpotter {
+    pub fn new(host: &str, port: u16, phrases: Vec<String>) -> Self {
+        let (audio_tx, audio_rx) = mpsc::unbounded_channel();
+        let (det_tx, det_rx) = mpsc::unbounded_channel();
+        let host = host.to_string();
+
+        let spawned = std::thread::Builder::new()
+            .name("wyoming-wake".into())
+            .spawn(move || {
+                match tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                {
+                    Ok(rt) => rt.block_on(wake_worker(host, port, phrases, audio_rx, det_tx)),
+                    Err(e) => warn!("⚠️ Failed to start Wyoming wake runtime: {}", e),
+                }
+            });
+        if let Err(e) = spawned {
+            warn!("⚠️ Failed to spawn Wyoming wake worker: {}", e);
+        }
+
+        Self {
+            audio: audio_tx,
+            detections: det_rx,
+        }
+    }
+}
+
+impl WakeWordSpotter for WyomingWakeSpotter {
+    fn process(&mut self, samples: &[i16]) -> Result<Option<String>> {
+        if self.audio.send(samples.to_vec()).is_err() {
+            anyhow::bail!("Wyoming wake worker stopped");
+        }
+        Ok(self.detections.try_recv().ok())
+    }
+
+    fn reset(&mut self) {
+        while self.detections.try_recv().is_ok() {}
+    }
+}
+
+/// Stream audio to the wake service, reconnecting when it goes away
+async fn wake_worker(
+    host: String,
+    port: u16,
+    phrases: Vec<String>,
+    mut audio: mpsc::UnboundedReceiver<Vec<i16>>,
+    detections: mpsc::UnboundedSender<String>,
+) {
+    let mut retry_at = Instant::now();
+
+    loop {
+        // Drop audio while disconnected instead of buffering stale speech
+        while Instant::now() < retry_at {
+            if audio.recv().await.is_none() {
+                return;
+            }
+        }
+
+        match wake_session(&host, port, &phrases, &mut audio, &detections).await {
+            Ok(()) => return, // Audio channel closed
+            Err(e) => {
+                warn!("⚠️ Wyoming wake service: {:#}", e);
+                retry_at = Instant::now() + RECONNECT_DELAY;
+            }
+        }
+    }
+}
+
+/// One connection to the wake service; returns Ok when the audio channel closes
+async fn wake_session(
+    host: &str,
+    port: u16,
+    phrases: &[String],
+    audio: &mut mpsc::UnboundedReceiver<Vec<i16>>,
+    detections: &mpsc::UnboundedSender<String>,
+) -> Result<()> {
+    let stream = tokio::time::timeout(Duration::from_secs(2), TcpStream::connect((host, port)))
+        .await
+        .context("Timed out connecting")?
+        .context("Failed to connect")?;
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(reader);
+
+    // Model names are usually the wake phrase with underscores ("hey_jarvis")
+    let names: Vec<String> = phrases
+        .iter()
+        .map(|p| p.to_lowercase().replace(' ', "_"))
+        .collect();
+    let format = serde_json::json!({ "rate": SAMPLE_RATE, "width": 2, "channels": 1 });
+    write_event(
+        &mut writer,
+        "detect",
+        Some(serde_json::json!({ "names": names })),
+        None,
+    )
+    .await?;
+    write_event(&mut writer, "audio-start", Some(format.clone()), None).await?;
+    writer.flush().await?;
+    info!("👂 Connected to Wyoming wake service at {}:{}", host, port);
+
+    // Events are read on their own task: read_event isn't cancel-safe inside select!
+    let phrases = phrases.to_vec();
+    let detections = detections.clone();
+    let mut events = tokio::spawn(async move {
+        while let Some(event) = read_event(&mut reader).await? {
+            if event.event_type != "detection" {
+                debug!("Wyoming wake: ignoring '{}' event", event.event_type);
+                continue;
+            }
+            let name = event
+                .data
+                .get("name")
+                .and_then(|n| n.as_str())
+                .unwrap_or_default();
+            let phrase = find_phrase(&phrases, &name.replace('_', " "))
+                .or_else(|| (phrases.len() == 1).then(|| phrases[0].clone()));
+            match phrase {
+                Some(phrase) => {
+                    let _ = detections.send(phrase);
+                }
+                None => debug!("Wyoming wake: unmapped detection '{}'", name),
+            }
+        }
+        anyhow::bail!("Server closed the connection")
+    });
+
+    let result = loop {
+        tokio::select! {
+            chunk = audio.recv() => {
+                let Some(chunk) = chunk else { break Ok(()) };
+                let bytes = samples_to_bytes(&chunk);
+                let sent = async {
+                    write_event(&mut writer, "audio-chunk", Some(format.clone()), Some(&bytes)).await?;
+                    writer.flush().await?;
+                    Ok::<_, anyhow::Error>(())
+                };
+                if let Err(e) = sent.await {
+                    break Err(e);
+                }
+            }
+            finished = &mut events => {
+                break match finished {
+                    Ok(result) => result,
+                    Err(e) => Err(e.into()),
+                };
+            }
+        }
+    };
+
+    events.abort();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_phrase_whole_words() {
+        let phrases = vec!["Computer".to_string(), "hey jarvis".to_string()];
+        assert_eq!(
+            find_phrase(&phrases, "computer landing gear"),
+            Some("Computer".to_string())
+        );
+        assert_eq!(
+            find_phrase(&phrases, " hey jarvis"),
+            Some("hey jarvis".to_string())
+        );
+        assert_eq!(find_phrase(&phrases, "computers are great"), None);
+        // Mentioned mid-sentence (other words come back as [unk])
+        assert_eq!(find_phrase(&phrases, "my computer is slow"), None);
+        assert_eq!(find_phrase(&phrases, "[unk] computer [unk]"), None);
+        assert_eq!(find_phrase(&phrases, "[unk]"), None);
+    }
+}
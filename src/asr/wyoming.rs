@@ -1,16 +1,24 @@
 //! Wyoming Protocol Client
 //!
 //! Implements the Wyoming protocol for external ASR services.
-//! Wyoming is a simple protocol where events are JSON lines over TCP.
+//! Wyoming is a simple protocol where events are JSON lines over TCP,
+//! optionally followed by a binary payload (raw PCM for audio chunks).
 //!
 //! Reference: https://github.com/rhasspy/wyoming
 
+use crate::audio::{VadEvent, VoiceActivityDetector};
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::io::{
+    AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader,
+};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
 use tokio::net::TcpStream;
-use tracing::{debug, info, warn};
+use tokio::sync::mpsc;
+use tracing::{debug, error, info, warn};
 
 /// Wyoming event types
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -103,11 +111,270 @@ mod base64_bytes {
     }
 }
 
+/// Largest `data_length` or `payload_length` accepted from a peer, so a bad
+/// header can't make us allocate gigabytes (a minute of 16 kHz audio is ~2 MB)
+const MAX_EVENT_LENGTH: usize = 8 * 1024 * 1024;
+
+/// Timeout for opening a TCP connection to the server
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Timeout for the server to return a transcript of a short command after
+/// audio-stop; see [`transcript_timeout`] for longer utterances
+///
+/// Kept short: a command that arrives later than this is no use, and a stalled
+/// server should hand over to the next engine instead of eating utterances.
+const TRANSCRIPT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long a server that failed to transcribe stays unhealthy before it is
+/// retried (it may still accept connections while stuck)
+const STALL_BACKOFF: Duration = Duration::from_secs(30);
+
+/// How often a disconnected worker retries the server
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(3);
+
+/// A decoded Wyoming event (header, data and binary payload)
+#[derive(Debug, Clone)]
+pub struct RawEvent {
+    pub event_type: String,
+    pub data: serde_json::Value,
+    pub payload: Vec<u8>,
+}
+
+/// Write a single Wyoming event: a JSON header line followed by an optional binary payload
+pub async fn write_event<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    event_type: &str,
+    data: Option<serde_json::Value>,
+    payload: Option<&[u8]>,
+) -> Result<()> {
+    let mut header = serde_json::json!({ "type": event_type });
+    if let Some(data) = data {
+        header["data"] = data;
+    }
+    if let Some(payload) = payload {
+        header["payload_length"] = payload.len().into();
+    }
+
+    writer.write_all(header.to_string().as_bytes()).await?;
+    writer.write_all(b"\n").await?;
+    if let Some(payload) = payload {
+        writer.write_all(payload).await?;
+    }
+    Ok(())
+}
+
+/// Read a single Wyoming event, returning `None` when the peer closed the connection
+///
+/// Handles both inline `data` and the newer `data_length` framing.
+pub async fn read_event<R: AsyncBufRead + Unpin>(reader: &mut R) -> Result<Option<RawEvent>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line).await? == 0 {
+        return Ok(None);
+    }
+
+    let header: serde_json::Value =
+        serde_json::from_str(line.trim()).context("Invalid Wyoming event header")?;
+    let event_type = header
+        .get("type")
+        .and_then(|t| t.as_str())
+        .context("Wyoming event without type")?
+        .to_string();
+
+    let mut data = header
+        .get("data")
+        .cloned()
+        .unwrap_or_else(|| serde_json::json!({}));
+
+    let data_length = header
+        .get("data_length")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0) as usize;
+    check_length("data_length", data_length)?;
+    if data_length > 0 {
+        let mut buf = vec![0u8; data_length];
+        reader.read_exact(&mut buf).await?;
+        let extra: serde_json::Value =
+            serde_json::from_slice(&buf).context("Invalid Wyoming event data")?;
+        match (data.as_object_mut(), extra) {
+            (Some(obj), serde_json::Value::Object(extra)) => obj.extend(extra),
+            (_, extra) => data = extra,
+        }
+    }
+
+    let payload_length = header
+        .get("payload_length")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0) as usize;
+    check_length("payload_length", payload_length)?;
+    let mut payload = vec![0u8; payload_length];
+    if payload_length > 0 {
+        reader.read_exact(&mut payload).await?;
+    }
+
+    Ok(Some(RawEvent {
+        event_type,
+        data,
+        payload,
+    }))
+}
+
+/// Reject a header length over [`MAX_EVENT_LENGTH`]
+fn check_length(field: &str, length: usize) -> Result<()> {
+    if length > MAX_EVENT_LENGTH {
+        anyhow::bail!(
+            "Wyoming event {} of {} bytes exceeds the {} byte limit",
+            field,
+            length,
+            MAX_EVENT_LENGTH
+        );
+    }
+    Ok(())
+}
+
+/// Convert i16 samples to little-endian PCM bytes
+pub(super) fn samples_to_bytes(samples: &[i16]) -> Vec<u8> {
+    samples.iter().flat_map(|s| s.to_le_bytes()).collect()
+}
+
+/// A single TCP connection to a Wyoming ASR server
+struct Connection {
+    reader: BufReader<OwnedReadHalf>,
+    writer: OwnedWriteHalf,
+}
+
+impl Connection {
+    async fn open(host: &str, port: u16) -> Result<Self> {
+        let stream = tokio::time::timeout(CONNECT_TIMEOUT, TcpStream::connect((host, port)))
+            .await
+            .context("Timed out connecting to Wyoming server")?
+            .context("Failed to connect to Wyoming server")?;
+        let _ = stream.set_nodelay(true);
+
+        let (reader, writer) = stream.into_split();
+        Ok(Self {
+            reader: BufReader::new(reader),
+            writer,
+        })
+    }
+
+    async fn audio_start(&mut self, rate: u32) -> Result<()> {
+        let data = serde_json::json!({ "rate": rate, "width": 2, "channels": 1 });
+        write_event(&mut self.writer, "audio-start", Some(data), None).await?;
+        self.writer.flush().await?;
+        Ok(())
+    }
+
+    async fn audio_chunk(&mut self, rate: u32, audio: &[u8]) -> Result<()> {
+        let data = serde_json::json!({ "rate": rate, "width": 2, "channels": 1 });
+        write_event(&mut self.writer, "audio-chunk", Some(data), Some(audio)).await?;
+        self.writer.flush().await?;
+        Ok(())
+    }
+
+    /// Send audio-stop and wait up to `timeout` for the transcript
+    async fn finish(&mut self, timeout: Duration) -> Result<(String, Vec<super::AsrAlternative>)> {
+        write_event(&mut self.writer, "audio-stop", None, None).await?;
+        self.writer.flush().await?;
+
+        tokio::time::timeout(timeout, async {
+            while let Some(event) = read_event(&mut self.reader).await? {
+                if event.event_type == "transcript" {
+                    return Ok::<_, anyhow::Error>(parse_transcript(&event.data));
+                }
+                debug!("Wyoming: ignoring '{}' event", event.event_type);
+            }
+            warn!("Wyoming server closed the connection without a transcript");
+            Ok((String::new(), Vec::new()))
+        })
+        .await
+        .context("Timeout waiting for transcript")?
+    }
+}
+
+/// How long to wait for the transcript of `samples` of audio at `rate`
+///
+/// Servers take longer on longer utterances, so each second of audio adds a
+/// second to [`TRANSCRIPT_TIMEOUT`].
+fn transcript_timeout(samples: usize, rate: u32) -> Duration {
+    TRANSCRIPT_TIMEOUT + Duration::from_secs_f64(samples as f64 / rate.max(1) as f64)
+}
+
+/// Split a transcript event into the top text and any n-best alternatives
+///
+/// The core protocol only defines `text`. Servers that support n-best can add an
+/// `alternatives` array of `{"text", "confidence"}` objects (or plain strings),
+/// ordered best first.
+fn parse_transcript(data: &serde_json::Value) -> (String, Vec<super::AsrAlternative>) {
+    let text = data
+        .get("text")
+        .and_then(|t| t.as_str())
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+
+    let alternatives = data
+        .get("alternatives")
+        .and_then(|a| a.as_array())
+        .map(|list| {
+            list.iter()
+                .filter_map(|alt| {
+                    let (text, confidence) = match alt {
+                        serde_json::Value::String(t) => (t.as_str(), None),
+                        _ => (
+                            alt.get("text")?.as_str()?,
+                            alt.get("confidence").and_then(|c| c.as_f64()),
+                        ),
+                    };
+                    let text = text.trim();
+                    if text.is_empty() {
+                        return None;
+                    }
+                    Some(super::AsrAlternative {
+                        text: text.to_string(),
+                        // Unscored alternatives rank below the (unscored) top transcript
+                        confidence: confidence.unwrap_or(0.5) as f32,
+                    })
+                })
+                .filter(|alt| alt.text != text)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    (text, alternatives)
+}
+
+/// Commands sent from `process()` to the streaming worker
+enum StreamCommand {
+    /// Speech onset: send audio-start on the (possibly pre-opened) connection
+    Start,
+    /// Audio belonging to the current utterance
+    Chunk(Vec<i16>),
+    /// End of utterance: send audio-stop and wait for the transcript
+    Stop,
+    /// Discard the in-flight utterance
+    Abort,
+}
+
+/// Handles to the background streaming worker
+struct StreamHandle {
+    commands: mpsc::UnboundedSender<StreamCommand>,
+    results: mpsc::UnboundedReceiver<super::AsrResult>,
+}
+
 /// Wyoming client for ASR services
+///
+/// As an `AsrEngine` it streams microphone audio to the server over a persistent
+/// connection owned by a background worker. Utterances are segmented locally by
+/// the VAD, and each `transcript` event is returned from a later `process()` call.
 pub struct WyomingClient {
     host: String,
     port: u16,
     sample_rate: u32,
+    stream: Option<StreamHandle>,
+    paused: bool,
+    vad: VoiceActivityDetector,
+    /// Whether the worker can currently reach the server
+    healthy: Arc<AtomicBool>,
 }
 
 impl WyomingClient {
@@ -117,6 +384,11 @@ impl WyomingClient {
             host: host.to_string(),
             port,
             sample_rate: 16000,
+            stream: None,
+            paused: false,
+            vad: VoiceActivityDetector::default(),
+            // Optimistic until the worker's first connection attempt says otherwise
+            healthy: Arc::new(AtomicBool::new(true)),
         }
     }
 
@@ -136,101 +408,283 @@ impl WyomingClient {
 
     /// Transcribe audio data
     ///
-    /// Sends audio to Wyoming server and returns transcript
+    /// Sends a complete block of 16-bit PCM to the Wyoming server and returns the transcript
     pub async fn transcribe(&self, audio_data: &[u8]) -> Result<String> {
-        let stream = TcpStream::connect((&*self.host, self.port))
-            .await
-            .context("Failed to connect to Wyoming server")?;
-
-        let (reader, mut writer) = stream.into_split();
-        let mut reader = BufReader::new(reader);
+        let mut conn = Connection::open(&self.host, self.port).await?;
 
         // Send Describe (handshake)
-        let describe = serde_json::json!({"type": "describe"});
-        writer.write_all(describe.to_string().as_bytes()).await?;
-        writer.write_all(b"\n").await?;
-        writer.flush().await?;
-
-        // Read Info response
-        let mut line = String::new();
-        reader.read_line(&mut line).await?;
-        debug!("Wyoming handshake: {}", line.trim());
-
-        // Send AudioStart
-        let audio_start = serde_json::json!({
-            "type": "audio-start",
-            "data": {
-                "rate": self.sample_rate,
-                "width": 2,
-                "channels": 1
-            }
-        });
-        writer.write_all(audio_start.to_string().as_bytes()).await?;
-        writer.write_all(b"\n").await?;
-
-        // Send AudioChunk
-        let audio_chunk = serde_json::json!({
-            "type": "audio-chunk",
-            "data": {
-                "rate": self.sample_rate,
-                "width": 2,
-                "channels": 1,
-                "audio": base64::Engine::encode(&base64::engine::general_purpose::STANDARD, audio_data),
-                "timestamp": 0
-            }
-        });
-        writer.write_all(audio_chunk.to_string().as_bytes()).await?;
-        writer.write_all(b"\n").await?;
+        write_event(&mut conn.writer, "describe", None, None).await?;
+        conn.writer.flush().await?;
+        if let Some(info) = read_event(&mut conn.reader).await? {
+            debug!("Wyoming handshake: {} {}", info.event_type, info.data);
+        }
 
-        // Send AudioStop
-        let audio_stop = serde_json::json!({"type": "audio-stop"});
-        writer.write_all(audio_stop.to_string().as_bytes()).await?;
-        writer.write_all(b"\n").await?;
-        writer.flush().await?;
+        conn.audio_start(self.sample_rate).await?;
+        conn.audio_chunk(self.sample_rate, audio_data).await?;
 
         debug!(
             "Sent audio ({} bytes), waiting for transcript...",
             audio_data.len()
         );
 
-        // Read Transcript response (with timeout)
-        let timeout = Duration::from_secs(30);
-        let transcript = tokio::time::timeout(timeout, async {
-            loop {
-                let mut line = String::new();
-                if reader.read_line(&mut line).await? == 0 {
-                    break;
-                }
+        let timeout = transcript_timeout(audio_data.len() / 2, self.sample_rate);
+        let (transcript, _) = conn.finish(timeout).await?;
+        info!("📝 Wyoming transcript: '{}'", transcript);
+        Ok(transcript)
+    }
+
+    /// Spawn the streaming worker on first use
+    fn ensure_stream(&mut self) -> Result<&StreamHandle> {
+        if self.stream.is_none() {
+            let (cmd_tx, cmd_rx) = mpsc::unbounded_channel();
+            let (res_tx, res_rx) = mpsc::unbounded_channel();
+            let host = self.host.clone();
+            let port = self.port;
+            let rate = self.sample_rate;
+            let healthy = self.healthy.clone();
+
+            std::thread::Builder::new()
+                .name("wyoming-asr".into())
+                .spawn(move || {
+                    let runtime = match tokio::runtime::Builder::new_current_thread()
+                        .enable_all()
+                        .build()
+                    {
+                        Ok(rt) => rt,
+                        Err(e) => {
+                            error!("❌ Failed to start Wyoming runtime: {}", e);
+                            return;
+                        }
+                    };
+                    runtime.block_on(stream_worker(host, port, rate, cmd_rx, res_tx, healthy));
+                })
+                .context("Failed to spawn Wyoming worker thread")?;
+
+            info!(
+                "🔌 Wyoming streaming ASR started ({}:{})",
+                self.host, self.port
+            );
+            self.stream = Some(StreamHandle {
+                commands: cmd_tx,
+                results: res_rx,
+            });
+        }
+
+        self.stream.as_ref().context("Wyoming worker not available")
+    }
+
+    fn send(&mut self, command: StreamCommand) {
+        let failed = self
+            .stream
+            .as_ref()
+            .is_some_and(|s| s.commands.send(command).is_err());
+        if failed {
+            warn!("⚠️ Wyoming worker stopped, restarting on next chunk");
+            self.stream = None;
+            self.vad.reset();
+        }
+    }
+
+    /// Drop the current utterance and any buffered audio
+    fn discard_utterance(&mut self) {
+        if self.vad.is_active() {
+            self.send(StreamCommand::Abort);
+        }
+        self.vad.reset();
+    }
+
+    /// Run one chunk through the VAD and forward it to the worker
+    fn segment(&mut self, samples: &[i16]) {
+        match self.vad.process(samples) {
+            VadEvent::Silence => {}
+            VadEvent::UtteranceStart(audio) => {
+                self.send(StreamCommand::Start);
+                self.send(StreamCommand::Chunk(audio));
+            }
+            VadEvent::Speech => self.send(StreamCommand::Chunk(samples.to_vec())),
+            VadEvent::UtteranceEnd { .. } => {
+                self.send(StreamCommand::Chunk(samples.to_vec()));
+                self.send(StreamCommand::Stop);
+            }
+            VadEvent::Discarded => self.send(StreamCommand::Abort),
+        }
+    }
+}
 
-                if let Ok(event) = serde_json::from_str::<serde_json::Value>(&line) {
-                    if event.get("type").and_then(|t| t.as_str()) == Some("transcript") {
-                        if let Some(data) = event.get("data") {
-                            if let Some(text) = data.get("text").and_then(|t| t.as_str()) {
-                                return Ok::<_, anyhow::Error>(text.to_string());
-                            }
+/// Background worker owning the server connection
+///
+/// wyoming-faster-whisper closes the connection after each transcript, so a fresh
+/// connection is opened right away to keep the next utterance free of connect latency.
+async fn stream_worker(
+    host: String,
+    port: u16,
+    rate: u32,
+    mut commands: mpsc::UnboundedReceiver<StreamCommand>,
+    results: mpsc::UnboundedSender<super::AsrResult>,
+    healthy: Arc<AtomicBool>,
+) {
+    let open = |host: String| {
+        let healthy = healthy.clone();
+        async move {
+            let opened = Connection::open(&host, port).await;
+            if let Err(e) = &opened {
+                if healthy.swap(false, Ordering::Relaxed) {
+                    warn!("⚠️ Wyoming server unreachable: {:#}", e);
+                }
+            } else if !healthy.swap(true, Ordering::Relaxed) {
+                info!("🔌 Wyoming server reachable at {}:{}", host, port);
+            }
+            opened.ok()
+        }
+    };
+
+    let mut conn = open(host.clone()).await;
+    let mut streaming = false;
+    // Samples sent for the current utterance, to size the transcript timeout
+    let mut streamed = 0;
+    // Set after a failed transcript; no reconnects (which would report healthy) until then
+    let mut stalled_until: Option<tokio::time::Instant> = None;
+    let mut reconnect = tokio::time::interval(RECONNECT_INTERVAL);
+
+    loop {
+        let command = tokio::select! {
+            command = commands.recv() => match command {
+                Some(command) => command,
+                None => break,
+            },
+            // Keep probing while disconnected so health recovers without traffic
+            _ = reconnect.tick(), if conn.is_none() && !streaming => {
+                if stalled_until.is_some_and(|until| tokio::time::Instant::now() < until) {
+                    continue;
+                }
+                stalled_until = None;
+                conn = open(host.clone()).await;
+                continue;
+            }
+        };
+
+        match command {
+            StreamCommand::Start => {
+                streaming = false;
+                streamed = 0;
+                if conn.is_none() && stalled_until.is_none() {
+                    conn = open(host.clone()).await;
+                }
+                if let Some(c) = conn.as_mut() {
+                    match c.audio_start(rate).await {
+                        Ok(()) => streaming = true,
+                        Err(e) => {
+                            warn!("⚠️ Wyoming audio-start failed: {}", e);
+                            conn = None;
                         }
                     }
                 }
             }
-            Ok(String::new())
-        })
-        .await
-        .context("Timeout waiting for transcript")??;
-
-        info!("📝 Wyoming transcript: '{}'", transcript);
-        Ok(transcript)
+            StreamCommand::Chunk(samples) => {
+                if !streaming {
+                    continue;
+                }
+                streamed += samples.len();
+                if let Some(c) = conn.as_mut() {
+                    if let Err(e) = c.audio_chunk(rate, &samples_to_bytes(&samples)).await {
+                        warn!("⚠️ Wyoming audio-chunk failed: {}", e);
+                        healthy.store(false, Ordering::Relaxed);
+                        conn = None;
+                        streaming = false;
+                    }
+                }
+            }
+            StreamCommand::Stop => {
+                if !streaming {
+                    continue;
+                }
+                streaming = false;
+                if let Some(mut c) = conn.take() {
+                    let finished = c.finish(transcript_timeout(streamed, rate)).await;
+                    if finished.is_err() {
+                        stalled_until = Some(tokio::time::Instant::now() + STALL_BACKOFF);
+                    }
+                    match finished {
+                        Ok((text, alternatives)) if !text.is_empty() => {
+                            info!("📝 Wyoming transcript: '{}'", text);
+                            // Wyoming transcripts carry no confidence score
+                            let _ = results.send(super::AsrResult {
+                                text,
+                                confidence: 1.0,
+                                alternatives,
+                            });
+                        }
+                        Ok(_) => debug!("Wyoming returned an empty transcript"),
+                        Err(e) => {
+                            warn!("⚠️ Wyoming transcription failed: {}", e);
+                            healthy.store(false, Ordering::Relaxed);
+                        }
+                    }
+                }
+                if stalled_until.is_none() {
+                    conn = open(host.clone()).await;
+                }
+            }
+            StreamCommand::Abort => {
+                if streaming {
+                    streaming = false;
+                    conn = None;
+                }
+            }
+        }
     }
+
+    debug!("Wyoming worker stopped");
 }
 
 #[async_trait::async_trait]
 impl super::AsrEngine for WyomingClient {
-    fn process(&mut self, _samples: &[i16]) -> Result<Option<super::AsrResult>> {
-        // Wyoming stream processing is complex and requires persistent async connection.
-        // For now, Wyoming is handled via the separate transcribe() method for full blocks.
-        Ok(None)
+    fn process(&mut self, samples: &[i16]) -> Result<Option<super::AsrEvent>> {
+        if self.paused {
+            return Ok(None);
+        }
+
+        self.ensure_stream()?;
+        self.segment(samples);
+
+        // Wyoming only reports complete transcripts, never partials
+        Ok(self
+            .stream
+            .as_mut()
+            .and_then(|s| s.results.try_recv().ok())
+            .map(super::AsrEvent::Final))
+    }
+
+    fn reset(&mut self) {
+        self.discard_utterance();
+        if let Some(stream) = &mut self.stream {
+            while stream.results.try_recv().is_ok() {}
+        }
+    }
+
+    fn pause(&mut self) {
+        self.paused = true;
+        self.reset();
+        debug!("🔇 Wyoming ASR paused");
+    }
+
+    fn resume(&mut self) {
+        self.paused = false;
+        debug!("🔊 Wyoming ASR resumed");
+    }
+
+    fn is_paused(&self) -> bool {
+        self.paused
     }
 
-    fn reset(&mut self) {}
+    fn name(&self) -> &str {
+        "wyoming"
+    }
+
+    fn is_healthy(&mut self) -> bool {
+        // The worker does the probing, so it has to be running
+        self.ensure_stream().is_ok() && self.healthy.load(Ordering::Relaxed)
+    }
 }
 
 #[cfg(test)]
@@ -254,4 +708,93 @@ mod tests {
         });
         assert_eq!(audio_start["data"]["rate"], 16000);
     }
+
+    #[tokio::test]
+    async fn test_event_round_trip_with_payload() {
+        let (mut client, server) = tokio::io::duplex(1024);
+        let mut server = tokio::io::BufReader::new(server);
+
+        let audio = super::samples_to_bytes(&[1, -1, 256]);
+        let data = serde_json::json!({"rate": 16000, "width": 2, "channels": 1});
+        super::write_event(&mut client, "audio-chunk", Some(data), Some(&audio))
+            .await
+            .unwrap();
+
+        let event = super::read_event(&mut server).await.unwrap().unwrap();
+        assert_eq!(event.event_type, "audio-chunk");
+        assert_eq!(event.data["rate"], 16000);
+        assert_eq!(event.payload, vec![1, 0, 255, 255, 0, 1]);
+    }
+
+    #[tokio::test]
+    async fn test_read_event_with_data_length() {
+        use tokio::io::AsyncWriteExt;
+
+        let (mut client, server) = tokio::io::duplex(1024);
+        let mut server = tokio::io::BufReader::new(server);
+
+        let data = br#"{"text":"boost engines"}"#;
+        let header = format!(r#"{{"type":"transcript","data_length":{}}}"#, data.len());
+        client.write_all(header.as_bytes()).await.unwrap();
+        client.write_all(b"\n").await.unwrap();
+        client.write_all(data).await.unwrap();
+
+        let event = super::read_event(&mut server).await.unwrap().unwrap();
+        assert_eq!(event.event_type, "transcript");
+        assert_eq!(event.data["text"], "boost engines");
+        assert!(event.payload.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_read_event_rejects_huge_lengths() {
+        use tokio::io::AsyncWriteExt;
+
+        for field in ["data_length", "payload_length"] {
+            let (mut client, server) = tokio::io::duplex(1024);
+            let mut server = tokio::io::BufReader::new(server);
+            let header = format!(r#"{{"type":"audio-chunk","{}":{}}}"#, field, u64::MAX / 2);
+            client.write_all(header.as_bytes()).await.unwrap();
+            client.write_all(b"\n").await.unwrap();
+
+            let err = super::read_event(&mut server).await.unwrap_err();
+            assert!(err.to_string().contains(field));
+        }
+    }
+
+    #[test]
+    fn test_parse_transcript_alternatives() {
+        let data = serde_json::json!({
+            "text": " gear dawn ",
+            "alternatives": [
+                {"text": "gear down", "confidence": 0.4},
+                "gear town",
+                {"text": "gear dawn"},
+                {"confidence": 0.1}
+            ]
+        });
+
+        let (text, alternatives) = super::parse_transcript(&data);
+        assert_eq!(text, "gear dawn");
+        assert_eq!(alternatives.len(), 2);
+        assert_eq!(alternatives[0].text, "gear down");
+        assert!((alternatives[0].confidence - 0.4).abs() < f32::EPSILON);
+        assert_eq!(alternatives[1].text, "gear town");
+
+        let (_, none) = super::parse_transcript(&serde_json::json!({"text": "boost"}));
+        assert!(none.is_empty());
+    }
+
+    #[test]
+    fn test_transcript_timeout_scales_with_audio() {
+        use std::time::Duration;
+
+        assert_eq!(
+            super::transcript_timeout(0, 16000),
+            super::TRANSCRIPT_TIMEOUT
+        );
+        assert_eq!(
+            super::transcript_timeout(16000 * 10, 16000),
+            super::TRANSCRIPT_TIMEOUT + Duration::from_secs(10)
+        );
+    }
 }
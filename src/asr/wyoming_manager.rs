@@ -1,43 +1,296 @@
 //! Wyoming Server Manager
 //!
-//! Manages the lifecycle of the `wyoming-faster-whisper` server process.
+//! Supervises the `wyoming-faster-whisper` server process: starts it when
+//! nothing is listening on the configured port, restarts it with exponential
+//! backoff when it crashes, and captures its output into a rotated log.
+//! A server that was already running (e.g. a system service) is treated as
+//! external and only watched, never started or stopped.
 
 use crate::config::Config;
 use anyhow::{Context, Result};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Write};
 use std::net::TcpStream;
+use std::path::{Path, PathBuf};
 use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
 use std::time::{Duration, Instant};
 use tracing::{error, info, warn};
 
+/// How often the supervisor checks the process and the port
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// First restart delay; doubled after every consecutive failure
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Upper bound for the restart delay
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// A run this long counts as stable and resets the backoff
+const STABLE_UPTIME: Duration = Duration::from_secs(60);
+
+/// Warn if a freshly started server hasn't opened its port by then
+/// (the first start may download the model, so it isn't killed)
+const SLOW_START_WARNING: Duration = Duration::from_secs(30);
+
+/// Grace period between SIGTERM and SIGKILL on shutdown
+const TERMINATE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Rotate `wyoming_server.log` when it grows past this size
+const MAX_LOG_BYTES: u64 = 1024 * 1024;
+
+/// Rotated logs kept (`wyoming_server.log.1` .. `.N`)
+const LOG_BACKUPS: usize = 3;
+
 /// Check if a Wyoming server is running on the specified host:port
 pub fn is_server_running(host: &str, port: u16) -> bool {
     TcpStream::connect((host, port)).is_ok()
 }
 
-/// Start the wyoming-faster-whisper server
-pub fn start_server(config: &Config) -> Result<Child> {
-    let host = if config.wyoming_host == "localhost" {
-        "127.0.0.1"
+/// Lifecycle state of the Wyoming server
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServerState {
+    /// Supervisor not running
+    Stopped,
+    /// Our process is up but not accepting connections yet
+    Starting,
+    /// Our process is accepting connections
+    Running { pid: u32 },
+    /// A server we didn't start is listening on the port
+    External,
+    /// Waiting to (re)start after a crash or failed start
+    Restarting { attempt: u32, delay: Duration },
+}
+
+impl ServerState {
+    /// Whether clients can connect right now
+    pub fn is_healthy(&self) -> bool {
+        matches!(self, Self::Running { .. } | Self::External)
+    }
+
+    /// Short label for status reports
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Stopped => "stopped",
+            Self::Starting => "starting",
+            Self::Running { .. } => "running",
+            Self::External => "external",
+            Self::Restarting { .. } => "restarting",
+        }
+    }
+}
+
+/// Keeps a local Wyoming server alive for as long as it is held
+///
+/// Dropping the supervisor stops the server if we started it.
+pub struct WyomingSupervisor {
+    state: Arc<Mutex<ServerState>>,
+    shutdown: Arc<AtomicBool>,
+    thread_handle: Option<thread::JoinHandle<()>>,
+}
+
+impl WyomingSupervisor {
+    /// Start supervising the server described by `config`
+    pub fn start(config: &Config) -> Result<Self> {
+        let state = Arc::new(Mutex::new(ServerState::Starting));
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        let worker = Worker {
+            config: config.clone(),
+            host: local_host(&config.wyoming_host),
+            state: state.clone(),
+            shutdown: shutdown.clone(),
+        };
+        let handle = thread::Builder::new()
+            .name("wyoming-supervisor".into())
+            .spawn(move || worker.run())
+            .context("Failed to spawn Wyoming supervisor thread")?;
+
+        Ok(Self {
+            state,
+            shutdown,
+            thread_handle: Some(handle),
+        })
+    }
+
+    /// Current server state
+    pub fn state(&self) -> ServerState {
+        self.state
+            .lock()
+            .map(|s| s.clone())
+            .unwrap_or(ServerState::Stopped)
+    }
+
+    /// Stop supervising and terminate the server if it is ours
+    pub fn stop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        if let Some(handle) = self.thread_handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for WyomingSupervisor {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// State owned by the supervisor thread
+struct Worker {
+    config: Config,
+    host: String,
+    state: Arc<Mutex<ServerState>>,
+    shutdown: Arc<AtomicBool>,
+}
+
+impl Worker {
+    fn run(self) {
+        let port = self.config.wyoming_port;
+        let mut failures = 0u32;
+
+        while !self.stopping() {
+            if is_server_running(&self.host, port) {
+                if self.set_state(ServerState::External) {
+                    info!("🔌 Using external Wyoming server at {}:{}", self.host, port);
+                }
+                failures = 0;
+                self.sleep(POLL_INTERVAL * 10);
+                continue;
+            }
+
+            self.set_state(ServerState::Starting);
+            let ran_for = match spawn_server(&self.config, &self.host) {
+                Ok(child) => self.watch(child),
+                Err(e) => {
+                    error!("❌ Failed to start Wyoming server: {:#}", e);
+                    Duration::ZERO
+                }
+            };
+            if self.stopping() {
+                break;
+            }
+
+            failures = if ran_for >= STABLE_UPTIME {
+                1
+            } else {
+                failures + 1
+            };
+            let delay = backoff(failures);
+            warn!(
+                "🔁 Restarting Wyoming server in {:?} (attempt {})",
+                delay, failures
+            );
+            self.set_state(ServerState::Restarting {
+                attempt: failures,
+                delay,
+            });
+            self.sleep(delay);
+        }
+
+        self.set_state(ServerState::Stopped);
+    }
+
+    /// Watch our process until it exits or we shut down; returns its uptime
+    fn watch(&self, mut child: Child) -> Duration {
+        let started = Instant::now();
+        let mut listening = false;
+        let mut warned_slow = false;
+
+        loop {
+            if self.stopping() {
+                terminate(&mut child);
+                return started.elapsed();
+            }
+
+            match child.try_wait() {
+                Ok(Some(status)) => {
+                    error!("💥 Wyoming server exited: {}", status);
+                    return started.elapsed();
+                }
+                Ok(None) => {}
+                Err(e) => warn!("⚠️ Failed to poll Wyoming server: {}", e),
+            }
+
+            if !listening && is_server_running(&self.host, self.config.wyoming_port) {
+                listening = true;
+                info!(
+                    "✅ Wyoming server started successfully (PID: {})",
+                    child.id()
+                );
+                self.set_state(ServerState::Running { pid: child.id() });
+            } else if !listening && !warned_slow && started.elapsed() > SLOW_START_WARNING {
+                warned_slow = true;
+                warn!("⏳ Wyoming server is slow to start (downloading the model?)");
+            }
+
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    fn stopping(&self) -> bool {
+        self.shutdown.load(Ordering::SeqCst)
+    }
+
+    /// Sleep in small steps so shutdown stays responsive
+    fn sleep(&self, duration: Duration) {
+        let until = Instant::now() + duration;
+        while !self.stopping() && Instant::now() < until {
+            thread::sleep(POLL_INTERVAL.min(until - Instant::now()));
+        }
+    }
+
+    /// Publish a new state, returning whether it changed
+    fn set_state(&self, state: ServerState) -> bool {
+        let Ok(mut current) = self.state.lock() else {
+            return false;
+        };
+        let changed = *current != state;
+        *current = state;
+        changed
+    }
+}
+
+/// Restart delay after `failures` consecutive failures
+fn backoff(failures: u32) -> Duration {
+    let exponent = failures.saturating_sub(1).min(16);
+    (INITIAL_BACKOFF * 2u32.pow(exponent)).min(MAX_BACKOFF)
+}
+
+/// Loopback address for "localhost", so we don't bind/probe IPv6 by surprise
+fn local_host(host: &str) -> String {
+    if host == "localhost" {
+        "127.0.0.1".to_string()
     } else {
-        &config.wyoming_host
-    };
-
-    if is_server_running(host, config.wyoming_port) {
-        warn!(
-            "Wyoming server already running at {}:{}",
-            host, config.wyoming_port
-        );
-        // We can't return a Child handle to a process we didn't start.
-        // In a real app we might want to return an Enum (Existing, New(Child)).
-        // For now, we'll error out? Or we should check before calling start.
-        // But the signature returns Result<Child>.
-        // Let's assume the caller checks `is_server_running` first, or we
-        // spawn a dummy/check.
-        // Actually, Python returns None if running. Here we return Result.
-        return Err(anyhow::anyhow!("Server already running"));
-    }
-
-    // Check if binary exists
+        host.to_string()
+    }
+}
+
+/// Whisper language code: `wyoming_language`, else the UI language
+/// ("en_GB" → "en"), else the active i18n language
+pub fn whisper_language(config: &Config) -> String {
+    let ui_language = crate::i18n::current_language();
+    [
+        config.wyoming_language.as_str(),
+        config.ui_language.as_str(),
+        ui_language.as_str(),
+    ]
+    .iter()
+    .map(|lang| {
+        lang.split(['_', '-', '.'])
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_lowercase()
+    })
+    .find(|lang| !lang.is_empty())
+    .unwrap_or_else(|| "en".to_string())
+}
+
+/// Spawn wyoming-faster-whisper with its output captured into the log
+fn spawn_server(config: &Config, host: &str) -> Result<Child> {
     // This assumes wyoming-faster-whisper is in PATH
     let binary = "wyoming-faster-whisper";
 
@@ -51,9 +304,13 @@ pub fn start_server(config: &Config) -> Result<Child> {
         .unwrap_or_else(|| std::path::PathBuf::from("."))
         .join("tuxtalks/logs");
     std::fs::create_dir_all(&log_dir).context("Failed to create logs dir")?;
-    let log_file = std::fs::File::create(log_dir.join("wyoming_server.log"))
-        .context("Failed to create log file")?;
+    let log = RotatingLog::open(
+        &log_dir.join("wyoming_server.log"),
+        MAX_LOG_BYTES,
+        LOG_BACKUPS,
+    )?;
 
+    let language = whisper_language(config);
     info!("🚀 Starting Wyoming Whisper server...");
     info!("   Host: {}:{}", host, config.wyoming_port);
     info!(
@@ -61,6 +318,7 @@ pub fn start_server(config: &Config) -> Result<Child> {
         config.wyoming_model, config.wyoming_compute_type
     );
     info!("   Device: {}", config.wyoming_device);
+    info!("   Language: {}", language);
 
     let uri = format!("tcp://{}:{}", host, config.wyoming_port);
 
@@ -70,7 +328,7 @@ pub fn start_server(config: &Config) -> Result<Child> {
         .arg("--model")
         .arg(&config.wyoming_model)
         .arg("--language")
-        .arg("en") // Default to English for now, could be configurable
+        .arg(&language)
         .arg("--device")
         .arg(&config.wyoming_device)
         .arg("--compute-type")
@@ -79,58 +337,162 @@ pub fn start_server(config: &Config) -> Result<Child> {
         .arg("1")
         .arg("--data-dir")
         .arg(data_dir)
-        .stdout(Stdio::from(log_file.try_clone().unwrap()))
-        .stderr(Stdio::from(log_file))
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
         .spawn()
         .context("Failed to spawn wyoming-faster-whisper. Is it installed?")?;
 
-    // Wait briefly to see if it crashes immediately
-    std::thread::sleep(Duration::from_secs_f32(1.5));
+    let log = Arc::new(Mutex::new(log));
+    if let Ok(mut log) = log.lock() {
+        let _ = log.write_line(&format!(
+            "--- {} started wyoming-faster-whisper (PID {}) ---",
+            chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
+            child.id()
+        ));
+    }
+    if let Some(stdout) = child.stdout.take() {
+        capture_output(stdout, log.clone());
+    }
+    if let Some(stderr) = child.stderr.take() {
+        capture_output(stderr, log);
+    }
+
+    Ok(child)
+}
 
-    if let Ok(Some(status)) = child.try_wait() {
-        error!("Wyoming server exited immediately with status: {}", status);
-        return Err(anyhow::anyhow!("Server process exited prematurely"));
+/// Copy a pipe into the log line by line until the process closes it
+fn capture_output<R: Read + Send + 'static>(pipe: R, log: Arc<Mutex<RotatingLog>>) {
+    let spawned = thread::Builder::new()
+        .name("wyoming-log".into())
+        .spawn(move || {
+            for line in BufReader::new(pipe).lines() {
+                let Ok(line) = line else { break };
+                if let Ok(mut log) = log.lock() {
+                    if let Err(e) = log.write_line(&line) {
+                        warn!("⚠️ Failed to write Wyoming server log: {}", e);
+                        break;
+                    }
+                }
+            }
+        });
+    if let Err(e) = spawned {
+        warn!("⚠️ Failed to capture Wyoming server output: {}", e);
     }
+}
 
-    // Wait up to 10s for port to open
-    let start = Instant::now();
-    while start.elapsed() < Duration::from_secs(10) {
-        if is_server_running(host, config.wyoming_port) {
-            info!(
-                "✅ Wyoming server started successfully (PID: {})",
-                child.id()
-            );
-            return Ok(child);
+/// Ask the server to exit (SIGTERM), falling back to SIGKILL
+fn terminate(child: &mut Child) {
+    info!("🛑 Stopping Wyoming server (PID: {})...", child.id());
+
+    let _ = Command::new("kill")
+        .arg("-TERM")
+        .arg(child.id().to_string())
+        .status();
+
+    let deadline = Instant::now() + TERMINATE_TIMEOUT;
+    while Instant::now() < deadline {
+        if let Ok(Some(_)) = child.try_wait() {
+            return;
         }
-        std::thread::sleep(Duration::from_millis(500));
+        thread::sleep(Duration::from_millis(100));
     }
 
-    // Timeout
     let _ = child.kill();
-    Err(anyhow::anyhow!("Timed out waiting for server to listen"))
+    let _ = child.wait();
 }
 
-/// Stop the server process
-pub fn stop_server(child: &mut Child) {
-    info!("🛑 Stopping Wyoming server (PID: {})...", child.id());
+/// Append-only log file that rotates itself by size
+struct RotatingLog {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_bytes: u64,
+    backups: usize,
+}
 
-    // Try graceful SIGTERM (Unix only)
-    #[cfg(unix)]
-    {
-        // Simplistic approach: just kill it for now as std::process::Command doesn't expose SIGTERM easily
-        // without platform specific extensions. wrapper libraries like `nix` or `libc` usage would be needed.
-        // For standard lib, .kill() is SIGKILL.
-        // We can try to be nicer if we had `signal-hook` or similar, but .kill() is reliable.
-        // Python used .terminate() which is SIGTERM.
-        // Rust's .kill() is strictly SIGKILL on Unix usually? No, the documentation says:
-        // "On Unix, this sends the SIGKILL signal." -> That's abrupt.
-        // "On Windows, this uses TerminateProcess."
+impl RotatingLog {
+    fn open(path: &Path, max_bytes: u64, backups: usize) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+            size,
+            max_bytes,
+            backups,
+        })
+    }
 
-        // If we want SIGTERM, we need `nix::sys::signal::kill`.
-        // Or assume the user handles it.
-        // For parity with "quick and dirty" initially:
+    fn write_line(&mut self, line: &str) -> std::io::Result<()> {
+        let len = line.len() as u64 + 1;
+        if self.size > 0 && self.size + len > self.max_bytes {
+            self.rotate()?;
+        }
+        writeln!(self.file, "{}", line)?;
+        self.size += len;
+        Ok(())
     }
 
-    let _ = child.kill();
-    let _ = child.wait();
+    /// log → log.1 → log.2 ... dropping the oldest
+    fn rotate(&mut self) -> std::io::Result<()> {
+        let backup = |n: usize| PathBuf::from(format!("{}.{}", self.path.display(), n));
+        for n in (1..self.backups).rev() {
+            let from = backup(n);
+            if from.exists() {
+                std::fs::rename(&from, backup(n + 1))?;
+            }
+        }
+        if self.backups > 0 {
+            std::fs::rename(&self.path, backup(1))?;
+        }
+        self.file = File::create(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles_and_caps() {
+        assert_eq!(backoff(1), Duration::from_secs(1));
+        assert_eq!(backoff(2), Duration::from_secs(2));
+        assert_eq!(backoff(4), Duration::from_secs(8));
+        assert_eq!(backoff(100), MAX_BACKOFF);
+    }
+
+    #[test]
+    fn test_whisper_language_from_config() {
+        let mut config = Config {
+            ui_language: "de_DE".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(whisper_language(&config), "de");
+
+        config.wyoming_language = "FR".to_string();
+        assert_eq!(whisper_language(&config), "fr");
+    }
+
+    #[test]
+    fn test_log_rotation() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("server.log");
+        let mut log = RotatingLog::open(&path, 20, 2).unwrap();
+
+        for line in ["first line", "second line", "third line", "fourth line"] {
+            log.write_line(line).unwrap();
+        }
+
+        let read = |p: PathBuf| std::fs::read_to_string(p).unwrap();
+        assert_eq!(read(path.clone()), "fourth line\n");
+        assert_eq!(read(dir.path().join("server.log.1")), "third line\n");
+        assert_eq!(read(dir.path().join("server.log.2")), "second line\n");
+        assert!(!dir.path().join("server.log.3").exists());
+    }
 }
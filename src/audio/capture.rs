@@ -0,0 +1,464 @@
+//! Microphone capture
+//!
+//! Opens the input device in whatever format it supports (i16/f32/u16, any
+//! rate, any channel count) and converts to the 16 kHz mono i16 chunks the
+//! rest of the pipeline expects. The stream is owned by [`AudioCapture`], which
+//! reconnects automatically when the device goes away. Chunks then pass through
+//! the [`DspChain`]. While TuxTalks is playing audio itself the chunks are
+//! silenced (see [`PlaybackMonitor`]).
+
+use super::resample::{to_mono, Resampler};
+use super::{DspChain, DspConfig, PlaybackMonitor, CHUNK_SIZE, SAMPLE_RATE};
+use anyhow::{Context, Result};
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{
+    FromSample, SampleFormat, SizedSample, SupportedStreamConfig, SupportedStreamConfigRange,
+};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
+
+/// Which input device to capture from
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum DeviceSelector {
+    /// The host's default input device
+    #[default]
+    Default,
+    /// Position in the device list (not stable across reboots/replugs)
+    Index(usize),
+    /// First device whose name contains this text (case-insensitive)
+    Name(String),
+}
+
+impl DeviceSelector {
+    /// Selector from an optional index and a (possibly empty) name filter
+    pub fn from_args(index: Option<usize>, name: &str) -> Self {
+        match index {
+            Some(idx) => Self::Index(idx),
+            None if !name.trim().is_empty() => Self::Name(name.trim().to_string()),
+            None => Self::Default,
+        }
+    }
+}
+
+/// How often the capture thread checks on the stream
+const WATCHDOG_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Delay between reconnect attempts while the device is gone
+const RECONNECT_DELAY: Duration = Duration::from_secs(2);
+
+/// Commands sent to the capture thread
+enum CaptureCommand {
+    Stop,
+    Restart,
+    Switch(DeviceSelector),
+    /// Reported by the cpal error callback
+    StreamError(String),
+    Shutdown,
+}
+
+/// Handle to the microphone capture
+///
+/// cpal streams aren't `Send`, so a dedicated thread owns the stream and this
+/// handle talks to it over a channel. The thread reopens the device on stream
+/// errors or when it stops delivering audio (e.g. a USB mic was unplugged).
+/// Dropping the handle stops capture.
+pub struct AudioCapture {
+    commands: std::sync::mpsc::Sender<CaptureCommand>,
+}
+
+impl std::fmt::Debug for AudioCapture {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AudioCapture").finish()
+    }
+}
+
+impl AudioCapture {
+    /// Open the selected device and return the handle plus a receiver for audio
+    /// chunks, cleaned up by the `dsp` stages. Fails if the device can't be
+    /// opened initially.
+    pub fn start(
+        selector: DeviceSelector,
+        dsp: DspConfig,
+    ) -> Result<(Self, mpsc::UnboundedReceiver<Vec<i16>>)> {
+        let (cmd_tx, cmd_rx) = std::sync::mpsc::channel();
+        let (audio_tx, audio_rx) = mpsc::unbounded_channel();
+        let (ready_tx, ready_rx) = std::sync::mpsc::channel();
+
+        let errors = cmd_tx.clone();
+        thread::Builder::new()
+            .name("audio-capture".into())
+            .spawn(move || {
+                let mut worker = CaptureWorker {
+                    selector,
+                    dsp,
+                    audio: audio_tx,
+                    errors,
+                    stream: None,
+                    receiving: Arc::new(AtomicBool::new(false)),
+                };
+                let opened = worker.open();
+                let failed = opened.is_err();
+                let _ = ready_tx.send(opened);
+                if !failed {
+                    worker.run(cmd_rx);
+                }
+            })
+            .context("Failed to spawn audio capture thread")?;
+
+        ready_rx
+            .recv()
+            .context("Audio capture thread exited during startup")??;
+
+        Ok((Self { commands: cmd_tx }, audio_rx))
+    }
+
+    /// Stop capturing (the device is released; no reconnect attempts)
+    pub fn stop(&self) {
+        let _ = self.commands.send(CaptureCommand::Stop);
+    }
+
+    /// Reopen the current device (also resumes after `stop`)
+    pub fn restart(&self) {
+        let _ = self.commands.send(CaptureCommand::Restart);
+    }
+
+    /// Switch to another input device without interrupting the audio channel
+    pub fn switch_device(&self, selector: DeviceSelector) {
+        let _ = self.commands.send(CaptureCommand::Switch(selector));
+    }
+}
+
+impl Drop for AudioCapture {
+    fn drop(&mut self) {
+        // Not joined: the thread releases the device within one command poll,
+        // and blocking here would stall async callers
+        let _ = self.commands.send(CaptureCommand::Shutdown);
+    }
+}
+
+/// State owned by the capture thread
+struct CaptureWorker {
+    selector: DeviceSelector,
+    dsp: DspConfig,
+    audio: mpsc::UnboundedSender<Vec<i16>>,
+    errors: std::sync::mpsc::Sender<CaptureCommand>,
+    stream: Option<cpal::Stream>,
+    /// Set by the data callback, cleared by the watchdog
+    receiving: Arc<AtomicBool>,
+}
+
+impl CaptureWorker {
+    fn run(&mut self, commands: std::sync::mpsc::Receiver<CaptureCommand>) {
+        let mut stopped = false;
+
+        loop {
+            let timeout = if self.stream.is_some() {
+                WATCHDOG_INTERVAL
+            } else {
+                RECONNECT_DELAY
+            };
+
+            match commands.recv_timeout(timeout) {
+                Ok(CaptureCommand::Stop) => {
+                    info!("⏹️ Audio capture stopped");
+                    self.stream = None;
+                    stopped = true;
+                }
+                Ok(CaptureCommand::Restart) => {
+                    stopped = false;
+                    self.reopen();
+                }
+                Ok(CaptureCommand::Switch(selector)) => {
+                    info!("🔀 Switching input device to {:?}", selector);
+                    self.selector = selector;
+                    stopped = false;
+                    self.reopen();
+                }
+                Ok(CaptureCommand::StreamError(e)) => {
+                    if self.stream.is_some() {
+                        warn!("⚠️ Audio stream error: {} - reconnecting", e);
+                        self.stream = None;
+                    }
+                }
+                Ok(CaptureCommand::Shutdown) | Err(RecvTimeoutError::Disconnected) => break,
+                Err(RecvTimeoutError::Timeout) => {
+                    if stopped {
+                        continue;
+                    }
+                    if self.stream.is_none() {
+                        self.reopen();
+                    } else if !self.receiving.swap(false, Ordering::Relaxed) {
+                        warn!("⚠️ No audio from input device - reconnecting");
+                        self.stream = None;
+                        self.reopen();
+                    }
+                }
+            }
+
+            // Nobody is listening any more
+            if self.audio.is_closed() {
+                break;
+            }
+        }
+
+        debug!("Audio capture thread exiting");
+    }
+
+    fn reopen(&mut self) {
+        self.stream = None;
+        match self.open() {
+            Ok(()) => info!("🎙️ Audio capture (re)connected"),
+            Err(e) => debug!("Audio device not available yet: {:#}", e),
+        }
+    }
+
+    /// Open the selected device and start streaming
+    fn open(&mut self) -> Result<()> {
+        let host = cpal::default_host();
+        let device = select_device(&host, &self.selector)?;
+
+        let device_name = device.name().unwrap_or_else(|_| "Unknown".to_string());
+        info!("Using audio device: {}", device_name);
+
+        let supported = negotiate_config(&device)?;
+        info!(
+            "🎚️ Capture format: {} ch, {} Hz, {:?}",
+            supported.channels(),
+            supported.sample_rate().0,
+            supported.sample_format()
+        );
+
+        let stream = match supported.sample_format() {
+            SampleFormat::I16 => self.build_stream::<i16>(&device, &supported)?,
+            SampleFormat::F32 => self.build_stream::<f32>(&device, &supported)?,
+            SampleFormat::U16 => self.build_stream::<u16>(&device, &supported)?,
+            other => anyhow::bail!("Unsupported capture sample format: {:?}", other),
+        };
+
+        stream.play()?;
+        // Give the new stream a full watchdog interval before judging it
+        self.receiving.store(true, Ordering::Relaxed);
+        self.stream = Some(stream);
+        Ok(())
+    }
+
+    /// Build an input stream that converts `T` samples to 16 kHz mono i16 chunks
+    fn build_stream<T>(
+        &self,
+        device: &cpal::Device,
+        supported: &SupportedStreamConfig,
+    ) -> Result<cpal::Stream>
+    where
+        T: SizedSample,
+        i16: FromSample<T>,
+    {
+        let channels = supported.channels();
+        let config = supported.config();
+        let mut resampler = Resampler::new(config.sample_rate.0, SAMPLE_RATE);
+        let mut pending: Vec<i16> = Vec::with_capacity(CHUNK_SIZE * 2);
+        let mut dsp = DspChain::new(self.dsp.clone());
+        let tx = self.audio.clone();
+        let receiving = self.receiving.clone();
+        let errors = self.errors.clone();
+        debug!("Capture buffer size: {:?}", config.buffer_size);
+
+        let stream = device.build_input_stream(
+            &config,
+            move |data: &[T], _: &cpal::InputCallbackInfo| {
+                receiving.store(true, Ordering::Relaxed);
+                let converted: Vec<i16> = data.iter().map(|&s| s.to_sample::<i16>()).collect();
+                pending.extend(resampler.process(&to_mono(&converted, channels)));
+
+                // Send fixed-size chunks - Unbounded so it won't block the audio thread
+                // Our own TTS/effects are playing: hand on silence instead
+                if PlaybackMonitor::global().is_muted() {
+                    pending.fill(0);
+                }
+
+                while pending.len() >= CHUNK_SIZE {
+                    let mut chunk: Vec<i16> = pending.drain(..CHUNK_SIZE).collect();
+                    dsp.process(&mut chunk);
+                    if tx.send(chunk).is_err() {
+                        // If receiver is dropped, this is fine when shutting down
+                        return;
+                    }
+                }
+            },
+            move |err| {
+                let _ = errors.send(CaptureCommand::StreamError(err.to_string()));
+            },
+            None,
+        )?;
+
+        Ok(stream)
+    }
+}
+
+/// List input devices and pick the one matching `selector`
+fn select_device(host: &cpal::Host, selector: &DeviceSelector) -> Result<cpal::Device> {
+    let devices: Vec<cpal::Device> = host.input_devices()?.collect();
+    let names: Vec<String> = devices
+        .iter()
+        .map(|d| d.name().unwrap_or_else(|_| "Unknown".to_string()))
+        .collect();
+
+    let chosen = match selector {
+        DeviceSelector::Default => None,
+        DeviceSelector::Index(idx) => {
+            if *idx >= devices.len() {
+                anyhow::bail!("Device index out of range");
+            }
+            Some(*idx)
+        }
+        DeviceSelector::Name(filter) => {
+            let idx = find_by_name(&names, filter)
+                .with_context(|| format!("No input device matching '{}'", filter))?;
+            Some(idx)
+        }
+    };
+
+    // List available devices
+    info!("Available audio input devices:");
+    for (i, name) in names.iter().enumerate() {
+        let marker = if chosen == Some(i) { "*" } else { " " };
+        info!("  {} [{}] {}", marker, i, name);
+    }
+
+    match chosen {
+        Some(idx) => devices
+            .into_iter()
+            .nth(idx)
+            .context("Device index out of range"),
+        None => host
+            .default_input_device()
+            .context("No default input device"),
+    }
+}
+
+/// Index of the first name containing `filter` (case-insensitive)
+fn find_by_name(names: &[String], filter: &str) -> Option<usize> {
+    let filter = filter.to_lowercase();
+    names
+        .iter()
+        .position(|name| name.to_lowercase().contains(&filter))
+}
+
+/// Pick the device config closest to 16 kHz mono, falling back to the device default
+fn negotiate_config(device: &cpal::Device) -> Result<SupportedStreamConfig> {
+    let ranges: Vec<SupportedStreamConfigRange> = match device.supported_input_configs() {
+        Ok(configs) => configs.collect(),
+        Err(e) => {
+            warn!("⚠️ Could not query supported input configs: {}", e);
+            Vec::new()
+        }
+    };
+
+    match choose_config(&ranges) {
+        Some(config) => Ok(config),
+        None => device
+            .default_input_config()
+            .context("No usable input config for device"),
+    }
+}
+
+/// Rank supported configs: usable sample format first, then a rate as close to
+/// 16 kHz as possible (preferring to downsample), then fewest channels, then i16.
+fn choose_config(ranges: &[SupportedStreamConfigRange]) -> Option<SupportedStreamConfig> {
+    let format_rank = |format: SampleFormat| match format {
+        SampleFormat::I16 => Some(0),
+        SampleFormat::F32 => Some(1),
+        SampleFormat::U16 => Some(2),
+        _ => None,
+    };
+
+    ranges
+        .iter()
+        .filter_map(|range| {
+            let format = format_rank(range.sample_format())?;
+            let rate = SAMPLE_RATE.clamp(range.min_sample_rate().0, range.max_sample_rate().0);
+            // Upsampling can't add information, so a lower rate ranks after any higher one
+            let distance = if rate >= SAMPLE_RATE {
+                (rate - SAMPLE_RATE) as u64
+            } else {
+                u32::MAX as u64 + (SAMPLE_RATE - rate) as u64
+            };
+            let key = (distance, range.channels(), format);
+            Some((key, (*range).with_sample_rate(cpal::SampleRate(rate))))
+        })
+        .min_by_key(|(key, _)| *key)
+        .map(|(_, config)| config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cpal::{SampleRate, SupportedBufferSize};
+
+    fn range(
+        channels: u16,
+        min: u32,
+        max: u32,
+        format: SampleFormat,
+    ) -> SupportedStreamConfigRange {
+        SupportedStreamConfigRange::new(
+            channels,
+            SampleRate(min),
+            SampleRate(max),
+            SupportedBufferSize::Unknown,
+            format,
+        )
+    }
+
+    #[test]
+    fn test_choose_config_prefers_16k_mono() {
+        let ranges = [
+            range(2, 44_100, 48_000, SampleFormat::F32),
+            range(1, 8_000, 96_000, SampleFormat::F32),
+            range(2, 8_000, 96_000, SampleFormat::I16),
+        ];
+        let config = choose_config(&ranges).unwrap();
+        assert_eq!(config.sample_rate().0, SAMPLE_RATE);
+        assert_eq!(config.channels(), 1);
+        assert_eq!(config.sample_format(), SampleFormat::F32);
+    }
+
+    #[test]
+    fn test_choose_config_stereo_48k_only() {
+        let ranges = [
+            range(2, 48_000, 48_000, SampleFormat::F32),
+            range(2, 8_000, 8_000, SampleFormat::I16),
+            range(1, 16_000, 16_000, SampleFormat::I32),
+        ];
+        let config = choose_config(&ranges).unwrap();
+        assert_eq!(config.sample_rate().0, 48_000);
+        assert_eq!(config.sample_format(), SampleFormat::F32);
+    }
+
+    #[test]
+    fn test_find_by_name() {
+        let names = vec![
+            "pipewire".to_string(),
+            "HyperX Cloud II: USB Audio".to_string(),
+        ];
+        assert_eq!(find_by_name(&names, "hyperx"), Some(1));
+        assert_eq!(find_by_name(&names, "Jabra"), None);
+    }
+
+    #[test]
+    fn test_device_selector_from_args() {
+        assert_eq!(
+            DeviceSelector::from_args(Some(2), "usb"),
+            DeviceSelector::Index(2)
+        );
+        assert_eq!(
+            DeviceSelector::from_args(None, " usb "),
+            DeviceSelector::Name("usb".to_string())
+        );
+        assert_eq!(DeviceSelector::from_args(None, ""), DeviceSelector::Default);
+    }
+}
@@ -0,0 +1,272 @@
+//! Capture DSP chain
+//!
+//! Cleans up the microphone signal before it reaches the VAD and the
+//! recognizer. A high-pass filter removes DC offset and low rumble, automatic
+//! gain control brings quiet headsets and hot mics to a common speech level,
+//! and a noise gate silences steady background noise (fans, PC hum) between
+//! words. Both AGC and the gate key off a noise floor learned from the signal.
+
+use super::SAMPLE_RATE;
+use serde::{Deserialize, Serialize};
+
+/// Which stages run, and their tuning
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DspConfig {
+    /// Remove DC offset and rumble below `high_pass_hz`
+    pub high_pass: bool,
+    pub high_pass_hz: f32,
+    /// Automatic gain control
+    pub agc: bool,
+    /// Speech level AGC aims for (RMS of i16 samples)
+    pub agc_target_rms: f32,
+    /// Most AGC may amplify (linear gain)
+    pub agc_max_gain: f32,
+    /// Silence audio that isn't clearly above the learned noise floor
+    pub noise_gate: bool,
+    /// Signal must exceed the noise floor by this factor to open the gate
+    pub gate_ratio: f32,
+    /// How long the gate stays open after the signal drops, so word endings survive
+    pub gate_hold_ms: u64,
+}
+
+impl Default for DspConfig {
+    fn default() -> Self {
+        Self {
+            high_pass: true,
+            high_pass_hz: 80.0,
+            agc: true,
+            agc_target_rms: 3000.0,
+            agc_max_gain: 8.0,
+            noise_gate: true,
+            gate_ratio: 2.0,
+            gate_hold_ms: 300,
+        }
+    }
+}
+
+/// Chunks quieter than this are digital silence (muted capture), not room noise
+const DIGITAL_SILENCE: f32 = 1.0;
+
+/// Noise floor smoothing per chunk: falls quickly, rises slowly, and only
+/// creeps up during loud passages so a steadily louder room is still learned
+const FLOOR_FALL_RATE: f32 = 0.5;
+const FLOOR_RISE_RATE: f32 = 0.05;
+const FLOOR_CREEP_RATE: f32 = 0.005;
+
+/// AGC smoothing per chunk when turning up (slow) / down (fast, to avoid clipping)
+const AGC_ATTACK_RATE: f32 = 0.1;
+const AGC_RELEASE_RATE: f32 = 0.5;
+
+/// AGC never attenuates below this gain
+const AGC_MIN_GAIN: f32 = 0.25;
+
+/// Streaming processor for 16 kHz mono chunks
+#[derive(Debug, Clone)]
+pub struct DspChain {
+    config: DspConfig,
+    /// High-pass filter coefficient and state
+    hp_alpha: f32,
+    hp_prev_in: f32,
+    hp_prev_out: f32,
+    /// Learned background level (None until the first non-silent chunk)
+    noise_floor: Option<f32>,
+    gate_hold_left_ms: u64,
+    /// Gains applied at the end of the previous chunk (ramped to avoid clicks)
+    gate_gain: f32,
+    agc_gain: f32,
+}
+
+impl DspChain {
+    pub fn new(config: DspConfig) -> Self {
+        let rc = 1.0 / (2.0 * std::f32::consts::PI * config.high_pass_hz.max(1.0));
+        let dt = 1.0 / SAMPLE_RATE as f32;
+        Self {
+            hp_alpha: rc / (rc + dt),
+            hp_prev_in: 0.0,
+            hp_prev_out: 0.0,
+            noise_floor: None,
+            gate_hold_left_ms: 0,
+            gate_gain: 1.0,
+            agc_gain: 1.0,
+            config,
+        }
+    }
+
+    /// Whether any stage is enabled
+    pub fn is_active(&self) -> bool {
+        self.config.high_pass || self.config.agc || self.config.noise_gate
+    }
+
+    /// Current background level estimate
+    pub fn noise_floor(&self) -> Option<f32> {
+        self.noise_floor
+    }
+
+    /// Gain AGC currently applies
+    pub fn gain(&self) -> f32 {
+        self.agc_gain
+    }
+
+    /// Process one chunk in place
+    pub fn process(&mut self, chunk: &mut [i16]) {
+        if !self.is_active() || chunk.is_empty() {
+            return;
+        }
+
+        let mut signal: Vec<f32> = chunk.iter().map(|&s| s as f32).collect();
+        if self.config.high_pass {
+            self.high_pass(&mut signal);
+        }
+
+        // Level of this chunk before any gain, compared against the background
+        let energy = (signal.iter().map(|s| s * s).sum::<f32>() / signal.len() as f32).sqrt();
+        let speech = self.update_gate(energy, chunk.len());
+
+        let target_agc = if self.config.agc && speech && energy > 0.0 {
+            let desired = (self.config.agc_target_rms / energy)
+                .clamp(AGC_MIN_GAIN, self.config.agc_max_gain.max(AGC_MIN_GAIN));
+            let rate = if desired < self.agc_gain {
+                AGC_RELEASE_RATE
+            } else {
+                AGC_ATTACK_RATE
+            };
+            self.agc_gain + (desired - self.agc_gain) * rate
+        } else if self.config.agc {
+            // Hold the gain through pauses so the next word starts at the right level
+            self.agc_gain
+        } else {
+            1.0
+        };
+        let target_gate = if !self.config.noise_gate || speech {
+            1.0
+        } else {
+            0.0
+        };
+
+        // Ramp both gains linearly across the chunk
+        let n = signal.len() as f32;
+        for (i, (sample, out)) in signal.iter().zip(chunk.iter_mut()).enumerate() {
+            let t = (i + 1) as f32 / n;
+            let agc = self.agc_gain + (target_agc - self.agc_gain) * t;
+            let gate = self.gate_gain + (target_gate - self.gate_gain) * t;
+            *out = (sample * agc * gate).clamp(i16::MIN as f32, i16::MAX as f32) as i16;
+        }
+        self.agc_gain = target_agc;
+        self.gate_gain = target_gate;
+    }
+
+    /// First-order high-pass (also removes DC offset)
+    fn high_pass(&mut self, signal: &mut [f32]) {
+        for sample in signal {
+            let input = *sample;
+            let output = self.hp_alpha * (self.hp_prev_out + input - self.hp_prev_in);
+            self.hp_prev_in = input;
+            self.hp_prev_out = output;
+            *sample = output;
+        }
+    }
+
+    /// Learn the noise floor and decide whether this chunk is above it
+    fn update_gate(&mut self, energy: f32, len: usize) -> bool {
+        if energy < DIGITAL_SILENCE {
+            self.gate_hold_left_ms = 0;
+            return false;
+        }
+
+        let floor = *self.noise_floor.get_or_insert(energy);
+        let above = energy > floor * self.config.gate_ratio;
+        let rate = if energy < floor {
+            FLOOR_FALL_RATE
+        } else if above {
+            FLOOR_CREEP_RATE
+        } else {
+            FLOOR_RISE_RATE
+        };
+        self.noise_floor = Some(floor + (energy - floor) * rate);
+
+        let chunk_ms = len as u64 * 1000 / SAMPLE_RATE as u64;
+        if above {
+            self.gate_hold_left_ms = self.config.gate_hold_ms.max(chunk_ms);
+        } else {
+            self.gate_hold_left_ms = self.gate_hold_left_ms.saturating_sub(chunk_ms);
+        }
+        above || self.gate_hold_left_ms > 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::calculate_energy;
+
+    const CHUNK: usize = 1024;
+
+    fn tone(amplitude: f32, offset: i16, len: usize) -> Vec<i16> {
+        (0..len)
+            .map(|i| {
+                let phase = i as f32 * 2.0 * std::f32::consts::PI * 440.0 / SAMPLE_RATE as f32;
+                (phase.sin() * amplitude) as i16 + offset
+            })
+            .collect()
+    }
+
+    fn run(dsp: &mut DspChain, audio: &[i16]) -> Vec<i16> {
+        audio
+            .chunks(CHUNK)
+            .flat_map(|chunk| {
+                let mut chunk = chunk.to_vec();
+                dsp.process(&mut chunk);
+                chunk
+            })
+            .collect()
+    }
+
+    fn mean(samples: &[i16]) -> f32 {
+        samples.iter().map(|&s| s as f32).sum::<f32>() / samples.len() as f32
+    }
+
+    #[test]
+    fn test_high_pass_removes_dc_offset() {
+        let mut dsp = DspChain::new(DspConfig {
+            agc: false,
+            noise_gate: false,
+            ..Default::default()
+        });
+        let out = run(&mut dsp, &tone(1000.0, 2000, CHUNK * 16));
+        let tail = &out[CHUNK * 8..];
+        assert!(mean(tail).abs() < 50.0, "DC left: {}", mean(tail));
+        assert!(calculate_energy(tail) > 500.0, "speech band must survive");
+    }
+
+    #[test]
+    fn test_agc_raises_quiet_speech() {
+        let mut dsp = DspChain::new(DspConfig {
+            noise_gate: false,
+            ..Default::default()
+        });
+        // Learn a quiet room first, then a soft voice well above it
+        run(&mut dsp, &tone(20.0, 0, CHUNK * 8));
+        let out = run(&mut dsp, &tone(600.0, 0, CHUNK * 40));
+        let level = calculate_energy(&out[out.len() - CHUNK..]);
+        assert!(level > 2000.0, "quiet speech only reached {}", level);
+        assert!(dsp.gain() <= 8.0);
+    }
+
+    #[test]
+    fn test_noise_gate_silences_background() {
+        let mut dsp = DspChain::new(DspConfig {
+            agc: false,
+            gate_hold_ms: 100,
+            ..Default::default()
+        });
+        // Steady fan noise: after the floor is learned it is gated out
+        let fan = run(&mut dsp, &tone(300.0, 0, CHUNK * 20));
+        assert_eq!(calculate_energy(&fan[fan.len() - CHUNK..]), 0.0);
+
+        // Speech over the fan passes
+        let speech = run(&mut dsp, &tone(3000.0, 0, CHUNK * 4));
+        assert!(calculate_energy(&speech[speech.len() - CHUNK..]) > 1500.0);
+        assert!(dsp.noise_floor().unwrap() < 400.0);
+    }
+}
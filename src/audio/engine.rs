@@ -8,6 +8,7 @@ use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::sync::mpsc;
 use std::thread;
+use std::time::Duration;
 use tracing::{debug, error, info, warn};
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
@@ -18,11 +19,26 @@ pub enum PlaybackMode {
     Sequential,
 }
 
+/// How often the audio thread checks whether queued playback has finished
+const PLAYBACK_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Loudest volume `set_volume` accepts (twice normal)
+const MAX_VOLUME: f32 = 2.0;
+
 /// Commands sent to the audio thread
 enum AudioCommand {
     PlayFile(PathBuf),
     PlayWait(PathBuf, mpsc::Sender<()>),
+    /// Raw 16-bit PCM (e.g. streamed TTS), acknowledged when finished
+    PlayPcmWait {
+        samples: Vec<i16>,
+        rate: u32,
+        channels: u16,
+        done: mpsc::Sender<()>,
+    },
     Stop,
+    /// Volume for everything played from now on (1.0 is normal)
+    SetVolume(f32),
     PlayPool {
         pool_id: String,
         files: Vec<PathBuf>,
@@ -77,10 +93,40 @@ impl SoundEngine {
             }
         };
         let mut pool_states: HashMap<String, usize> = HashMap::new();
+        let mut volume = 1.0;
 
         info!("🔊 Audio thread started");
 
-        while let Ok(cmd) = receiver.recv() {
+        // Raised while the sink has anything queued, so capture can mute itself
+        // (speech is guarded by the TTS wrapper either way)
+        let monitor = super::PlaybackMonitor::global();
+        let mut playing = None;
+
+        loop {
+            // Poll while playing to notice when the sink drains
+            let cmd = if playing.is_some() {
+                match receiver.recv_timeout(PLAYBACK_POLL_INTERVAL) {
+                    Ok(cmd) => Some(cmd),
+                    Err(mpsc::RecvTimeoutError::Timeout) => None,
+                    Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                }
+            } else {
+                match receiver.recv() {
+                    Ok(cmd) => Some(cmd),
+                    Err(_) => break,
+                }
+            };
+            let Some(cmd) = cmd else {
+                if sink.empty() {
+                    playing = None;
+                }
+                continue;
+            };
+            let starts_audio = !matches!(cmd, AudioCommand::Stop | AudioCommand::SetVolume(_));
+            if playing.is_none() && starts_audio && monitor.mutes_effects() {
+                playing = Some(monitor.begin());
+            }
+
             match cmd {
                 AudioCommand::PlayFile(path) => {
                     info!("🔊 Playing file: {:?}", path);
@@ -96,14 +142,36 @@ impl SoundEngine {
                     sink.sleep_until_end();
                     let _ = resp.send(());
                 }
+                AudioCommand::PlayPcmWait {
+                    samples,
+                    rate,
+                    channels,
+                    done,
+                } => {
+                    debug!(
+                        "🔊 Playing {} PCM samples ({} Hz, {} ch)",
+                        samples.len(),
+                        rate,
+                        channels
+                    );
+                    sink.append(rodio::buffer::SamplesBuffer::new(channels, rate, samples));
+                    sink.sleep_until_end();
+                    let _ = done.send(());
+                }
                 AudioCommand::Stop => {
                     info!("🛑 Stopping all playback");
                     sink.stop();
                     // Re-create sink after stop as it becomes unusable if we want to play again
                     if let Ok(new_sink) = rodio::Sink::try_new(&stream_handle) {
+                        new_sink.set_volume(volume);
                         sink = new_sink;
                     }
                 }
+                AudioCommand::SetVolume(level) => {
+                    info!("🔊 Volume set to {:.2}", level);
+                    volume = level;
+                    sink.set_volume(volume);
+                }
                 AudioCommand::PlayPool {
                     pool_id,
                     files,
@@ -151,6 +219,9 @@ impl SoundEngine {
                     }
                 }
             }
+            if sink.empty() {
+                playing = None;
+            }
         }
 
         info!("🔇 Audio thread stopped");
@@ -192,6 +263,22 @@ impl SoundEngine {
         Ok(())
     }
 
+    /// Play raw 16-bit PCM and wait for completion (Sync/Blocking)
+    pub fn play_pcm_sync(&self, samples: Vec<i16>, rate: u32, channels: u16) -> anyhow::Result<()> {
+        let (tx, rx) = mpsc::channel();
+        self.sender
+            .send(AudioCommand::PlayPcmWait {
+                samples,
+                rate,
+                channels,
+                done: tx,
+            })
+            .map_err(|e| anyhow::anyhow!("Audio thread disconnected: {}", e))?;
+
+        let _ = rx.recv();
+        Ok(())
+    }
+
     /// Stop all current playback and clear queue
     pub fn stop(&self) -> anyhow::Result<()> {
         self.sender
@@ -199,6 +286,13 @@ impl SoundEngine {
             .map_err(|e| anyhow::anyhow!("Audio thread disconnected: {}", e))
     }
 
+    /// Set the playback volume (1.0 is normal, clamped to 0.0-2.0)
+    pub fn set_volume(&self, level: f32) -> anyhow::Result<()> {
+        self.sender
+            .send(AudioCommand::SetVolume(level.clamp(0.0, MAX_VOLUME)))
+            .map_err(|e| anyhow::anyhow!("Audio thread disconnected: {}", e))
+    }
+
     /// Play a sound pool
     pub fn play_pool(
         &self,
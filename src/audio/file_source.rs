@@ -0,0 +1,213 @@
+//! File audio source
+//!
+//! Replays WAV/FLAC recordings (a single file or a directory of them) into the
+//! same channel as live capture, for reproducing bug reports and headless runs.
+//! Also writes recordings back out (voice training clips).
+
+use super::resample::{to_mono, Resampler};
+use super::{CHUNK_SIZE, SAMPLE_RATE};
+use anyhow::{Context, Result};
+use rodio::{Decoder, Source};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+/// Extensions picked up when replaying a directory
+const SUPPORTED_EXTENSIONS: &[&str] = &["wav", "flac"];
+
+/// Silence inserted after each recording so the VAD closes the utterance
+const TRAILING_SILENCE_MS: u64 = 1500;
+
+/// How fast recordings are fed into the pipeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pacing {
+    /// One chunk per chunk duration, like a live microphone
+    Realtime,
+    /// As fast as the consumer keeps up
+    Fast,
+}
+
+/// Start replaying `path` (a file or a directory) and return a receiver for
+/// 16 kHz mono chunks. The channel closes after the last recording.
+pub fn start_file_source(path: &Path, pacing: Pacing) -> Result<mpsc::UnboundedReceiver<Vec<i16>>> {
+    let files = collect_files(path)?;
+    info!(
+        "📼 Replaying {} recording(s) from {} ({:?})",
+        files.len(),
+        path.display(),
+        pacing
+    );
+
+    let (tx, rx) = mpsc::unbounded_channel();
+
+    std::thread::Builder::new()
+        .name("file-audio".into())
+        .spawn(move || {
+            let chunk_duration =
+                Duration::from_millis(CHUNK_SIZE as u64 * 1000 / SAMPLE_RATE as u64);
+            let silence = vec![0i16; SAMPLE_RATE as usize * TRAILING_SILENCE_MS as usize / 1000];
+            let mut next_chunk_at = Instant::now();
+
+            for file in files {
+                let samples = match decode_file(&file) {
+                    Ok(samples) => samples,
+                    Err(e) => {
+                        warn!("⚠️ Skipping {}: {:#}", file.display(), e);
+                        continue;
+                    }
+                };
+                info!("📼 Playing {}", file.display());
+
+                for chunk in samples.chunks(CHUNK_SIZE).chain(silence.chunks(CHUNK_SIZE)) {
+                    if pacing == Pacing::Realtime {
+                        next_chunk_at += chunk_duration;
+                        if let Some(wait) = next_chunk_at.checked_duration_since(Instant::now()) {
+                            std::thread::sleep(wait);
+                        }
+                    }
+                    if tx.send(chunk.to_vec()).is_err() {
+                        return; // Receiver dropped (shutting down)
+                    }
+                }
+            }
+            info!("📼 Replay finished");
+        })
+        .context("Failed to spawn file audio thread")?;
+
+    Ok(rx)
+}
+
+/// Decode a recording to 16 kHz mono
+pub fn decode_file(path: &Path) -> Result<Vec<i16>> {
+    let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let decoder = Decoder::new(BufReader::new(file))
+        .with_context(|| format!("Unsupported audio file {}", path.display()))?;
+
+    let channels = decoder.channels();
+    let rate = decoder.sample_rate();
+    let interleaved: Vec<i16> = decoder.collect();
+
+    let mono = to_mono(&interleaved, channels);
+    Ok(Resampler::new(rate, SAMPLE_RATE).process(&mono))
+}
+
+/// Write 16 kHz mono samples as a 16-bit PCM WAV file
+pub fn write_wav(path: &Path, samples: &[i16]) -> Result<()> {
+    let data_len = (samples.len() * 2) as u32;
+    let mut bytes = Vec::with_capacity(44 + data_len as usize);
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+    bytes.extend_from_slice(b"WAVEfmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes()); // fmt chunk size
+    bytes.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    bytes.extend_from_slice(&1u16.to_le_bytes()); // mono
+    bytes.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    bytes.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes()); // byte rate
+    bytes.extend_from_slice(&2u16.to_le_bytes()); // block align
+    bytes.extend_from_slice(&16u16.to_le_bytes()); // bits per sample
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_len.to_le_bytes());
+    for s in samples {
+        bytes.extend_from_slice(&s.to_le_bytes());
+    }
+    std::fs::write(path, bytes).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// A single file, or the supported recordings in a directory sorted by name
+pub fn collect_files(path: &Path) -> Result<Vec<PathBuf>> {
+    if !path.is_dir() {
+        if !path.exists() {
+            anyhow::bail!("Input file not found: {}", path.display());
+        }
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let mut files: Vec<PathBuf> = std::fs::read_dir(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| p.is_file() && is_supported(p))
+        .collect();
+    files.sort();
+
+    if files.is_empty() {
+        anyhow::bail!("No WAV/FLAC recordings in {}", path.display());
+    }
+    Ok(files)
+}
+
+fn is_supported(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| SUPPORTED_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Minimal 16-bit PCM WAV writer for fixtures
+    fn write_wav(path: &Path, rate: u32, channels: u16, samples: &[i16]) {
+        let data_len = (samples.len() * 2) as u32;
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"RIFF");
+        bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+        bytes.extend_from_slice(b"WAVEfmt ");
+        bytes.extend_from_slice(&16u32.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&channels.to_le_bytes());
+        bytes.extend_from_slice(&rate.to_le_bytes());
+        bytes.extend_from_slice(&(rate * channels as u32 * 2).to_le_bytes());
+        bytes.extend_from_slice(&(channels * 2).to_le_bytes());
+        bytes.extend_from_slice(&16u16.to_le_bytes());
+        bytes.extend_from_slice(b"data");
+        bytes.extend_from_slice(&data_len.to_le_bytes());
+        for s in samples {
+            bytes.extend_from_slice(&s.to_le_bytes());
+        }
+        std::fs::write(path, bytes).unwrap();
+    }
+
+    #[test]
+    fn test_decode_stereo_48k_to_16k_mono() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("clip.wav");
+        // 0.5 s of stereo at 48 kHz
+        let samples: Vec<i16> = (0..48_000)
+            .map(|i| if i % 2 == 0 { 1000 } else { 3000 })
+            .collect();
+        write_wav(&path, 48_000, 2, &samples);
+
+        let decoded = decode_file(&path).unwrap();
+        assert!(
+            (7999..=8000).contains(&decoded.len()),
+            "got {}",
+            decoded.len()
+        );
+        assert!(decoded.iter().all(|&s| s == 2000));
+    }
+
+    #[test]
+    fn test_directory_replay_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        write_wav(&dir.path().join("b.wav"), 16_000, 1, &[2; 100]);
+        write_wav(&dir.path().join("a.WAV"), 16_000, 1, &[1; 100]);
+        std::fs::write(dir.path().join("notes.txt"), "ignored").unwrap();
+
+        let files = collect_files(dir.path()).unwrap();
+        assert_eq!(files.len(), 2);
+        assert!(files[0].ends_with("a.WAV"));
+
+        let mut rx = start_file_source(dir.path(), Pacing::Fast).unwrap();
+        let mut received = Vec::new();
+        while let Some(chunk) = rx.blocking_recv() {
+            received.extend(chunk);
+        }
+        let silence = SAMPLE_RATE as usize * TRAILING_SILENCE_MS as usize / 1000;
+        assert_eq!(received.len(), 2 * (100 + silence));
+        assert_eq!(received[0], 1);
+        assert_eq!(received[100 + silence], 2);
+    }
+}
@@ -1,76 +1,24 @@
-//! Captures audio from the default input device and sends it to a channel.
+//! Audio input (microphone capture, file replay), DSP, VAD and sound playback.
 
+pub mod capture;
+pub mod dsp;
 pub mod engine;
+pub mod file_source;
+pub mod playback;
+pub mod resample;
+pub mod vad;
+pub use capture::{AudioCapture, DeviceSelector};
+pub use dsp::{DspChain, DspConfig};
 pub use engine::{PlaybackMode, SoundEngine};
+pub use file_source::{start_file_source, Pacing};
+pub use playback::{PlaybackGuard, PlaybackMonitor};
+pub use resample::Resampler;
+pub use vad::{VadConfig, VadEvent, VoiceActivityDetector};
 
-use anyhow::Context;
-use anyhow::Result;
-use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-use tokio::sync::mpsc;
-use tracing::{info, warn};
-
-const SAMPLE_RATE: u32 = 16000;
+/// Rate every capture source delivers (mono i16)
+pub const SAMPLE_RATE: u32 = 16000;
 const CHUNK_SIZE: usize = 1024;
 
-/// Start audio capture and return a receiver for audio chunks
-pub fn start_capture(device_index: Option<usize>) -> Result<mpsc::UnboundedReceiver<Vec<i16>>> {
-    let host = cpal::default_host();
-
-    // List available devices
-    info!("Available audio input devices:");
-    for (i, device) in host.input_devices()?.enumerate() {
-        let name = device.name().unwrap_or_else(|_| "Unknown".to_string());
-        let marker = if device_index == Some(i) { "*" } else { " " };
-        info!("  {} [{}] {}", marker, i, name);
-    }
-
-    // Select device
-    let device = if let Some(idx) = device_index {
-        host.input_devices()?
-            .nth(idx)
-            .context("Device index out of range")?
-    } else {
-        host.default_input_device()
-            .context("No default input device")?
-    };
-
-    let device_name = device.name().unwrap_or_else(|_| "Unknown".to_string());
-    info!("Using audio device: {}", device_name);
-
-    // Configure stream
-    let config = cpal::StreamConfig {
-        channels: 1,
-        sample_rate: cpal::SampleRate(SAMPLE_RATE),
-        buffer_size: cpal::BufferSize::Fixed(CHUNK_SIZE as u32),
-    };
-
-    let (tx, rx) = mpsc::unbounded_channel();
-
-    // Build input stream
-    let stream = device.build_input_stream(
-        &config,
-        move |data: &[i16], _: &cpal::InputCallbackInfo| {
-            // Send audio chunk - Unbounded so it won't block the audio thread
-            if tx.send(data.to_vec()).is_err() {
-                // If receiver is dropped, this is fine when shutting down
-            }
-        },
-        |err| {
-            warn!("Audio stream error: {}", err);
-        },
-        None,
-    )?;
-
-    stream.play()?;
-
-    // Keep stream alive by leaking it (it runs in background)
-    // In production, we'd store this in a struct in the App state
-    // but for now we follow the same pattern for simplicity.
-    std::mem::forget(stream);
-
-    Ok(rx)
-}
-
 /// Calculate audio energy for VAD
 pub fn calculate_energy(samples: &[i16]) -> f32 {
     if samples.is_empty() {
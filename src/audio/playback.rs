@@ -0,0 +1,134 @@
+//! Playback activity signal
+//!
+//! TTS engines and the sound engine mark when TuxTalks itself is making noise;
+//! capture listens to this and mutes the microphone (plus a short tail for room
+//! echo) so the recognizer never transcribes our own voice lines or effects.
+
+use lazy_static::lazy_static;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// Default time to stay muted after playback ends
+pub const DEFAULT_MUTE_TAIL: Duration = Duration::from_millis(500);
+
+lazy_static! {
+    static ref GLOBAL: PlaybackMonitor = PlaybackMonitor::new();
+}
+
+/// Counts active playbacks and remembers when the last one ended
+///
+/// Lock-free so it can be queried from the realtime capture callback.
+#[derive(Debug)]
+pub struct PlaybackMonitor {
+    epoch: Instant,
+    active: AtomicUsize,
+    /// Milliseconds since `epoch` when the last playback ended (0 = never)
+    last_end_ms: AtomicU64,
+    tail_ms: AtomicU64,
+    /// Whether sound effects (not just speech) mute capture
+    mute_effects: AtomicBool,
+}
+
+impl PlaybackMonitor {
+    pub fn new() -> Self {
+        Self {
+            epoch: Instant::now(),
+            active: AtomicUsize::new(0),
+            last_end_ms: AtomicU64::new(0),
+            tail_ms: AtomicU64::new(DEFAULT_MUTE_TAIL.as_millis() as u64),
+            mute_effects: AtomicBool::new(true),
+        }
+    }
+
+    /// The process-wide monitor shared by playback and capture
+    pub fn global() -> &'static PlaybackMonitor {
+        &GLOBAL
+    }
+
+    /// How long capture stays muted after playback ends
+    pub fn set_tail(&self, tail: Duration) {
+        self.tail_ms
+            .store(tail.as_millis() as u64, Ordering::Relaxed);
+    }
+
+    /// Whether sound effects and macro audio mute capture (speech always does)
+    ///
+    /// Off, a spoken "abort" is heard over a long macro sound, but the
+    /// recognizer may also pick up the effect itself.
+    pub fn set_mute_effects(&self, mute: bool) {
+        self.mute_effects.store(mute, Ordering::Relaxed);
+    }
+
+    pub fn mutes_effects(&self) -> bool {
+        self.mute_effects.load(Ordering::Relaxed)
+    }
+
+    /// Mark playback as active until the returned guard is dropped
+    pub fn begin(&self) -> PlaybackGuard<'_> {
+        self.active.fetch_add(1, Ordering::SeqCst);
+        PlaybackGuard { monitor: self }
+    }
+
+    /// Whether anything is playing right now
+    pub fn is_active(&self) -> bool {
+        self.active.load(Ordering::SeqCst) > 0
+    }
+
+    /// Whether capture should be muted (playing, or within the tail)
+    pub fn is_muted(&self) -> bool {
+        if self.is_active() {
+            return true;
+        }
+        let last_end = self.last_end_ms.load(Ordering::SeqCst);
+        last_end > 0 && self.now_ms() < last_end + self.tail_ms.load(Ordering::Relaxed)
+    }
+
+    fn now_ms(&self) -> u64 {
+        // Offset by one so a playback ending at the epoch still counts
+        self.epoch.elapsed().as_millis() as u64 + 1
+    }
+}
+
+impl Default for PlaybackMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Keeps the playback signal raised while alive
+#[derive(Debug)]
+pub struct PlaybackGuard<'a> {
+    monitor: &'a PlaybackMonitor,
+}
+
+impl Drop for PlaybackGuard<'_> {
+    fn drop(&mut self) {
+        self.monitor
+            .last_end_ms
+            .store(self.monitor.now_ms(), Ordering::SeqCst);
+        self.monitor.active.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_muted_during_playback_and_tail() {
+        let monitor = PlaybackMonitor::new();
+        monitor.set_tail(Duration::from_millis(50));
+        assert!(!monitor.is_muted());
+
+        let first = monitor.begin();
+        let second = monitor.begin();
+        drop(first);
+        assert!(monitor.is_active());
+        drop(second);
+        assert!(!monitor.is_active());
+        assert!(monitor.is_muted());
+
+        std::thread::sleep(Duration::from_millis(80));
+        assert!(!monitor.is_muted());
+    }
+}
@@ -0,0 +1,116 @@
+//! Sample format helpers
+//!
+//! Downmixing and resampling to the 16 kHz mono stream the ASR engines expect.
+
+/// Average interleaved channels down to mono
+pub fn to_mono(samples: &[i16], channels: u16) -> Vec<i16> {
+    if channels <= 1 {
+        return samples.to_vec();
+    }
+
+    samples
+        .chunks(channels as usize)
+        .map(|frame| {
+            let sum: i32 = frame.iter().map(|&s| s as i32).sum();
+            (sum / frame.len() as i32) as i16
+        })
+        .collect()
+}
+
+/// Streaming linear-interpolation resampler
+///
+/// Keeps the last input sample between calls so chunk boundaries don't click.
+/// Linear interpolation is plenty for speech recognition.
+#[derive(Debug, Clone)]
+pub struct Resampler {
+    /// Input samples consumed per output sample
+    step: f64,
+    /// Position of the next output sample, relative to `prev`
+    pos: f64,
+    prev: Option<i16>,
+}
+
+impl Resampler {
+    pub fn new(from_rate: u32, to_rate: u32) -> Self {
+        Self {
+            step: from_rate as f64 / to_rate as f64,
+            pos: 0.0,
+            prev: None,
+        }
+    }
+
+    /// Whether the rates differ at all
+    pub fn is_passthrough(&self) -> bool {
+        self.step == 1.0
+    }
+
+    /// Resample one chunk of mono audio
+    pub fn process(&mut self, input: &[i16]) -> Vec<i16> {
+        if self.is_passthrough() {
+            return input.to_vec();
+        }
+        if input.is_empty() {
+            return Vec::new();
+        }
+
+        // Index 0 is the last sample of the previous chunk (or the first sample)
+        let (prev, rest) = match self.prev {
+            Some(prev) => (prev, input),
+            None => (input[0], &input[1..]),
+        };
+        let at = |i: usize| if i == 0 { prev } else { rest[i - 1] };
+        let len = rest.len() + 1;
+
+        let mut output = Vec::with_capacity((len as f64 / self.step) as usize + 1);
+        while self.pos + 1.0 < len as f64 {
+            let idx = self.pos as usize;
+            let frac = self.pos - idx as f64;
+            let a = at(idx) as f64;
+            let b = at(idx + 1) as f64;
+            output.push((a + (b - a) * frac).round() as i16);
+            self.pos += self.step;
+        }
+
+        self.pos -= (len - 1) as f64;
+        self.prev = Some(at(len - 1));
+        output
+    }
+
+    /// Forget stream state (e.g. before an unrelated recording)
+    pub fn reset(&mut self) {
+        self.pos = 0.0;
+        self.prev = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_mono() {
+        assert_eq!(to_mono(&[100, 300, -50, 50], 2), vec![200, 0]);
+        assert_eq!(to_mono(&[1, 2, 3], 1), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_downsample_length_across_chunks() {
+        let mut resampler = Resampler::new(48_000, 16_000);
+        let input: Vec<i16> = (0..4800).map(|i| (i % 100) as i16).collect();
+
+        let total: usize = input
+            .chunks(441)
+            .map(|chunk| resampler.process(chunk).len())
+            .sum();
+        assert!((1599..=1600).contains(&total), "got {}", total);
+    }
+
+    #[test]
+    fn test_upsample_interpolates() {
+        let mut resampler = Resampler::new(8_000, 16_000);
+        let output = resampler.process(&[0, 100, 200]);
+        assert_eq!(output, vec![0, 50, 100, 150]);
+        // The boundary sample is interpolated once the next chunk arrives
+        assert_eq!(resampler.process(&[300]), vec![200, 250]);
+    }
+}
@@ -0,0 +1,384 @@
+//! Voice Activity Detection
+//!
+//! Energy-based VAD with an adaptive noise floor. Splits the capture stream into
+//! utterances so ASR engines know when speech starts and when to finalize.
+//! Noise that starts mid-session (engine rumble) first looks like speech; once
+//! its energy has stayed flat for a while the utterance is ended and the floor
+//! jumps to the new noise level.
+
+use super::{calculate_energy, SAMPLE_RATE};
+use std::collections::VecDeque;
+use tracing::debug;
+
+/// Tuning parameters for the VAD
+#[derive(Debug, Clone)]
+pub struct VadConfig {
+    /// Speech must exceed the noise floor by this factor
+    pub threshold_ratio: f32,
+    /// Absolute minimum energy treated as speech (guards against a near-zero floor)
+    pub min_energy: f32,
+    /// Starting noise floor estimate
+    pub initial_noise_floor: f32,
+    /// Noise floor smoothing factor per chunk (0.0 - 1.0)
+    pub noise_adapt_rate: f32,
+    /// Continuous speech required before an utterance starts
+    pub onset_ms: u64,
+    /// Silence tolerated inside an utterance before it ends
+    pub hangover_ms: u64,
+    /// Utterances with less speech than this are discarded as noise
+    pub min_utterance_ms: u64,
+    /// Utterances are force-ended at this length
+    pub max_utterance_ms: u64,
+    /// Audio kept from before the onset so the first syllable isn't clipped
+    pub pre_roll_ms: u64,
+    /// Energy this steady for this long inside an utterance is background noise
+    pub flat_noise_ms: u64,
+}
+
+/// Chunks within this factor of the running level count as "flat" energy
+const FLAT_ENERGY_RATIO: f32 = 1.5;
+
+impl Default for VadConfig {
+    fn default() -> Self {
+        Self {
+            threshold_ratio: 3.0,
+            min_energy: 300.0,
+            initial_noise_floor: 100.0,
+            noise_adapt_rate: 0.05,
+            onset_ms: 100,
+            hangover_ms: 700,
+            min_utterance_ms: 250,
+            max_utterance_ms: 15_000,
+            pre_roll_ms: 300,
+            flat_noise_ms: 3_000,
+        }
+    }
+}
+
+/// Result of feeding one chunk into the VAD
+#[derive(Debug, Clone, PartialEq)]
+pub enum VadEvent {
+    /// No utterance in progress
+    Silence,
+    /// Speech onset confirmed. Carries the pre-roll audio, including the current chunk.
+    UtteranceStart(Vec<i16>),
+    /// The current chunk belongs to an ongoing utterance
+    Speech,
+    /// The utterance finished (the current chunk is its last)
+    UtteranceEnd { duration_ms: u64 },
+    /// The utterance was too short to be speech and should be thrown away
+    Discarded,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum State {
+    Idle,
+    Onset {
+        speech_ms: u64,
+    },
+    Active {
+        duration_ms: u64,
+        speech_ms: u64,
+        silence_ms: u64,
+        /// Running energy level of the current steady stretch, and its length
+        flat_level: f32,
+        flat_ms: u64,
+    },
+}
+
+/// Energy-based voice activity detector
+#[derive(Debug, Clone)]
+pub struct VoiceActivityDetector {
+    config: VadConfig,
+    noise_floor: f32,
+    state: State,
+    pre_roll: VecDeque<Vec<i16>>,
+    pre_roll_len_ms: u64,
+}
+
+impl Default for VoiceActivityDetector {
+    fn default() -> Self {
+        Self::new(VadConfig::default())
+    }
+}
+
+impl VoiceActivityDetector {
+    pub fn new(config: VadConfig) -> Self {
+        Self {
+            noise_floor: config.initial_noise_floor,
+            config,
+            state: State::Idle,
+            pre_roll: VecDeque::new(),
+            pre_roll_len_ms: 0,
+        }
+    }
+
+    /// Current noise floor estimate
+    pub fn noise_floor(&self) -> f32 {
+        self.noise_floor
+    }
+
+    /// Energy a chunk must exceed to count as speech
+    pub fn threshold(&self) -> f32 {
+        (self.noise_floor * self.config.threshold_ratio).max(self.config.min_energy)
+    }
+
+    /// Whether an utterance is currently in progress
+    pub fn is_active(&self) -> bool {
+        matches!(self.state, State::Active { .. })
+    }
+
+    /// Drop any in-progress utterance and buffered audio (keeps the noise floor)
+    pub fn reset(&mut self) {
+        self.state = State::Idle;
+        self.pre_roll.clear();
+        self.pre_roll_len_ms = 0;
+    }
+
+    /// Feed one chunk of 16 kHz mono audio
+    pub fn process(&mut self, samples: &[i16]) -> VadEvent {
+        let chunk_ms = samples.len() as u64 * 1000 / SAMPLE_RATE as u64;
+        let energy = calculate_energy(samples);
+        let is_speech = energy > self.threshold();
+
+        match self.state {
+            State::Idle | State::Onset { .. } => {
+                self.push_pre_roll(samples, chunk_ms);
+
+                if !is_speech {
+                    self.state = State::Idle;
+                    self.adapt(energy, self.config.noise_adapt_rate);
+                    return VadEvent::Silence;
+                }
+
+                let speech_ms = match self.state {
+                    State::Onset { speech_ms } => speech_ms + chunk_ms,
+                    _ => chunk_ms,
+                };
+
+                if speech_ms < self.config.onset_ms {
+                    self.state = State::Onset { speech_ms };
+                    // Creep upwards slowly so a constant loud fan eventually stops triggering
+                    self.adapt(energy, self.config.noise_adapt_rate / 10.0);
+                    return VadEvent::Silence;
+                }
+
+                let audio: Vec<i16> = self.pre_roll.drain(..).flatten().collect();
+                let duration_ms = self.pre_roll_len_ms;
+                self.pre_roll_len_ms = 0;
+                self.state = State::Active {
+                    duration_ms,
+                    speech_ms,
+                    silence_ms: 0,
+                    flat_level: energy,
+                    flat_ms: 0,
+                };
+
+                debug!(
+                    "🗣️ VAD: utterance start (energy {:.0}, floor {:.0})",
+                    energy, self.noise_floor
+                );
+                VadEvent::UtteranceStart(audio)
+            }
+            State::Active {
+                duration_ms,
+                speech_ms,
+                silence_ms,
+                flat_level,
+                flat_ms,
+            } => {
+                let duration_ms = duration_ms + chunk_ms;
+                let (speech_ms, silence_ms) = if is_speech {
+                    (speech_ms + chunk_ms, 0)
+                } else {
+                    (speech_ms, silence_ms + chunk_ms)
+                };
+
+                // Speech rises and falls; loud noise that started after the
+                // floor was learned stays level
+                let steady = is_speech
+                    && energy < flat_level * FLAT_ENERGY_RATIO
+                    && energy * FLAT_ENERGY_RATIO > flat_level;
+                let (flat_level, flat_ms) = if steady {
+                    (flat_level + (energy - flat_level) * 0.1, flat_ms + chunk_ms)
+                } else {
+                    (energy, 0)
+                };
+                if flat_ms >= self.config.flat_noise_ms {
+                    debug!(
+                        "VAD: energy flat for {} ms, raising noise floor {:.0} -> {:.0}",
+                        flat_ms, self.noise_floor, flat_level
+                    );
+                    self.noise_floor = flat_level;
+                    self.state = State::Idle;
+                    // Keep whatever was said before the noise set in
+                    if speech_ms.saturating_sub(flat_ms) >= self.config.min_utterance_ms {
+                        return VadEvent::UtteranceEnd { duration_ms };
+                    }
+                    return VadEvent::Discarded;
+                }
+
+                if silence_ms >= self.config.hangover_ms
+                    || duration_ms >= self.config.max_utterance_ms
+                {
+                    self.state = State::Idle;
+                    if speech_ms >= self.config.min_utterance_ms {
+                        debug!("🗣️ VAD: utterance end ({} ms)", duration_ms);
+                        return VadEvent::UtteranceEnd { duration_ms };
+                    }
+                    debug!("VAD: discarding {} ms noise burst", duration_ms);
+                    return VadEvent::Discarded;
+                }
+
+                self.state = State::Active {
+                    duration_ms,
+                    speech_ms,
+                    silence_ms,
+                    flat_level,
+                    flat_ms,
+                };
+                VadEvent::Speech
+            }
+        }
+    }
+
+    fn adapt(&mut self, energy: f32, rate: f32) {
+        self.noise_floor += (energy - self.noise_floor) * rate;
+    }
+
+    fn push_pre_roll(&mut self, samples: &[i16], chunk_ms: u64) {
+        self.pre_roll.push_back(samples.to_vec());
+        self.pre_roll_len_ms += chunk_ms;
+
+        // Keep enough for the pre-roll plus the onset chunks themselves
+        let keep_ms = self.config.pre_roll_ms + self.config.onset_ms;
+        while let Some(front) = self.pre_roll.front() {
+            let front_ms = front.len() as u64 * 1000 / SAMPLE_RATE as u64;
+            if self.pre_roll_len_ms - front_ms < keep_ms {
+                break;
+            }
+            self.pre_roll_len_ms -= front_ms;
+            self.pre_roll.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 64 ms chunk at 16 kHz
+    const CHUNK: usize = 1024;
+
+    fn silence() -> Vec<i16> {
+        vec![0; CHUNK]
+    }
+
+    fn speech() -> Vec<i16> {
+        (0..CHUNK)
+            .map(|i| if i % 2 == 0 { 4000 } else { -4000 })
+            .collect()
+    }
+
+    #[test]
+    fn test_utterance_start_and_end() {
+        let mut vad = VoiceActivityDetector::default();
+
+        for _ in 0..5 {
+            assert_eq!(vad.process(&silence()), VadEvent::Silence);
+        }
+
+        // First speech chunk is still within the onset window
+        assert_eq!(vad.process(&speech()), VadEvent::Silence);
+        match vad.process(&speech()) {
+            VadEvent::UtteranceStart(audio) => {
+                // Pre-roll silence + both onset chunks
+                assert!(audio.len() > 2 * CHUNK);
+            }
+            other => panic!("Expected UtteranceStart, got {:?}", other),
+        }
+        assert!(vad.is_active());
+
+        for _ in 0..5 {
+            assert_eq!(vad.process(&speech()), VadEvent::Speech);
+        }
+
+        // 700 ms hangover = 11 chunks of 64 ms
+        let mut event = VadEvent::Speech;
+        for _ in 0..11 {
+            event = vad.process(&silence());
+        }
+        assert!(matches!(event, VadEvent::UtteranceEnd { .. }));
+        assert!(!vad.is_active());
+    }
+
+    #[test]
+    fn test_short_burst_discarded() {
+        let mut vad = VoiceActivityDetector::new(VadConfig {
+            onset_ms: 0,
+            ..Default::default()
+        });
+
+        assert!(matches!(
+            vad.process(&speech()),
+            VadEvent::UtteranceStart(_)
+        ));
+
+        let mut event = VadEvent::Speech;
+        for _ in 0..11 {
+            event = vad.process(&silence());
+        }
+        assert_eq!(event, VadEvent::Discarded);
+    }
+
+    #[test]
+    fn test_max_utterance_length() {
+        let mut vad = VoiceActivityDetector::new(VadConfig {
+            onset_ms: 0,
+            max_utterance_ms: 640,
+            ..Default::default()
+        });
+
+        assert!(matches!(
+            vad.process(&speech()),
+            VadEvent::UtteranceStart(_)
+        ));
+        let mut chunks = 1;
+        while vad.process(&speech()) == VadEvent::Speech {
+            chunks += 1;
+            assert!(chunks < 20, "Utterance was never force-ended");
+        }
+        assert!(!vad.is_active());
+    }
+
+    #[test]
+    fn test_noise_floor_adapts() {
+        let mut vad = VoiceActivityDetector::default();
+        let hum: Vec<i16> = vec![250; CHUNK];
+
+        for _ in 0..200 {
+            assert_eq!(vad.process(&hum), VadEvent::Silence);
+        }
+        assert!(vad.noise_floor() > 200.0);
+        assert!(vad.threshold() > 600.0);
+    }
+
+    #[test]
+    fn test_late_noise_raises_floor() {
+        let mut vad = VoiceActivityDetector::default();
+        for _ in 0..5 {
+            vad.process(&silence());
+        }
+
+        // Engine rumble starts: first taken for speech, then learned as noise
+        let rumble: Vec<i16> = vec![2000; CHUNK];
+        let mut events = Vec::new();
+        for _ in 0..60 {
+            events.push(vad.process(&rumble));
+        }
+        assert!(events.contains(&VadEvent::Discarded));
+        assert!(vad.noise_floor() > 1500.0);
+        assert_eq!(vad.process(&rumble), VadEvent::Silence);
+        assert!(!vad.is_active());
+    }
+}
@@ -2,16 +2,23 @@
 //!
 //! Handles voice command matching and action execution.
 
+use crate::asr::AsrAlternative;
+use crate::core::command_modifiers::{self, CommandModifiers};
+use crate::core::macro_runner::{InFlight, MacroRunner, SpeechWaiters};
 use crate::core::ollama::{Intent, OllamaHandler};
+use crate::core::trigger_template::{fill_slots, tokenize, SlotValues, TriggerTemplate};
+use crate::games::{TagKind, TagState};
 use crate::input::{parse_key, VirtualKeyboard};
 use crate::player_manager::PlayerManager;
 use crate::utils::fuzzy::similarity;
 use anyhow::Result;
 use evdev::Key;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::sync::{Arc, Mutex};
-use tracing::{debug, error, info, warn};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
 
 /// A step in a macro
 #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
@@ -34,14 +41,153 @@ pub struct MacroStep {
     /// LAL audio ID for content pack lookup
     #[serde(default)]
     pub audio_feedback: Option<String>,
+    /// Speech, media or typing, run before the step's key press
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effect: Option<StepEffect>,
+    /// Control flow, run after the step's sound and key press
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flow: Option<StepFlow>,
 }
 
-/// A macro consisting of multiple steps
+/// Non-keyboard work a macro step can do
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StepEffect {
+    /// Say a line with the TTS voice (the macro waits until it's spoken)
+    Speak { text: String },
+    /// Control the media player
+    Player { command: PlayerCommand },
+    /// Type literal text on the virtual keyboard (US layout)
+    Type { text: String },
+    /// Set the sound effect volume (1.0 is normal)
+    Volume { level: f32 },
+}
+
+/// Media player command for a macro step
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PlayerCommand {
+    /// Toggle between playing and paused
+    PlayPause,
+    /// Pause (stays paused if it already is)
+    Pause,
+    /// Start or resume playback
+    #[serde(alias = "resume")]
+    Play,
+    Next,
+    Previous,
+    Stop,
+    VolumeUp,
+    VolumeDown,
+}
+
+/// How long a `wait_for` step waits before stopping its macro
+pub const DEFAULT_WAIT_TIMEOUT_MS: u64 = 10_000;
+
+/// Most times a `repeat` step may run its steps; larger counts are clamped
+/// when profiles load
+pub const MAX_STEP_REPEAT: u32 = 100;
+
+fn default_wait_timeout() -> u64 {
+    DEFAULT_WAIT_TIMEOUT_MS
+}
+
+/// Control flow within a macro step
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StepFlow {
+    /// Run another command (usually a macro) by name
+    Call { name: String },
+    /// Run the nested steps several times
+    Repeat { times: u32, steps: Vec<MacroStep> },
+    /// Pause until one of the phrases is heard; the macro stops on timeout
+    WaitFor {
+        phrases: Vec<String>,
+        #[serde(default = "default_wait_timeout")]
+        timeout_ms: u64,
+    },
+    /// Run `then` if the condition holds, `else` otherwise
+    If {
+        condition: StepCondition,
+        #[serde(default)]
+        then: Vec<MacroStep>,
+        #[serde(default, rename = "else")]
+        otherwise: Vec<MacroStep>,
+    },
+}
+
+/// Tracked state a macro can branch on
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum StepCondition {
+    /// A toggle believed to be on (or off); unknown state never matches
+    Toggle { name: String, on: bool },
+    /// The active game profile (by name, case-insensitive)
+    Profile { name: String },
+}
+
+/// A macro consisting of multiple steps
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 pub struct Macro {
     pub name: String,
     pub triggers: Vec<String>,
     pub steps: Vec<MacroStep>,
+    /// Command (by name) that reverses this macro, run by "undo"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub undo: Option<String>,
+}
+
+impl Macro {
+    /// Phrases its `wait_for` steps listen for, for recognizer grammars
+    pub fn wait_phrases(&self) -> Vec<&str> {
+        fn collect<'a>(steps: &'a [MacroStep], phrases: &mut Vec<&'a str>) {
+            for step in steps {
+                match &step.flow {
+                    Some(StepFlow::WaitFor { phrases: p, .. }) => {
+                        phrases.extend(p.iter().map(String::as_str))
+                    }
+                    Some(StepFlow::Repeat { steps, .. }) => collect(steps, phrases),
+                    Some(StepFlow::If {
+                        then, otherwise, ..
+                    }) => {
+                        collect(then, phrases);
+                        collect(otherwise, phrases);
+                    }
+                    Some(StepFlow::Call { .. }) | None => {}
+                }
+            }
+        }
+        let mut phrases = Vec::new();
+        collect(&self.steps, &mut phrases);
+        phrases
+    }
+
+    /// Limit its `repeat` steps to [`MAX_STEP_REPEAT`]; true if any was lowered
+    pub fn clamp_repeats(&mut self) -> bool {
+        fn clamp(steps: &mut [MacroStep]) -> bool {
+            let mut clamped = false;
+            for step in steps {
+                match &mut step.flow {
+                    Some(StepFlow::Repeat { times, steps }) => {
+                        if *times > MAX_STEP_REPEAT {
+                            *times = MAX_STEP_REPEAT;
+                            clamped = true;
+                        }
+                        clamped |= clamp(steps);
+                    }
+                    Some(StepFlow::If {
+                        then, otherwise, ..
+                    }) => {
+                        clamped |= clamp(then);
+                        clamped |= clamp(otherwise);
+                    }
+                    Some(StepFlow::Call { .. } | StepFlow::WaitFor { .. }) | None => {}
+                }
+            }
+            clamped
+        }
+        clamp(&mut self.steps)
+    }
 }
 
 /// A voice command binding
@@ -60,6 +206,110 @@ pub enum Command {
     Macro(Macro),
 }
 
+impl Command {
+    /// Display name of the action or macro
+    pub fn name(&self) -> &str {
+        match self {
+            Command::Action { name, .. } => name,
+            Command::Macro(m) => &m.name,
+        }
+    }
+
+    /// Voice triggers bound to this command
+    pub fn triggers(&self) -> &[String] {
+        match self {
+            Command::Action { triggers, .. } => triggers,
+            Command::Macro(m) => &m.triggers,
+        }
+    }
+
+    /// Copy with `{slot}` placeholders filled in from a templated trigger match
+    ///
+    /// Substitutes into the name, the key of an action, and the macro steps'
+    /// action IDs (so `Select Weapon Group {n}` resolves to a concrete binding).
+    pub fn bind(&self, values: &SlotValues) -> Command {
+        if values.is_empty() {
+            return self.clone();
+        }
+        match self {
+            Command::Action {
+                name,
+                triggers,
+                key,
+                modifiers,
+            } => Command::Action {
+                name: fill_slots(name, values),
+                triggers: triggers.clone(),
+                key: fill_slots(key, values),
+                modifiers: modifiers.clone(),
+            },
+            Command::Macro(m) => Command::Macro(Macro {
+                name: fill_slots(&m.name, values),
+                triggers: m.triggers.clone(),
+                steps: m
+                    .steps
+                    .iter()
+                    .map(|step| MacroStep {
+                        action: fill_slots(&step.action, values),
+                        ..step.clone()
+                    })
+                    .collect(),
+                ..m.clone()
+            }),
+        }
+    }
+}
+
+/// A candidate command for an utterance, as ranked by
+/// [`CommandProcessor::rank_commands`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandMatch {
+    /// The command, with slot values filled in
+    pub command: Command,
+    /// The trigger that matched
+    pub trigger: String,
+    /// 0.0-1.0: whole-word matches score from `WORD_MATCH_BASE` (1.0 when the
+    /// trigger is the entire utterance), phonetic matches below it
+    pub score: f64,
+    /// Literal (non-slot) trigger words, to rank equal scores
+    pub specificity: usize,
+}
+
+impl CommandMatch {
+    fn ranks_above(&self, other: &CommandMatch) -> bool {
+        (self.score, self.specificity) > (other.score, other.specificity)
+    }
+
+    /// Whether two matches are indistinguishable by rank
+    fn ties_with(&self, other: &CommandMatch) -> bool {
+        (self.score - other.score).abs() < SCORE_TIE_EPSILON
+            && self.specificity == other.specificity
+    }
+}
+
+/// Lowest score of a whole-word trigger match (one covering none of the rest
+/// of the utterance); phonetic matches are scaled to stay below it
+const WORD_MATCH_BASE: f64 = 0.75;
+
+/// Scores closer than this count as a tie
+const SCORE_TIE_EPSILON: f64 = 1e-6;
+
+fn is_conjunction(token: &str) -> bool {
+    CONJUNCTIONS.contains(&token)
+}
+
+/// The leading matches that tie with the best one (empty if none matched)
+fn tied_leaders(ranked: Vec<CommandMatch>) -> Vec<CommandMatch> {
+    let mut leaders: Vec<CommandMatch> = Vec::new();
+    for candidate in ranked {
+        match leaders.first() {
+            Some(top) if !candidate.ties_with(top) => break,
+            _ => leaders.push(candidate),
+        }
+    }
+    leaders
+}
+
 /// Result of processing a voice command
 #[derive(Debug, Clone)]
 pub enum ProcessResult {
@@ -76,12 +326,57 @@ pub enum ProcessResult {
         query: String,
         results: Vec<crate::players::SearchResult>,
     },
-    /// Requires verbal confirmation for high-risk action
-    ConfirmationRequired { action: String, command: Command },
+    /// Requires verbal confirmation for high-risk action; once confirmed, run
+    /// it with [`CommandProcessor::run_confirmed`]
+    ConfirmationRequired {
+        action: String,
+        command: CommandMatch,
+        modifiers: CommandModifiers,
+    },
+    /// A toggle is already in the requested state, so nothing was pressed
+    AlreadySet { action: String, on: bool },
+    /// Understood, but there was nothing to act on ("nothing to undo");
+    /// `reason` is the reply for the user
+    Declined { reason: String },
+    /// Several game commands matched equally well; the user picks one, which
+    /// runs with [`CommandProcessor::run_confirmed`]
+    CommandChoiceRequired {
+        query: String,
+        candidates: Vec<CommandMatch>,
+        modifiers: CommandModifiers,
+    },
     /// No command matched
     NotFound,
 }
 
+/// Phrases handled by the fast keyword layer (keep in sync with `check_fast_keywords`)
+pub const FAST_KEYWORD_PHRASES: &[&str] = &[
+    "stop",
+    "stop music",
+    "pause",
+    "resume",
+    "play",
+    "next",
+    "next track",
+    "next song",
+    "skip",
+    "previous",
+    "previous track",
+    "back",
+    "volume up",
+    "louder",
+    "volume down",
+    "quieter",
+    "what's playing",
+    "what is playing",
+];
+
+/// Answers accepted while a selection or confirmation prompt is open
+pub const PROMPT_PHRASES: &[&str] = &[
+    "one", "two", "three", "first", "second", "third", "confirm", "yes", "do it", "cancel", "no",
+    "abort",
+];
+
 /// Commands that require verbal confirmation for safety (Stamos requirement)
 const DANGEROUS_COMMANDS: &[&str] = &[
     "self destruct",
@@ -91,19 +386,97 @@ const DANGEROUS_COMMANDS: &[&str] = &[
     "self-destruct",
 ];
 
+/// Whether a command needs verbal confirmation before it runs
+pub fn is_dangerous(name: &str) -> bool {
+    let name = name.to_lowercase();
+    DANGEROUS_COMMANDS.iter().any(|c| name.contains(c))
+}
+
+/// Phrases that reverse the last command ("cancel that")
+pub const UNDO_PHRASES: &[&str] = &["undo", "undo that", "cancel that", "scratch that"];
+
+/// Phrases that stop running macros and release held keys
+pub const ABORT_PHRASES: &[&str] = &["abort", "belay that", "stop macro", "abort macro"];
+
+/// Phrases that run the last command again
+pub const REPEAT_PHRASES: &[&str] = &["again", "repeat", "repeat that", "do that again"];
+
+/// Executed commands kept for undo/repeat
+const MAX_HISTORY: usize = 20;
+
+/// Words that join several commands in one utterance ("gear and lights on")
+pub const CONJUNCTIONS: &[&str] = &["and", "then"];
+
+/// Default pause between the commands of a compound utterance
+pub const DEFAULT_COMPOUND_GAP: Duration = Duration::from_millis(150);
+
+/// Pause between repetitions of a repeated command, so the game sees each press
+const REPEAT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How long a partial hypothesis must stay unchanged before firing early
+/// (measured in time so it doesn't depend on the capture chunk size)
+const PARTIAL_STABLE_TIME: Duration = Duration::from_millis(200);
+
+/// How running a matched command went
+enum Outcome {
+    /// Pressed already
+    Done,
+    /// Still running in the background (repeats, holds and macros)
+    Running(JoinHandle<()>),
+    /// A toggle already in the requested state, so nothing was pressed
+    AlreadySet(bool),
+}
+
+/// A command that ran, as recorded for undo/repeat
+#[derive(Debug, Clone)]
+struct HistoryEntry {
+    matched: CommandMatch,
+    /// Modifiers as performed (explicit toggle requests always press once)
+    modifiers: CommandModifiers,
+}
+
+/// Partial hypotheses seen for the utterance in progress
+#[derive(Debug, Clone, Default)]
+struct PartialTracker {
+    text: String,
+    /// When `text` was first seen
+    since: Option<std::time::Instant>,
+    /// The command that fired early in this utterance
+    fired: Option<CommandMatch>,
+}
+
 /// Command processor that matches voice input to actions
 #[derive(Clone)]
 pub struct CommandProcessor {
     commands: Vec<Command>,
+    /// Parsed triggers (literal words and slots), keyed by trigger text
+    templates: HashMap<String, TriggerTemplate>,
+    /// Early-fire state for the current utterance
+    partial: PartialTracker,
+    /// Pause between the commands of a compound utterance
+    compound_gap: Duration,
     pub keyboard: Arc<Mutex<Option<VirtualKeyboard>>>,
     /// Map of Action ID -> KeyBinding (populated by the active game profile)
     action_map: HashMap<String, crate::games::KeyBinding>,
+    /// Toggle behaviour and believed state by command name (shared by clones,
+    /// so state learned by one processing task is seen by the next)
+    toggles: Arc<Mutex<HashMap<String, TagState>>>,
+    /// Recently executed game commands, oldest first (shared by clones)
+    history: Arc<Mutex<VecDeque<HistoryEntry>>>,
+    /// Name of the active game profile, for macros that branch on it
+    active_profile: Option<String>,
+    /// Running macros paused until a phrase is heard
+    waiters: SpeechWaiters,
+    /// Running macros and held keys, for "abort"
+    in_flight: InFlight,
     /// Audio engine for SFX
     pub sound_engine: Option<Arc<crate::audio::SoundEngine>>,
     /// Ollama Intent Handler
     pub ollama_handler: Option<OllamaHandler>,
     /// Player Manager for media control
     pub player_manager: Option<Arc<PlayerManager>>,
+    /// Voice for macro `speak` steps
+    pub tts: Option<Arc<dyn crate::tts::TtsEngine>>,
     /// LAL Manager for content packs
     pub lal_manager: Option<Arc<crate::lal::LALManager>>,
 }
@@ -121,13 +494,23 @@ impl CommandProcessor {
             }
         };
 
+        let keyboard = Arc::new(Mutex::new(keyboard));
         Ok(Self {
             commands: Vec::new(),
-            keyboard: Arc::new(Mutex::new(keyboard)),
+            templates: HashMap::new(),
+            partial: PartialTracker::default(),
+            compound_gap: DEFAULT_COMPOUND_GAP,
+            in_flight: InFlight::new(keyboard.clone()),
+            keyboard,
             action_map: HashMap::new(),
+            toggles: Arc::new(Mutex::new(HashMap::new())),
+            history: Arc::new(Mutex::new(VecDeque::new())),
+            active_profile: None,
+            waiters: SpeechWaiters::default(),
             sound_engine: None,
             ollama_handler: None,
             player_manager: None,
+            tts: None,
             lal_manager: None,
         })
     }
@@ -146,11 +529,21 @@ impl CommandProcessor {
         self.ollama_handler = Some(handler);
     }
 
+    /// Set the pause between commands spoken in one utterance
+    pub fn set_compound_gap(&mut self, gap: Duration) {
+        self.compound_gap = gap;
+    }
+
     /// Set Player Manager
     pub fn set_player_manager(&mut self, manager: Arc<PlayerManager>) {
         self.player_manager = Some(manager);
     }
 
+    /// Set the TTS engine used by macro `speak` steps
+    pub fn set_tts(&mut self, tts: Arc<dyn crate::tts::TtsEngine>) {
+        self.tts = Some(tts);
+    }
+
     /// Update the action map from the current game profile
     pub fn set_action_map(&mut self, map: HashMap<String, crate::games::KeyBinding>) {
         self.action_map = map;
@@ -161,11 +554,102 @@ impl CommandProcessor {
         self.action_map.clone()
     }
 
+    /// Handle for stopping running macros and held keys from elsewhere (IPC)
+    pub fn in_flight(&self) -> InFlight {
+        self.in_flight.clone()
+    }
+
+    /// Set the name of the active game profile
+    pub fn set_active_profile(&mut self, name: Option<String>) {
+        self.active_profile = name;
+    }
+
+    /// Replace the toggle states (from the active game profile's `tag_states`)
+    pub fn set_toggles(&mut self, toggles: HashMap<String, TagState>) {
+        *self.toggles.lock().expect("Toggle mutex poisoned") = toggles;
+    }
+
+    /// Believed state of a toggle (None if unknown or not a toggle)
+    pub fn toggle_state(&self, name: &str) -> Option<bool> {
+        let toggles = self.toggles.lock().expect("Toggle mutex poisoned");
+        toggles
+            .get(name)
+            .filter(|t| t.kind == TagKind::Toggle)
+            .and_then(|t| t.state)
+    }
+
+    /// Correct a toggle's believed state from an external source (game status
+    /// file, the user, a key press we didn't make). Returns false if `name` is
+    /// not a toggle.
+    pub fn sync_toggle(&self, name: &str, state: Option<bool>) -> bool {
+        let mut toggles = self.toggles.lock().expect("Toggle mutex poisoned");
+        match toggles.get_mut(name).filter(|t| t.kind == TagKind::Toggle) {
+            Some(toggle) => {
+                if toggle.state != state {
+                    debug!(
+                        "🔄 Toggle '{}' resynced: {:?} -> {:?}",
+                        name, toggle.state, state
+                    );
+                }
+                toggle.state = state;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The state an on/off trigger of a toggle asks for
+    fn requested_toggle_state(&self, name: &str, trigger: &str) -> Option<bool> {
+        let toggles = self.toggles.lock().expect("Toggle mutex poisoned");
+        toggles
+            .get(name)
+            .filter(|t| t.kind == TagKind::Toggle)
+            .and_then(|t| t.requested_state(trigger))
+    }
+
+    /// Update a toggle's believed state after `presses` presses
+    fn record_toggle(&self, name: &str, requested: Option<bool>, presses: u32) {
+        let mut toggles = self.toggles.lock().expect("Toggle mutex poisoned");
+        let Some(toggle) = toggles.get_mut(name).filter(|t| t.kind == TagKind::Toggle) else {
+            return;
+        };
+        toggle.state = match requested {
+            Some(on) => Some(on),
+            None => toggle.state.map(|on| on ^ (presses % 2 == 1)),
+        };
+    }
+
     /// Add a command
     pub fn add_command(&mut self, command: Command) {
+        for trigger in command.triggers() {
+            if self.templates.contains_key(trigger) {
+                continue;
+            }
+            match TriggerTemplate::parse(trigger) {
+                Ok(template) => {
+                    self.templates.insert(trigger.clone(), template);
+                }
+                Err(e) => warn!("⚠️ Ignoring trigger of '{}': {:#}", command.name(), e),
+            }
+        }
         self.commands.push(command);
     }
 
+    /// Remove all commands (e.g. before loading another game profile)
+    pub fn clear_commands(&mut self) {
+        self.commands.clear();
+        self.templates.clear();
+        // Undoing a command of the previous profile would press the wrong key
+        self.history.lock().expect("History mutex poisoned").clear();
+        self.reset_partial();
+    }
+
+    /// Recently executed game commands, oldest first
+    pub fn history(&self) -> Vec<Command> {
+        let history = self.history.lock().expect("History mutex poisoned");
+        history.iter().map(|e| e.matched.command.clone()).collect()
+    }
+
     /// Add default demo bindings
     pub fn add_demo_bindings(&mut self) {
         self.add_command(Command::Action {
@@ -183,46 +667,692 @@ impl CommandProcessor {
     }
 
     /// Match voice input to a command (without executing)
+    ///
+    /// Returns the top-ranked command; see [`Self::rank_commands`] for the
+    /// scores and for detecting ties.
     pub fn match_command(&self, text: &str) -> Option<Command> {
+        self.rank_commands(text)
+            .into_iter()
+            .next()
+            .map(|m| m.command)
+    }
+
+    /// Score every command against `text`, best first
+    ///
+    /// Triggers match on whole words. A trigger covering more of the utterance
+    /// ranks higher, so "landing gear" beats "gear" and "jump to nav beacon"
+    /// prefers a "nav beacon" command over "jump". Equal scores are ordered by
+    /// specificity (literal words over slots). Commands without a word match
+    /// fall back to phonetic similarity and always rank below word matches.
+    pub fn rank_commands(&self, text: &str) -> Vec<CommandMatch> {
         let text_lower = text.to_lowercase();
+        let tokens = tokenize(&text_lower);
+        if tokens.is_empty() {
+            return Vec::new();
+        }
 
-        // 1. Precise match
-        if let Some(cmd) = self.commands.iter().find(|cmd| {
-            let triggers = match cmd {
-                Command::Action { triggers, .. } => triggers,
-                Command::Macro(m) => &m.triggers,
-            };
-            triggers.iter().any(|t| text_lower.contains(t))
-        }) {
-            return Some(cmd.clone());
+        let mut ranked: Vec<CommandMatch> = Vec::new();
+        for cmd in &self.commands {
+            let mut best: Option<CommandMatch> = None;
+            for trigger in cmd.triggers() {
+                let Some(candidate) = self.score_trigger(cmd, trigger, &text_lower, &tokens) else {
+                    continue;
+                };
+                if best.as_ref().is_none_or(|b| candidate.ranks_above(b)) {
+                    best = Some(candidate);
+                }
+            }
+            ranked.extend(best);
+        }
+
+        ranked.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then(b.specificity.cmp(&a.specificity))
+        });
+        ranked
+    }
+
+    /// Score one trigger of `cmd` against the utterance
+    fn score_trigger(
+        &self,
+        cmd: &Command,
+        trigger: &str,
+        text_lower: &str,
+        tokens: &[String],
+    ) -> Option<CommandMatch> {
+        // 1. Whole-word match, scored by how much of the utterance it covers
+        if let Some(template) = self.templates.get(trigger) {
+            if let Some(found) = template.find_in(tokens) {
+                let coverage = (found.end - found.start) as f64 / tokens.len() as f64;
+                return Some(CommandMatch {
+                    command: cmd.bind(&found.values),
+                    trigger: trigger.to_string(),
+                    score: WORD_MATCH_BASE + (1.0 - WORD_MATCH_BASE) * coverage,
+                    specificity: template.literal_words(),
+                });
+            }
         }
 
         // 2. Phonetic fallback (Wendy Chisholm requirement)
-        // Check if any trigger is phonetically similar to the input
-        let mut best_match: Option<(f64, &Command)> = None;
+        // Templated triggers only match with a valid slot value
+        if TriggerTemplate::is_template(trigger) {
+            return None;
+        }
+        let similarity = similarity(text_lower, &trigger.to_lowercase());
+        // Lowered from 0.8 for better recall (Wendy Chisholm requirement)
+        (similarity > 0.7).then(|| CommandMatch {
+            command: cmd.clone(),
+            trigger: trigger.to_string(),
+            score: similarity * WORD_MATCH_BASE,
+            specificity: 0,
+        })
+    }
 
-        for cmd in &self.commands {
-            let triggers = match cmd {
-                Command::Action { triggers, .. } => triggers,
-                Command::Macro(m) => &m.triggers,
+    /// Match every ASR hypothesis and keep the best joint score
+    ///
+    /// The joint score is the trigger match score weighted by the hypothesis
+    /// confidence, so a runner-up that names a command exactly can beat a top
+    /// result that is only a near-miss. Returns the command and the index of the
+    /// hypothesis it came from.
+    pub fn match_hypotheses(&self, hypotheses: &[AsrAlternative]) -> Option<(Command, usize)> {
+        self.best_hypothesis_matches(hypotheses)
+            .map(|(mut leaders, idx, _)| (leaders.remove(0).command, idx))
+    }
+
+    /// Split a compound utterance ("boost then hardpoints") into its commands
+    ///
+    /// Returns `None` unless there are at least two segments and each one
+    /// matches exactly one command, or when the best match for the whole
+    /// utterance is a trigger that itself contains a conjunction ("search and
+    /// destroy").
+    /// Each segment may carry its own modifiers ("next target twice and boost").
+    pub fn match_compound(&self, text: &str) -> Option<Vec<(CommandMatch, CommandModifiers)>> {
+        let tokens = tokenize(text);
+        if !tokens.iter().any(|t| is_conjunction(t)) {
+            return None;
+        }
+        let joined_trigger = self
+            .rank_commands(text)
+            .first()
+            .is_some_and(|m| tokenize(&m.trigger).iter().any(|t| is_conjunction(t)));
+        if joined_trigger {
+            return None;
+        }
+
+        let segments: Vec<String> = tokens
+            .split(|t| is_conjunction(t))
+            .filter(|segment| !segment.is_empty())
+            .map(|segment| segment.join(" "))
+            .collect();
+        if segments.len() < 2 {
+            return None;
+        }
+        segments
+            .iter()
+            .map(|segment| {
+                let (segment, modifiers) = self.split_modifiers(segment);
+                let mut leaders = tied_leaders(self.rank_commands(&segment));
+                (leaders.len() == 1).then(|| (leaders.remove(0), modifiers))
+            })
+            .collect()
+    }
+
+    /// Split "five times" / "for two seconds" off `text`, unless the words
+    /// belong to a trigger (the unsplit text matches at least as well)
+    fn split_modifiers(&self, text: &str) -> (String, CommandModifiers) {
+        let (rest, modifiers) = command_modifiers::extract(text);
+        if modifiers.is_plain() {
+            return (text.to_string(), modifiers);
+        }
+        let top_score = |text: &str| self.rank_commands(text).first().map(|m| m.score);
+        match (top_score(&rest), top_score(text)) {
+            (Some(split), Some(whole)) if split <= whole => (text.to_string(), Default::default()),
+            (Some(_), _) => (rest, modifiers),
+            (None, _) => (text.to_string(), Default::default()),
+        }
+    }
+
+    /// Like [`Self::match_hypotheses`], but returns every command tied for the
+    /// lead on the winning hypothesis (a single entry when it is clear-cut),
+    /// plus the spoken modifiers of that hypothesis
+    fn best_hypothesis_matches(
+        &self,
+        hypotheses: &[AsrAlternative],
+    ) -> Option<(Vec<CommandMatch>, usize, CommandModifiers)> {
+        let mut best: Option<(f64, Vec<CommandMatch>, usize, CommandModifiers)> = None;
+
+        for (idx, hypothesis) in hypotheses.iter().enumerate() {
+            let (text, modifiers) = self.split_modifiers(&sanitize_transcription(&hypothesis.text));
+            let leaders = tied_leaders(self.rank_commands(&text));
+            let Some(top) = leaders.first() else {
+                continue;
+            };
+
+            let joint = top.score * f64::from(hypothesis.confidence);
+            debug!(
+                "N-best #{} '{}' -> {} (match {:.2}, joint {:.3}, {} tied)",
+                idx,
+                text,
+                top.command.name(),
+                top.score,
+                joint,
+                leaders.len()
+            );
+            if best.as_ref().is_none_or(|(b, ..)| joint > *b) {
+                best = Some((joint, leaders, idx, modifiers));
+            }
+        }
+
+        best.map(|(_, leaders, idx, modifiers)| (leaders, idx, modifiers))
+    }
+
+    /// Find the command whose trigger is exactly `text`
+    ///
+    /// Returns `None` if another command has the same trigger, or a longer
+    /// trigger of a different command starts with `text`, since the utterance
+    /// may still grow into that command.
+    fn match_exact_trigger(&self, text: &str) -> Option<&Command> {
+        let mut exact = self
+            .commands
+            .iter()
+            .filter(|cmd| cmd.triggers().iter().any(|t| t.to_lowercase() == text));
+        let first = exact.next()?;
+        if exact.any(|cmd| cmd.name() != first.name()) {
+            return None;
+        }
+
+        let prefix = format!("{} ", text);
+        let ambiguous = self.commands.iter().any(|cmd| {
+            cmd.name() != first.name()
+                && cmd
+                    .triggers()
+                    .iter()
+                    .any(|t| t.to_lowercase().starts_with(&prefix))
+        });
+
+        if ambiguous {
+            None
+        } else {
+            Some(first)
+        }
+    }
+
+    /// Execute a command as many times as asked, holding its key if asked
+    ///
+    /// Repeated and held commands, like macros, run in the background (and
+    /// can be aborted); only a single tap happens before this returns. The
+    /// handle of the background task is returned for callers that need to
+    /// wait for it.
+    async fn perform(
+        &self,
+        command: Command,
+        modifiers: CommandModifiers,
+    ) -> Result<Option<JoinHandle<()>>> {
+        let (name, key, key_modifiers) = match command {
+            Command::Macro(m) => {
+                if modifiers.hold.is_some() {
+                    warn!("⚠️ Macro '{}' can't be held, running it instead", m.name);
+                }
+                return self.spawn_macro(m, modifiers.repeat).map(Some);
+            }
+            Command::Action { .. } if modifiers.is_plain() => {
+                let mut kb_lock = self.keyboard.lock().expect("Keyboard mutex poisoned");
+                return self
+                    .execute_command_blocking(&mut kb_lock, command)
+                    .map(|_| None);
+            }
+            Command::Action {
+                name,
+                key,
+                modifiers,
+                ..
+            } => (name, key, modifiers),
+        };
+        if !self.has_keyboard() {
+            anyhow::bail!("No keyboard");
+        }
+        let combo = parse_combo(&key, &key_modifiers)?;
+        let claim = self
+            .in_flight
+            .claim(&name)
+            .ok_or_else(|| anyhow::anyhow!("'{}' is already running", name))?;
+
+        info!(
+            "🔁 Performing {} x{}{}",
+            name,
+            modifiers.repeat,
+            modifiers
+                .hold
+                .map(|d| format!(", held {:?}", d))
+                .unwrap_or_default()
+        );
+        let runner = self.macro_runner(None);
+        let task = tokio::spawn(async move {
+            runner
+                .run_repeated(
+                    &name,
+                    &combo,
+                    modifiers.hold,
+                    modifiers.repeat,
+                    REPEAT_INTERVAL,
+                    &claim,
+                )
+                .await;
+        });
+        Ok(Some(task))
+    }
+
+    /// Run a matched command, unless it is a toggle already in the state its
+    /// trigger asks for
+    async fn run_match(
+        &self,
+        matched: CommandMatch,
+        mut modifiers: CommandModifiers,
+    ) -> Result<Outcome> {
+        let name = matched.command.name().to_string();
+        let requested = self.requested_toggle_state(&name, &matched.trigger);
+        if let Some(on) = requested {
+            if self.toggle_state(&name) == Some(on) {
+                info!("✋ {} is already {}, not pressing", name, on_off(on));
+                return Ok(Outcome::AlreadySet(on));
+            }
+            // "deploy gear twice" still means "deployed"
+            modifiers.repeat = 1;
+        }
+
+        let running = self.perform(matched.command.clone(), modifiers).await?;
+        self.record_toggle(&name, requested, modifiers.repeat);
+        self.record_history(matched, modifiers);
+        Ok(match running {
+            Some(task) => Outcome::Running(task),
+            None => Outcome::Done,
+        })
+    }
+
+    /// Run a match the user confirmed or picked from a prompt
+    ///
+    /// Goes through the same toggle checks and history as any other match, so
+    /// "cancel that" and "again" work on it too.
+    pub async fn run_confirmed(
+        &self,
+        matched: CommandMatch,
+        modifiers: CommandModifiers,
+    ) -> ProcessResult {
+        let name = matched.command.name().to_string();
+        match self.run_match(matched, modifiers).await {
+            Ok(Outcome::AlreadySet(on)) => ProcessResult::AlreadySet { action: name, on },
+            Ok(_) => ProcessResult::Success(name),
+            Err(e) => {
+                warn!("❌ Failed to execute {}: {}", name, e);
+                ProcessResult::NotFound
+            }
+        }
+    }
+
+    /// Remember a command that ran, for undo/repeat
+    fn record_history(&self, matched: CommandMatch, modifiers: CommandModifiers) {
+        let mut history = self.history.lock().expect("History mutex poisoned");
+        if history.len() == MAX_HISTORY {
+            history.pop_front();
+        }
+        history.push_back(HistoryEntry { matched, modifiers });
+    }
+
+    /// "undo": reverse the last command
+    ///
+    /// A toggle is pressed again (if it was pressed an odd number of times); a
+    /// macro runs its declared `undo` command. Anything else can't be undone
+    /// and stays in the history.
+    async fn undo_last(&self) -> ProcessResult {
+        let Some(entry) = self
+            .history
+            .lock()
+            .expect("History mutex poisoned")
+            .pop_back()
+        else {
+            info!("↩️ Nothing to undo");
+            return ProcessResult::Declined {
+                reason: "Nothing to undo".to_string(),
             };
+        };
+        let name = entry.matched.command.name().to_string();
 
-            for trigger in triggers {
-                let score = similarity(&text_lower, trigger);
-                if score > 0.7 {
-                    // Lowered from 0.8 for better recall (Wendy Chisholm requirement)
-                    if best_match.is_none() || score > best_match.as_ref().unwrap().0 {
-                        best_match = Some((score, cmd));
+        let is_toggle = self
+            .toggles
+            .lock()
+            .expect("Toggle mutex poisoned")
+            .get(&name)
+            .is_some_and(|t| t.kind == TagKind::Toggle);
+        let inverse = match &entry.matched.command {
+            _ if is_toggle => {
+                (entry.modifiers.repeat % 2 == 1).then(|| entry.matched.command.clone())
+            }
+            Command::Macro(m) => m
+                .undo
+                .as_ref()
+                .and_then(|undo| self.commands.iter().find(|c| c.name() == undo))
+                .cloned(),
+            Command::Action { .. } => None,
+        };
+        let Some(inverse) = inverse else {
+            info!("↩️ {} can't be undone", name);
+            self.history
+                .lock()
+                .expect("History mutex poisoned")
+                .push_back(entry);
+            return ProcessResult::Declined {
+                reason: format!("{} can't be undone", name),
+            };
+        };
+
+        info!("↩️ Undoing {} with {}", name, inverse.name());
+        if let Err(e) = self.perform(inverse, CommandModifiers::default()).await {
+            warn!("❌ Failed to undo {}: {}", name, e);
+            self.history
+                .lock()
+                .expect("History mutex poisoned")
+                .push_back(entry);
+            return ProcessResult::NotFound;
+        }
+        if is_toggle {
+            self.record_toggle(&name, None, 1);
+        }
+        ProcessResult::Success(format!("Undo {}", name))
+    }
+
+    /// "again": run the last command once more, with the same modifiers
+    async fn repeat_last(&self) -> ProcessResult {
+        let last = self
+            .history
+            .lock()
+            .expect("History mutex poisoned")
+            .back()
+            .cloned();
+        let Some(entry) = last else {
+            info!("🔁 Nothing to repeat");
+            return ProcessResult::Declined {
+                reason: "Nothing to repeat".to_string(),
+            };
+        };
+        let name = entry.matched.command.name().to_string();
+
+        info!("🔁 Repeating {}", name);
+        match self.run_match(entry.matched, entry.modifiers).await {
+            Ok(Outcome::AlreadySet(on)) => ProcessResult::AlreadySet { action: name, on },
+            Ok(_) => ProcessResult::Success(name),
+            Err(e) => {
+                warn!("❌ Failed to repeat {}: {}", name, e);
+                ProcessResult::NotFound
+            }
+        }
+    }
+
+    /// Run the commands of a compound utterance in order, `compound_gap` apart
+    async fn execute_compound(
+        &self,
+        commands: Vec<(CommandMatch, CommandModifiers)>,
+    ) -> ProcessResult {
+        // Dangerous commands only run on their own, after confirmation
+        if let Some((m, modifiers)) = commands
+            .iter()
+            .find(|(m, _)| is_dangerous(m.command.name()))
+        {
+            warn!(
+                "⚠️ Compound command includes dangerous '{}', requesting confirmation for it alone",
+                m.command.name()
+            );
+            return ProcessResult::ConfirmationRequired {
+                action: m.command.name().to_string(),
+                command: m.clone(),
+                modifiers: *modifiers,
+            };
+        }
+
+        let names: Vec<String> = commands
+            .iter()
+            .map(|(m, _)| m.command.name().to_string())
+            .collect();
+        info!("🔗 Layer 2 (Compound) matched: {}", names.join(" → "));
+
+        let mut executed = Vec::new();
+        let mut already_set = None;
+        let mut segments = commands.into_iter().enumerate();
+        while let Some((i, (matched, modifiers))) = segments.next() {
+            if i > 0 {
+                tokio::time::sleep(self.compound_gap).await;
+            }
+            match self.run_match(matched, modifiers).await {
+                Ok(Outcome::Done) => executed.push(names[i].clone()),
+                Ok(Outcome::AlreadySet(on)) => {
+                    already_set.get_or_insert((names[i].clone(), on));
+                }
+                // Still repeating (or a macro): the rest waits for it, in the
+                // background so the caller isn't held up
+                Ok(Outcome::Running(task)) => {
+                    executed.push(names[i].clone());
+                    let rest: Vec<_> = segments.map(|(_, segment)| segment).collect();
+                    if !rest.is_empty() {
+                        executed.extend(rest.iter().map(|(m, _)| m.command.name().to_string()));
+                        let processor = self.clone();
+                        tokio::spawn(async move { processor.run_after(task, rest).await });
                     }
+                    break;
+                }
+                Err(e) => {
+                    warn!("❌ Failed to execute {}: {}", names[i], e);
+                    break;
                 }
             }
         }
 
-        best_match.map(|(_, cmd)| cmd.clone())
+        if !executed.is_empty() {
+            return ProcessResult::Success(executed.join(", "));
+        }
+        match already_set {
+            Some((action, on)) => ProcessResult::AlreadySet { action, on },
+            None => ProcessResult::NotFound,
+        }
+    }
+
+    /// Run the remaining segments of a compound utterance once `running` ends,
+    /// each `compound_gap` after the previous one has finished
+    async fn run_after(
+        &self,
+        running: JoinHandle<()>,
+        rest: Vec<(CommandMatch, CommandModifiers)>,
+    ) {
+        let mut running = Some(running);
+        for (matched, modifiers) in rest {
+            if let Some(task) = running.take() {
+                if task.await.is_err() {
+                    return;
+                }
+            }
+            tokio::time::sleep(self.compound_gap).await;
+            let name = matched.command.name().to_string();
+            match self.run_match(matched, modifiers).await {
+                Ok(Outcome::Running(task)) => running = Some(task),
+                Ok(_) => {}
+                Err(e) => {
+                    warn!("❌ Failed to execute {}: {}", name, e);
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Fire a game command from a stable partial hypothesis, without waiting for
+    /// the recognizer to finalize the utterance
+    ///
+    /// Only an exact, unambiguous trigger that has stayed the same for
+    /// `PARTIAL_STABLE_TIME` fires, and at most once per utterance.
+    /// Returns the command name if it was executed.
+    pub fn process_partial(&mut self, text: &str) -> Option<String> {
+        let text = sanitize_transcription(text);
+        if text.is_empty() || self.partial.fired.is_some() {
+            return None;
+        }
+
+        if text != self.partial.text || self.partial.since.is_none() {
+            self.partial.text = text.clone();
+            self.partial.since = Some(std::time::Instant::now());
+        }
+        if self
+            .partial
+            .since
+            .is_some_and(|since| since.elapsed() < PARTIAL_STABLE_TIME)
+        {
+            return None;
+        }
+
+        let cmd = self.match_exact_trigger(&text)?.clone();
+        let name = cmd.name().to_string();
+
+        // Dangerous commands always go through the confirmation flow on the final result
+        if is_dangerous(&name) {
+            return None;
+        }
+        // A toggle already in the requested state is left to the final, which
+        // says so instead of pressing
+        let requested = self.requested_toggle_state(&name, &text);
+        if requested.is_some() && self.toggle_state(&name) == requested {
+            return None;
+        }
+
+        let mut kb_lock = self.keyboard.lock().expect("Keyboard mutex poisoned");
+        if let Err(e) = self.execute_command_blocking(&mut kb_lock, cmd.clone()) {
+            warn!("❌ Failed to execute {} from partial: {}", name, e);
+            return None;
+        }
+        drop(kb_lock);
+        self.record_toggle(&name, requested, 1);
+
+        info!("⚡ Early fire from stable partial '{}': {}", text, name);
+        let matched = CommandMatch {
+            command: cmd,
+            trigger: text,
+            score: 1.0,
+            specificity: 0,
+        };
+        self.record_history(matched.clone(), CommandModifiers::default());
+        self.partial.fired = Some(matched);
+        Some(name)
+    }
+
+    /// Perform the modifiers the final transcript adds to a command that
+    /// already fired from a partial
+    ///
+    /// "next target five times" pressed "next target" once from the partial;
+    /// the other four presses follow here (a hold is performed in full).
+    /// Returns the command name if it ran again.
+    pub async fn finish_early_fire(&self, final_text: &str) -> Option<String> {
+        let fired = self.partial.fired.as_ref()?;
+        let tokens = tokenize(final_text);
+        let clause: Vec<&str> = tokens
+            .iter()
+            .map(String::as_str)
+            .take_while(|t| !is_conjunction(t))
+            .collect();
+        let (rest, modifiers) = self.split_modifiers(&clause.join(" "));
+        if modifiers.is_plain() || tokenize(&rest) != tokenize(&self.partial.text) {
+            return None;
+        }
+
+        let name = fired.command.name().to_string();
+        // "deploy gear twice" still means "deployed"
+        if self.requested_toggle_state(&name, &fired.trigger).is_some() {
+            return None;
+        }
+        let extra = CommandModifiers {
+            repeat: match modifiers.hold {
+                Some(_) => modifiers.repeat,
+                None => modifiers.repeat - 1,
+            },
+            hold: modifiers.hold,
+        };
+        if extra.repeat == 0 {
+            return None;
+        }
+
+        info!("⚡ Completing early-fired {} with {:?}", name, extra);
+        let performed = match fired.command.clone() {
+            // The early run still holds the macro's name; queue behind it
+            Command::Macro(m) => {
+                self.queue_macro(m, extra.repeat);
+                Ok(None)
+            }
+            command => self.perform(command, extra).await,
+        };
+        if let Err(e) = performed {
+            warn!("❌ Failed to complete {}: {}", name, e);
+            return None;
+        }
+        self.record_toggle(&name, None, extra.repeat);
+        // "again" repeats what was said, not just the early press
+        if let Some(last) = self
+            .history
+            .lock()
+            .expect("History mutex poisoned")
+            .back_mut()
+            .filter(|last| last.matched == *fired)
+        {
+            last.modifiers = modifiers;
+        }
+        Some(name)
+    }
+
+    /// What the user said after "and"/"then" following a command that already
+    /// fired from a partial, e.g. "lights on" when "deploy gear" fired early
+    /// from "deploy gear and lights on"
+    pub fn early_fire_remainder(&self, final_text: &str) -> Option<String> {
+        self.partial.fired.as_ref()?;
+        let fired = tokenize(&self.partial.text);
+        if fired.is_empty() {
+            return None;
+        }
+        let tokens = tokenize(final_text);
+        let end = tokens
+            .windows(fired.len())
+            .position(|w| w == fired.as_slice())?
+            + fired.len();
+
+        let rest = &tokens[end..];
+        if !rest.first().is_some_and(|t| is_conjunction(t)) {
+            return None;
+        }
+        let rest: Vec<&str> = rest
+            .iter()
+            .map(String::as_str)
+            .skip_while(|t| is_conjunction(t))
+            .collect();
+        (!rest.is_empty()).then(|| rest.join(" "))
+    }
+
+    /// Forget partial hypotheses (call when an utterance ends)
+    pub fn reset_partial(&mut self) {
+        self.partial = PartialTracker::default();
     }
 
     /// Process voice input with Triple-Layer Strategy
     pub async fn process(&mut self, text: &str) -> ProcessResult {
+        self.process_hypotheses(&[AsrAlternative {
+            text: text.to_string(),
+            confidence: 1.0,
+        }])
+        .await
+    }
+
+    /// Process an ASR n-best list (best first)
+    ///
+    /// Game commands are matched against every hypothesis; the other layers only
+    /// see the top one.
+    pub async fn process_hypotheses(&mut self, hypotheses: &[AsrAlternative]) -> ProcessResult {
+        self.reset_partial();
+        let Some(top) = hypotheses.first() else {
+            return ProcessResult::NotFound;
+        };
+        let text = top.text.as_str();
         let text_sanitized = sanitize_transcription(text);
         let text_lower = text_sanitized.to_lowercase();
         info!(
@@ -230,42 +1360,82 @@ impl CommandProcessor {
             text_lower, text
         );
 
+        // A running macro waiting for this phrase takes it first
+        if let Some(name) = self.waiters.resume(&text_lower) {
+            return ProcessResult::Success(format!("{} resumed", name));
+        }
+
+        // "abort": stop running macros and held keys
+        if ABORT_PHRASES.contains(&text_lower.as_str()) {
+            let aborted = self.in_flight.abort_all();
+            if aborted.is_empty() {
+                info!("🛑 Nothing running to abort");
+                return ProcessResult::Declined {
+                    reason: aborted.to_string(),
+                };
+            }
+            return ProcessResult::Success(aborted.to_string());
+        }
+
         // LAYER 1: Fast Keywords (Instant)
         if let Some(action) = self.check_fast_keywords(&text_lower).await {
             info!("⚡ Layer 1 (Fast Keyword) matched: {}", action);
             return ProcessResult::Success(action);
         }
 
-        // LAYER 2: Existing Game Commands (Exact triggers)
-        if let Some(cmd) = self.match_command(&text_sanitized) {
-            let name = match &cmd {
-                Command::Action { name, .. } => name.clone(),
-                Command::Macro(m) => m.name.clone(),
-            };
+        // LAYER 1b: Command history ("cancel that", "again")
+        if UNDO_PHRASES.contains(&text_lower.as_str()) {
+            return self.undo_last().await;
+        }
+        if REPEAT_PHRASES.contains(&text_lower.as_str()) {
+            return self.repeat_last().await;
+        }
+
+        // LAYER 2a: Compound utterances, run in spoken order
+        if let Some(commands) = self.match_compound(&text_lower) {
+            return self.execute_compound(commands).await;
+        }
 
-            info!("🎯 Layer 2 (Game Command) matched: {}", name);
+        // LAYER 2: Existing Game Commands (Exact triggers, rescored across the n-best)
+        if let Some((mut leaders, idx, modifiers)) = self.best_hypothesis_matches(hypotheses) {
+            // Equally good matches for different commands: ask rather than guess
+            if leaders.len() > 1 {
+                info!(
+                    "🤔 Layer 2 tie between {:?}, asking which was meant",
+                    leaders.iter().map(|m| m.command.name()).collect::<Vec<_>>()
+                );
+                return ProcessResult::CommandChoiceRequired {
+                    query: hypotheses[idx].text.clone(),
+                    candidates: leaders,
+                    modifiers,
+                };
+            }
+            let top = leaders.remove(0);
+            let name = top.command.name().to_string();
+
+            if idx == 0 {
+                info!("🎯 Layer 2 (Game Command) matched: {}", name);
+            } else {
+                info!(
+                    "🎯 Layer 2 (Game Command) matched: {} (from alternative '{}')",
+                    name, hypotheses[idx].text
+                );
+            }
 
             // Check for dangerous commands (Red Team: Stamos)
-            if DANGEROUS_COMMANDS
-                .iter()
-                .any(|c| name.to_lowercase().contains(c))
-            {
+            if is_dangerous(&name) {
                 info!(
                     "⚠️ Dangerous command detected: '{}', requesting confirmation",
                     name
                 );
                 return ProcessResult::ConfirmationRequired {
                     action: name,
-                    command: cmd,
+                    command: top,
+                    modifiers,
                 };
             }
 
-            let mut kb_lock = self.keyboard.lock().expect("Keyboard mutex poisoned");
-            if let Err(e) = self.execute_command_blocking(&mut kb_lock, cmd) {
-                warn!("❌ Failed to execute {}: {}", name, e);
-                return ProcessResult::NotFound;
-            }
-            return ProcessResult::Success(name);
+            return self.run_confirmed(top, modifiers).await;
         }
 
         // LAYER 3: Ollama (Smart Intent)
@@ -642,209 +1812,81 @@ impl CommandProcessor {
             Command::Action { key, modifiers, .. } => {
                 self.press_keys_internal_opt(keyboard, &key, &modifiers)
             }
-            Command::Macro(m) => {
-                info!("📜 Executing macro: {}", m.name);
-                for (step_idx, step) in m.steps.iter().enumerate() {
-                    // === Audio Fallback Chain (matching Python) ===
-                    if let Some(engine) = &self.sound_engine {
-                        let mut audio_pool = step.audio_pool.clone();
-
-                        // Legacy fallback
-                        if audio_pool.is_empty() {
-                            if let Some(ref legacy_file) = step.audio_feedback_file {
-                                if legacy_file != "(Sound Pool)" {
-                                    audio_pool.push(legacy_file.clone());
-                                }
-                            }
-                        }
-
-                        // Play audio
-                        if !audio_pool.is_empty() {
-                            let pool_id = format!("{}_step_{}", m.name, step_idx);
-                            let paths = audio_pool.iter().map(std::path::PathBuf::from).collect();
-                            let _ = engine.play_pool(&pool_id, paths, step.playback_mode);
-                        }
-                    }
+            Command::Macro(m) => self.spawn_macro(m, 1).map(drop),
+        }
+    }
 
-                    // Execute key press (only if action is specified)
-                    if !step.action.is_empty() {
-                        let binding = self.action_map.get(&step.action).cloned();
+    /// Execute a command asynchronously (for use in Tasks)
+    pub async fn execute_command_async(runner: MacroRunner, command: Command) {
+        runner.run(command).await
+    }
 
-                        if let Some(binding) = binding {
-                            if let Some(key) = &binding.primary_key {
-                                self.press_keys_internal_opt(keyboard, key, &binding.modifiers)?;
-                            }
-                        } else {
-                            warn!("⚠️ Unknown action in macro: {}", step.action);
-                        }
-                    }
+    /// Whether a running macro is waiting for a spoken phrase (the listening
+    /// window should stay open until it is heard or the wait times out)
+    pub fn awaiting_speech(&self) -> bool {
+        self.waiters.is_waiting()
+    }
 
-                    if step.delay > 0 {
-                        std::thread::sleep(std::time::Duration::from_millis(step.delay));
-                    }
-                }
-                Ok(())
-            }
+    /// Snapshot of what running macros need (keyboard, bindings, state)
+    pub fn macro_runner(&self, custom_audio_dir: Option<std::path::PathBuf>) -> MacroRunner {
+        MacroRunner {
+            keyboard: self.keyboard.clone(),
+            action_map: self.action_map.clone(),
+            commands: self.commands.clone(),
+            toggles: self.toggles.clone(),
+            active_profile: self.active_profile.clone(),
+            waiters: self.waiters.clone(),
+            in_flight: self.in_flight.clone(),
+            sound_engine: self.sound_engine.clone(),
+            player_manager: self.player_manager.clone(),
+            tts: self.tts.clone(),
+            lal_manager: self.lal_manager.clone(),
+            custom_audio_dir,
         }
     }
 
-    /// Execute a command asynchronously (for use in Tasks)
-    pub async fn execute_command_async(
-        shared_keyboard: Arc<Mutex<Option<VirtualKeyboard>>>,
-        action_map: HashMap<String, crate::games::KeyBinding>,
-        sound_engine: Option<Arc<crate::audio::SoundEngine>>,
-        lal_manager: Option<Arc<crate::lal::LALManager>>,
-        custom_audio_dir: Option<std::path::PathBuf>,
-        command: Command,
-    ) {
-        match command {
-            Command::Action {
-                key,
-                modifiers,
-                name,
-                ..
-            } => {
-                info!(
-                    "⚡ Executing Action: {} (Key: {}, Modifiers: {:?})",
-                    name, key, modifiers
-                );
-                let mut kb = shared_keyboard
-                    .lock()
-                    .expect("Shared keyboard mutex poisoned");
-                if let Some(ref mut k) = *kb {
-                    if let Err(e) = Self::press_keys_internal(k, &key, &modifiers) {
-                        error!("❌ Failed to press keys: {}", e);
-                    }
-                } else {
-                    warn!("⚠️ No virtual keyboard available for action execution");
+    /// Run a macro in the background, `times` times in a row, so steps that
+    /// wait for speech don't hold up the processor
+    ///
+    /// Fails if the macro is already running.
+    fn spawn_macro(&self, m: Macro, times: u32) -> Result<JoinHandle<()>> {
+        let claim = self
+            .in_flight
+            .claim(&m.name)
+            .ok_or_else(|| anyhow::anyhow!("Macro '{}' is already running", m.name))?;
+        let runner = self.macro_runner(None);
+        let task = tokio::spawn(async move {
+            for i in 0..times {
+                if i > 0 {
+                    tokio::time::sleep(REPEAT_INTERVAL).await;
+                }
+                if !runner.run_claimed(&m, &claim).await {
+                    break;
                 }
             }
-            Command::Macro(m) => {
-                info!("📜 Executing macro (async): {}", m.name);
-                for (step_idx, step) in m.steps.iter().enumerate() {
-                    // === Audio Fallback Chain (matching Python) ===
-                    // 1. Check audio_pool
-                    // 2. If empty, check audio_feedback_file (legacy single file)
-                    // 3. If empty, check audio_feedback (LAL ID)
-
-                    if let Some(engine) = &sound_engine {
-                        let mut audio_pool = step.audio_pool.clone();
-
-                        // Legacy fallback: if no pool, use single file
-                        if audio_pool.is_empty() {
-                            if let Some(ref legacy_file) = step.audio_feedback_file {
-                                if legacy_file != "(Sound Pool)" {
-                                    audio_pool.push(legacy_file.clone());
-                                }
-                            }
-                        }
-
-                        // LAL ID fallback: if still empty and ID exists
-                        if audio_pool.is_empty() {
-                            if let Some(ref audio_id) = step.audio_feedback {
-                                if let Some(mgr) = &lal_manager {
-                                    if let Some(path) = mgr.get_audio(audio_id) {
-                                        let path_str = path.to_string_lossy().to_string();
-                                        audio_pool.push(path_str);
-                                    } else {
-                                        warn!("⚠️ LAL Audio ID not found: {}", audio_id);
-                                    }
-                                }
-                            }
-                        }
-
-                        // Play audio
-                        if !audio_pool.is_empty() {
-                            let pool_id = format!("{}_step_{}", m.name, step_idx);
-                            let mut resolved_paths = Vec::new();
-
-                            for p in audio_pool {
-                                let path = std::path::PathBuf::from(&p);
-                                let mut final_path = path.clone();
-
-                                // 1. Check if path exists directly (or relative to CWD)
-                                if !final_path.exists() {
-                                    // 2. Check Custom Audio Dir
-                                    if let Some(ref custom_dir) = custom_audio_dir {
-                                        let custom_path = custom_dir.join(&p);
-                                        if custom_path.exists() {
-                                            final_path = custom_path;
-                                        }
-                                    }
-                                }
-
-                                // 3. Directory Randomization (Task 7.5)
-                                if final_path.is_dir() {
-                                    if let Ok(entries) = std::fs::read_dir(&final_path) {
-                                        let files: Vec<_> = entries
-                                            .filter_map(|e| e.ok())
-                                            .map(|e| e.path())
-                                            .filter(|p| {
-                                                p.is_file()
-                                                    && p.extension().is_some_and(|ext| {
-                                                        let ext = ext.to_string_lossy();
-                                                        matches!(
-                                                            ext.as_ref(),
-                                                            "wav" | "mp3" | "ogg" | "flac"
-                                                        )
-                                                    })
-                                            })
-                                            .collect();
-
-                                        if !files.is_empty() {
-                                            use rand::seq::SliceRandom;
-                                            if let Some(picked) =
-                                                files.choose(&mut rand::thread_rng())
-                                            {
-                                                final_path = picked.clone();
-                                                debug!(
-                                                    "🎲 Randomly picked {} from {}",
-                                                    final_path.display(),
-                                                    p
-                                                );
-                                            }
-                                        }
-                                    }
-                                }
-
-                                if final_path.exists() && final_path.is_file() {
-                                    resolved_paths.push(final_path);
-                                } else {
-                                    warn!("⚠️ Audio file not found: {}", p);
-                                }
-                            }
-
-                            if !resolved_paths.is_empty() {
-                                let _ =
-                                    engine.play_pool(&pool_id, resolved_paths, step.playback_mode);
-                            }
-                        }
-                    }
-
-                    // Execute key press (only if action is specified)
-                    if !step.action.is_empty() {
-                        let binding = action_map.get(&step.action).cloned();
-
-                        if let Some(binding) = binding {
-                            if let Some(key) = &binding.primary_key {
-                                let mut kb = shared_keyboard
-                                    .lock()
-                                    .expect("Shared keyboard mutex poisoned");
-                                if let Some(ref mut k) = *kb {
-                                    let _ = Self::press_keys_internal(k, key, &binding.modifiers);
-                                }
-                            }
-                        } else {
-                            warn!("⚠️ Unknown action in macro: {}", step.action);
-                        }
-                    }
+        });
+        Ok(task)
+    }
 
-                    if step.delay > 0 {
-                        tokio::time::sleep(std::time::Duration::from_millis(step.delay)).await;
-                    }
+    /// Like [`Self::spawn_macro`], but waits for a run already in progress to
+    /// end first (and gives up if that run is aborted)
+    fn queue_macro(&self, m: Macro, times: u32) {
+        let runner = self.macro_runner(None);
+        tokio::spawn(async move {
+            if !runner.in_flight.finished(&m.name).await {
+                return;
+            }
+            let Some(claim) = runner.in_flight.claim(&m.name) else {
+                warn!("⚠️ Macro '{}' is already running", m.name);
+                return;
+            };
+            for _ in 0..times {
+                tokio::time::sleep(REPEAT_INTERVAL).await;
+                if !runner.run_claimed(&m, &claim).await {
+                    break;
                 }
             }
-        }
+        });
     }
 
     fn press_keys_internal_opt(
@@ -859,17 +1901,16 @@ impl CommandProcessor {
         Self::press_keys_internal(k, key_str, modifier_strs)
     }
 
-    fn press_keys_internal(
+    pub(crate) fn press_keys_internal(
         keyboard: &mut VirtualKeyboard,
         key_str: &str,
         modifier_strs: &[String],
     ) -> Result<()> {
-        let key = parse_key(key_str).ok_or_else(|| anyhow::anyhow!("Unknown key: {}", key_str))?;
+        let (modifiers, key) = parse_combo(key_str, modifier_strs)?;
 
-        if modifier_strs.is_empty() {
+        if modifiers.is_empty() {
             keyboard.tap_key(key)?;
         } else {
-            let modifiers: Vec<Key> = modifier_strs.iter().filter_map(|m| parse_key(m)).collect();
             keyboard.key_combo(&modifiers, key)?;
         }
 
@@ -886,6 +1927,22 @@ impl CommandProcessor {
     }
 }
 
+/// Spoken name of a toggle state
+pub fn on_off(on: bool) -> &'static str {
+    if on {
+        "on"
+    } else {
+        "off"
+    }
+}
+
+/// Resolve a binding's key and modifier names (unknown modifiers are skipped)
+fn parse_combo(key_str: &str, modifier_strs: &[String]) -> Result<(Vec<Key>, Key)> {
+    let key = parse_key(key_str).ok_or_else(|| anyhow::anyhow!("Unknown key: {}", key_str))?;
+    let modifiers = modifier_strs.iter().filter_map(|m| parse_key(m)).collect();
+    Ok((modifiers, key))
+}
+
 /// Sanitize transcription by stripping junk prefixes and punctuation
 fn sanitize_transcription(text: &str) -> String {
     let mut s = text.to_lowercase();
@@ -18,11 +18,23 @@ pub struct Config {
 
     // Speech
     pub asr_engine: String,
+    /// ASR failover chain in priority order (empty = just `asr_engine`)
+    #[serde(default)]
+    pub asr_engines: Vec<String>,
     pub tts_engine: String,
     pub wake_word: String,
+    /// Wake words with their own action/profile (empty = just `wake_word`)
+    #[serde(default)]
+    pub wake_words: Vec<WakeWordBinding>,
+    /// Keyword spotter: "vosk", "wyoming", or "asr" (match full transcripts)
+    #[serde(default = "default_wake_engine")]
+    pub wake_engine: String,
     pub vosk_model_path: String,
     pub piper_voice: String,
     pub command_timeout: u64,
+    /// Runner-up hypotheses requested from the ASR engine (0 = top result only)
+    #[serde(default = "default_asr_max_alternatives")]
+    pub asr_max_alternatives: u16,
 
     // Input/PTT
     pub ptt_enabled: bool,
@@ -36,6 +48,21 @@ pub struct Config {
     pub wyoming_model: String,
     pub wyoming_device: String,
     pub wyoming_compute_type: String,
+    /// Whisper language code (empty = derived from `ui_language`)
+    #[serde(default)]
+    pub wyoming_language: String,
+    /// Port of the Wyoming wake word service (e.g. wyoming-openwakeword)
+    #[serde(default = "default_wyoming_wake_port")]
+    pub wyoming_wake_port: u16,
+    /// Host of the Wyoming TTS server (empty = `wyoming_host`)
+    #[serde(default)]
+    pub wyoming_tts_host: String,
+    /// Port of the Wyoming TTS server (e.g. wyoming-piper)
+    #[serde(default = "default_wyoming_tts_port")]
+    pub wyoming_tts_port: u16,
+    /// Voice requested from the Wyoming TTS server (empty = server default)
+    #[serde(default)]
+    pub wyoming_tts_voice: String,
 
     // AI
     pub ollama_enabled: bool,
@@ -55,6 +82,64 @@ pub struct Config {
     // Audio
     #[serde(default)]
     pub custom_audio_dir: String,
+    /// Input device name filter (case-insensitive substring, empty = default device)
+    #[serde(default)]
+    pub audio_device: String,
+    /// How long the microphone stays muted after our own speech/sounds end
+    #[serde(default = "default_playback_mute_tail_ms")]
+    pub playback_mute_tail_ms: u64,
+    /// Mute the microphone during sound effects and macro audio too, not just
+    /// speech. Turn off to be able to say "abort" over a long macro sound.
+    #[serde(default = "default_mute_during_effects")]
+    pub mute_during_effects: bool,
+    /// Capture clean-up stages (high-pass, AGC, noise gate)
+    #[serde(default)]
+    pub dsp: crate::audio::DspConfig,
+
+    // Commands
+    /// Pause between commands spoken in one utterance ("gear and lights on")
+    #[serde(default = "default_compound_command_gap_ms")]
+    pub compound_command_gap_ms: u64,
+}
+
+fn default_asr_max_alternatives() -> u16 {
+    3
+}
+
+fn default_playback_mute_tail_ms() -> u64 {
+    crate::audio::playback::DEFAULT_MUTE_TAIL.as_millis() as u64
+}
+
+fn default_mute_during_effects() -> bool {
+    true
+}
+
+fn default_compound_command_gap_ms() -> u64 {
+    crate::commands::DEFAULT_COMPOUND_GAP.as_millis() as u64
+}
+
+fn default_wyoming_tts_port() -> u16 {
+    10200
+}
+
+fn default_wake_engine() -> String {
+    "vosk".to_string()
+}
+
+fn default_wyoming_wake_port() -> u16 {
+    10400
+}
+
+/// A wake word and what it does when spoken
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WakeWordBinding {
+    pub phrase: String,
+    /// Command to run immediately instead of waiting for one
+    #[serde(default)]
+    pub action: Option<String>,
+    /// Game profile to activate before listening for a command
+    #[serde(default)]
+    pub profile: Option<String>,
 }
 
 impl Default for Config {
@@ -80,8 +165,11 @@ impl Default for Config {
                 .to_string_lossy()
                 .to_string(),
             asr_engine: "vosk".to_string(),
+            asr_engines: Vec::new(),
             tts_engine: "piper".to_string(),
             wake_word: "tuxtalks".to_string(),
+            wake_words: Vec::new(),
+            wake_engine: default_wake_engine(),
             vosk_model_path: dirs::data_dir()
                 .unwrap_or_default()
                 .join("tuxtalks/models/vosk-model-en-gb-small")
@@ -89,6 +177,7 @@ impl Default for Config {
                 .to_string(),
             piper_voice: "en_GB-cori-high".to_string(),
             command_timeout: 5,
+            asr_max_alternatives: default_asr_max_alternatives(),
             ptt_enabled: false,
             ptt_mode: "HOLD".to_string(),
             ptt_key: "KEY_LEFTCTRL".to_string(),
@@ -98,6 +187,11 @@ impl Default for Config {
             wyoming_model: "tiny".to_string(),
             wyoming_device: "cpu".to_string(),
             wyoming_compute_type: "int8".to_string(),
+            wyoming_language: String::new(),
+            wyoming_wake_port: default_wyoming_wake_port(),
+            wyoming_tts_host: String::new(),
+            wyoming_tts_port: default_wyoming_tts_port(),
+            wyoming_tts_voice: String::new(),
             ollama_enabled: false,
             ollama_url: "http://localhost:11434".to_string(),
             ollama_model: "llama2".to_string(),
@@ -118,11 +212,48 @@ impl Default for Config {
                 .join("tuxtalks/audio")
                 .to_string_lossy()
                 .to_string(),
+            audio_device: String::new(),
+            playback_mute_tail_ms: default_playback_mute_tail_ms(),
+            mute_during_effects: default_mute_during_effects(),
+            dsp: Default::default(),
+            compound_command_gap_ms: default_compound_command_gap_ms(),
         }
     }
 }
 
 impl Config {
+    /// Configured wake words, falling back to the single `wake_word`
+    pub fn wake_word_bindings(&self) -> Vec<WakeWordBinding> {
+        let bindings: Vec<WakeWordBinding> = self
+            .wake_words
+            .iter()
+            .filter(|b| !b.phrase.trim().is_empty())
+            .cloned()
+            .collect();
+        if !bindings.is_empty() || self.wake_word.trim().is_empty() {
+            return bindings;
+        }
+        vec![WakeWordBinding {
+            phrase: self.wake_word.clone(),
+            ..Default::default()
+        }]
+    }
+
+    /// ASR engines in failover order, falling back to the single `asr_engine`
+    pub fn asr_engine_chain(&self) -> Vec<String> {
+        let mut chain: Vec<String> = Vec::new();
+        for name in &self.asr_engines {
+            let name = name.trim().to_lowercase();
+            if !name.is_empty() && !chain.contains(&name) {
+                chain.push(name);
+            }
+        }
+        if chain.is_empty() {
+            chain.push(self.asr_engine.clone());
+        }
+        chain
+    }
+
     /// Load config from file, migrate from Python, or create default
     pub fn load() -> Result<Self> {
         let config_path = config_path();
@@ -231,6 +362,26 @@ mod tests {
         assert_eq!(config.wake_word, restored.wake_word);
     }
 
+    #[test]
+    fn test_wake_word_bindings_fallback() {
+        let mut config = Config::default();
+        let bindings = config.wake_word_bindings();
+        assert_eq!(bindings.len(), 1);
+        assert_eq!(bindings[0].phrase, "tuxtalks");
+
+        config.wake_words = vec![
+            WakeWordBinding {
+                phrase: "computer".to_string(),
+                profile: Some("Elite Dangerous".to_string()),
+                ..Default::default()
+            },
+            WakeWordBinding::default(),
+        ];
+        let bindings = config.wake_word_bindings();
+        assert_eq!(bindings.len(), 1);
+        assert_eq!(bindings[0].phrase, "computer");
+    }
+
     #[test]
     fn test_config_corrupt_json_handling() {
         // Config::load uses graceful degradation - this tests the parsing path
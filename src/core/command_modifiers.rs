@@ -0,0 +1,172 @@
+//! Spoken command modifiers
+//!
+//! Players can append how a command should be performed: "next target five
+//! times" repeats the key press, "boost for two seconds" (or "hold boost for
+//! two seconds") keeps the key down. [`extract`] splits these phrases off the
+//! utterance so the rest is matched against the profile as usual.
+
+use super::trigger_template::{tokenize, TriggerTemplate};
+use lazy_static::lazy_static;
+use std::time::Duration;
+use tracing::warn;
+
+/// Most repetitions a single utterance may ask for
+pub const MAX_REPEAT: u32 = 20;
+
+/// Numbers per slot listed in grammars (covers the 1-60 second range)
+const MAX_GRAMMAR_NUMBERS: usize = 61;
+
+/// How a matched command should be performed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommandModifiers {
+    /// Number of times to run the command (at least 1)
+    pub repeat: u32,
+    /// Keep the key down this long instead of tapping it
+    pub hold: Option<Duration>,
+}
+
+impl Default for CommandModifiers {
+    fn default() -> Self {
+        Self {
+            repeat: 1,
+            hold: None,
+        }
+    }
+}
+
+impl CommandModifiers {
+    /// Whether the command runs once, as a tap
+    pub fn is_plain(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Effect {
+    /// Repeat a fixed number of times, or `{n}` times if `None`
+    Repeat(Option<u32>),
+    /// Hold for `{n}` seconds
+    HoldSeconds,
+    /// Hold for a fixed duration
+    Hold(Duration),
+}
+
+lazy_static! {
+    /// Modifier phrases, longest first so "hold for two seconds" wins over
+    /// "for two seconds"
+    static ref PATTERNS: Vec<(TriggerTemplate, Effect)> = [
+        ("{n} times", Effect::Repeat(None)),
+        ("twice", Effect::Repeat(Some(2))),
+        ("thrice", Effect::Repeat(Some(3))),
+        ("hold for half a second", Effect::Hold(Duration::from_millis(500))),
+        ("hold for a second", Effect::Hold(Duration::from_secs(1))),
+        ("hold for {n:1-60} seconds", Effect::HoldSeconds),
+        ("hold for {n:1-60} second", Effect::HoldSeconds),
+        ("hold {n:1-60} seconds", Effect::HoldSeconds),
+        ("for half a second", Effect::Hold(Duration::from_millis(500))),
+        ("for a second", Effect::Hold(Duration::from_secs(1))),
+        ("for {n:1-60} seconds", Effect::HoldSeconds),
+        ("for {n:1-60} second", Effect::HoldSeconds),
+    ]
+    .into_iter()
+    .map(|(pattern, effect)| {
+        let template = TriggerTemplate::parse(pattern).expect("Invalid modifier pattern");
+        (template, effect)
+    })
+    .collect();
+}
+
+/// Every modifier phrase with numbers spelled out, for recognizer grammars
+pub fn phrases() -> Vec<String> {
+    PATTERNS
+        .iter()
+        .flat_map(|(template, _)| template.expand(MAX_GRAMMAR_NUMBERS))
+        .collect()
+}
+
+/// Split repetition and hold phrases off an utterance
+///
+/// Returns the remaining text (lowercased, one space between words) and the
+/// modifiers. An utterance that is nothing but a modifier ("twice") is returned
+/// unchanged.
+pub fn extract(text: &str) -> (String, CommandModifiers) {
+    let mut tokens = tokenize(text);
+    let mut modifiers = CommandModifiers::default();
+    let (mut has_repeat, mut has_hold) = (false, false);
+
+    for (template, effect) in PATTERNS.iter() {
+        let is_repeat = matches!(effect, Effect::Repeat(_));
+        if (is_repeat && has_repeat) || (!is_repeat && has_hold) {
+            continue;
+        }
+        let Some(found) = template.find_in(&tokens) else {
+            continue;
+        };
+        let n = found.values.get("n").copied().unwrap_or(1);
+        match *effect {
+            Effect::Repeat(fixed) => {
+                let times = fixed.unwrap_or(n).max(1);
+                if times > MAX_REPEAT {
+                    warn!("⚠️ Capping {} repetitions at {}", times, MAX_REPEAT);
+                }
+                modifiers.repeat = times.min(MAX_REPEAT);
+                has_repeat = true;
+            }
+            Effect::HoldSeconds => {
+                modifiers.hold = Some(Duration::from_secs(u64::from(n)));
+                has_hold = true;
+            }
+            Effect::Hold(duration) => {
+                modifiers.hold = Some(duration);
+                has_hold = true;
+            }
+        }
+        tokens.drain(found.start..found.end);
+    }
+
+    // "hold boost for two seconds": the leading "hold" belongs to the modifier
+    if has_hold && tokens.first().is_some_and(|t| t == "hold") {
+        tokens.remove(0);
+    }
+
+    if tokens.is_empty() {
+        return (text.to_lowercase(), CommandModifiers::default());
+    }
+    (tokens.join(" "), modifiers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_repeat() {
+        let (text, mods) = extract("Next target five times");
+        assert_eq!(text, "next target");
+        assert_eq!(mods.repeat, 5);
+        assert_eq!(mods.hold, None);
+
+        assert_eq!(extract("boost twice").1.repeat, 2);
+        assert_eq!(extract("fire 99 times").1.repeat, MAX_REPEAT);
+        assert!(extract("next target").1.is_plain());
+        assert!(extract("twice").1.is_plain());
+    }
+
+    #[test]
+    fn test_extract_hold() {
+        for utterance in [
+            "boost hold for two seconds",
+            "hold boost for two seconds",
+            "boost for 2 seconds",
+        ] {
+            let (text, mods) = extract(utterance);
+            assert_eq!(text, "boost", "from '{}'", utterance);
+            assert_eq!(mods.hold, Some(Duration::from_secs(2)));
+        }
+
+        let (text, mods) = extract("fire for half a second three times");
+        assert_eq!(text, "fire");
+        assert_eq!(mods.hold, Some(Duration::from_millis(500)));
+        assert_eq!(mods.repeat, 3);
+    }
+}
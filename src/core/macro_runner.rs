@@ -0,0 +1,858 @@
+//! Macro interpreter
+//!
+//! Runs commands off the processor: key presses, macro sounds and delays,
+//! speech, media and typing steps, and macro control flow (calling another
+//! command, repeating a block, waiting for a spoken phrase, branching on a
+//! toggle or the active profile). Running macros are tracked in [`InFlight`]
+//! so they can be aborted and aren't started twice.
+
+use crate::commands::{
+    Command, CommandProcessor, Macro, MacroStep, PlayerCommand, StepCondition, StepEffect, StepFlow,
+};
+use crate::games::{KeyBinding, TagState};
+use crate::input::VirtualKeyboard;
+use crate::player_manager::PlayerManager;
+use crate::tts::TtsEngine;
+use anyhow::{anyhow, bail, Result};
+use evdev::Key;
+use futures::future::{BoxFuture, FutureExt};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, warn};
+
+/// Deepest chain of macros calling macros (stops call cycles)
+const MAX_CALL_DEPTH: usize = 8;
+
+/// How often a queued run checks whether the run before it has ended
+const FINISH_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// A macro paused on a `wait_for` step
+struct Waiter {
+    macro_name: String,
+    phrases: Vec<String>,
+    resume: oneshot::Sender<()>,
+}
+
+/// Macros waiting for a spoken phrase (shared by the processor and runners)
+#[derive(Clone, Default)]
+pub struct SpeechWaiters(Arc<Mutex<Vec<Waiter>>>);
+
+impl SpeechWaiters {
+    fn wait(&self, macro_name: &str, phrases: &[String]) -> oneshot::Receiver<()> {
+        let (resume, heard) = oneshot::channel();
+        self.0.lock().expect("Waiter mutex poisoned").push(Waiter {
+            macro_name: macro_name.to_string(),
+            phrases: phrases.iter().map(|p| p.trim().to_lowercase()).collect(),
+            resume,
+        });
+        heard
+    }
+
+    /// Whether any macro is waiting for a phrase
+    pub fn is_waiting(&self) -> bool {
+        let mut waiters = self.0.lock().expect("Waiter mutex poisoned");
+        waiters.retain(|w| !w.resume.is_closed());
+        !waiters.is_empty()
+    }
+
+    /// Resume the macro waiting for `text` (lowercase); returns its name
+    pub fn resume(&self, text: &str) -> Option<String> {
+        let mut waiters = self.0.lock().expect("Waiter mutex poisoned");
+        // Drop macros that gave up waiting
+        waiters.retain(|w| !w.resume.is_closed());
+        let index = waiters
+            .iter()
+            .position(|w| w.phrases.iter().any(|p| p == text))?;
+        let waiter = waiters.remove(index);
+        waiter.resume.send(()).ok()?;
+        Some(waiter.macro_name)
+    }
+}
+
+/// Modifier keys and the key, as held with `press_combo`
+pub(crate) type Combo = (Vec<Key>, Key);
+
+/// Macros, repeated or held commands and key holds in progress (shared by the
+/// processor, its runners and the IPC abort action)
+#[derive(Clone)]
+pub struct InFlight {
+    /// Running macros by name, with the id of the run that claimed the name
+    macros: Arc<Mutex<HashMap<String, (u64, CancellationToken)>>>,
+    next_id: Arc<AtomicU64>,
+    /// Key combinations currently held down
+    held: Arc<Mutex<Vec<Combo>>>,
+    keyboard: Arc<Mutex<Option<VirtualKeyboard>>>,
+}
+
+/// What an abort stopped
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Aborted {
+    /// Macros and repeated or held commands that were running, by name
+    pub commands: Vec<String>,
+    /// Key combinations that were released
+    pub released_keys: usize,
+}
+
+impl Aborted {
+    /// Whether there was nothing to stop
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty() && self.released_keys == 0
+    }
+}
+
+impl std::fmt::Display for Aborted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.commands.is_empty(), self.released_keys) {
+            (true, 0) => write!(f, "Nothing to abort"),
+            (true, keys) => write!(f, "Released {} held key(s)", keys),
+            (false, 0) => write!(f, "Aborted {}", self.commands.join(", ")),
+            (false, keys) => write!(
+                f,
+                "Aborted {}, released {} held key(s)",
+                self.commands.join(", "),
+                keys
+            ),
+        }
+    }
+}
+
+/// A macro's claim on its name while it runs; dropping it frees the name
+pub struct MacroClaim {
+    in_flight: InFlight,
+    name: String,
+    id: u64,
+    token: CancellationToken,
+}
+
+impl Drop for MacroClaim {
+    fn drop(&mut self) {
+        let mut macros = self.in_flight.macros.lock().expect("Macro mutex poisoned");
+        if macros.get(&self.name).is_some_and(|(id, _)| *id == self.id) {
+            macros.remove(&self.name);
+        }
+    }
+}
+
+impl InFlight {
+    pub fn new(keyboard: Arc<Mutex<Option<VirtualKeyboard>>>) -> Self {
+        Self {
+            macros: Arc::new(Mutex::new(HashMap::new())),
+            next_id: Arc::new(AtomicU64::new(0)),
+            held: Arc::new(Mutex::new(Vec::new())),
+            keyboard,
+        }
+    }
+
+    /// Mark a macro as running; `None` if it already is
+    pub fn claim(&self, name: &str) -> Option<MacroClaim> {
+        let mut macros = self.macros.lock().expect("Macro mutex poisoned");
+        if macros.contains_key(name) {
+            return None;
+        }
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let token = CancellationToken::new();
+        macros.insert(name.to_string(), (id, token.clone()));
+        Some(MacroClaim {
+            in_flight: self.clone(),
+            name: name.to_string(),
+            id,
+            token,
+        })
+    }
+
+    /// Whether the named macro is running
+    pub fn is_running(&self, name: &str) -> bool {
+        let macros = self.macros.lock().expect("Macro mutex poisoned");
+        macros.contains_key(name)
+    }
+
+    /// Wait until the named macro's current run ends; false if it was aborted
+    pub(crate) async fn finished(&self, name: &str) -> bool {
+        let token = {
+            let macros = self.macros.lock().expect("Macro mutex poisoned");
+            match macros.get(name) {
+                Some((_, token)) => token.clone(),
+                None => return true,
+            }
+        };
+        while self.is_running(name) && !token.is_cancelled() {
+            tokio::time::sleep(FINISH_POLL_INTERVAL).await;
+        }
+        !token.is_cancelled()
+    }
+
+    /// Remember a combination about to be pressed with `press_combo`; call it
+    /// with the keyboard locked, before the press, so `abort_all` never misses it
+    pub(crate) fn hold(&self, modifiers: &[Key], key: Key) {
+        let mut held = self.held.lock().expect("Held keys mutex poisoned");
+        held.push((modifiers.to_vec(), key));
+    }
+
+    /// Forget a held combination before releasing it; false if an abort
+    /// already released it
+    pub(crate) fn unhold(&self, modifiers: &[Key], key: Key) -> bool {
+        let mut held = self.held.lock().expect("Held keys mutex poisoned");
+        let Some(index) = held.iter().position(|(m, k)| *k == key && m == modifiers) else {
+            return false;
+        };
+        held.remove(index);
+        true
+    }
+
+    /// Stop every running macro and release every held key
+    pub fn abort_all(&self) -> Aborted {
+        let mut names: Vec<String> = {
+            let macros = self.macros.lock().expect("Macro mutex poisoned");
+            for (_, token) in macros.values() {
+                token.cancel();
+            }
+            macros.keys().cloned().collect()
+        };
+        names.sort();
+
+        // Holds are registered under the keyboard lock, so none is pressed
+        // but missing from the list while we hold it
+        let held: Vec<_> = {
+            let mut kb = self.keyboard.lock().expect("Keyboard mutex poisoned");
+            let held: Vec<_> = self
+                .held
+                .lock()
+                .expect("Held keys mutex poisoned")
+                .drain(..)
+                .collect();
+            if let Some(kb) = kb.as_mut() {
+                for (modifiers, key) in &held {
+                    if let Err(e) = kb.release_combo(modifiers, *key) {
+                        warn!("❌ Failed to release {:?}: {}", key, e);
+                    }
+                }
+            }
+            held
+        };
+
+        info!(
+            "🛑 Aborted {} macro(s) and released {} held key(s)",
+            names.len(),
+            held.len()
+        );
+        Aborted {
+            commands: names,
+            released_keys: held.len(),
+        }
+    }
+}
+
+/// Everything a running command needs, detached from the processor
+#[derive(Clone)]
+pub struct MacroRunner {
+    pub(crate) keyboard: Arc<Mutex<Option<VirtualKeyboard>>>,
+    pub(crate) action_map: HashMap<String, KeyBinding>,
+    /// Commands a `call` step can name
+    pub(crate) commands: Vec<Command>,
+    pub(crate) toggles: Arc<Mutex<HashMap<String, TagState>>>,
+    pub(crate) active_profile: Option<String>,
+    pub(crate) waiters: SpeechWaiters,
+    pub(crate) in_flight: InFlight,
+    pub(crate) sound_engine: Option<Arc<crate::audio::SoundEngine>>,
+    pub(crate) player_manager: Option<Arc<PlayerManager>>,
+    pub(crate) tts: Option<Arc<dyn TtsEngine>>,
+    pub(crate) lal_manager: Option<Arc<crate::lal::LALManager>>,
+    pub(crate) custom_audio_dir: Option<PathBuf>,
+}
+
+impl MacroRunner {
+    /// Run a command to the end, logging rather than returning failures
+    pub async fn run(&self, command: Command) {
+        match command {
+            Command::Action {
+                key,
+                modifiers,
+                name,
+                ..
+            } => {
+                info!(
+                    "⚡ Executing Action: {} (Key: {}, Modifiers: {:?})",
+                    name, key, modifiers
+                );
+                if let Err(e) = self.press(&key, &modifiers) {
+                    error!("❌ Failed to press keys: {}", e);
+                }
+            }
+            Command::Macro(m) => match self.in_flight.claim(&m.name) {
+                Some(claim) => {
+                    self.run_claimed(&m, &claim).await;
+                }
+                None => warn!("⚠️ Macro '{}' is already running", m.name),
+            },
+        }
+    }
+
+    /// Run a macro whose name is claimed; false if it was aborted
+    pub(crate) async fn run_claimed(&self, m: &Macro, claim: &MacroClaim) -> bool {
+        info!("📜 Executing macro (async): {}", m.name);
+        tokio::select! {
+            biased;
+            _ = claim.token.cancelled() => {
+                info!("🛑 Macro '{}' aborted", m.name);
+                false
+            }
+            result = self.run_steps(&m.name, &m.steps, 0) => {
+                if let Err(e) = result {
+                    warn!("⏹️ Macro '{}' stopped: {}", m.name, e);
+                }
+                true
+            }
+        }
+    }
+
+    /// Press a key combination `times` times, `interval` apart, keeping it
+    /// down for `hold` each time if given; false if it was aborted
+    pub(crate) async fn run_repeated(
+        &self,
+        name: &str,
+        combo: &Combo,
+        hold: Option<Duration>,
+        times: u32,
+        interval: Duration,
+        claim: &MacroClaim,
+    ) -> bool {
+        tokio::select! {
+            biased;
+            _ = claim.token.cancelled() => {
+                info!("🛑 {} aborted", name);
+                false
+            }
+            result = self.press_repeated(combo, hold, times, interval) => {
+                if let Err(e) = result {
+                    warn!("❌ Failed to perform {}: {}", name, e);
+                }
+                true
+            }
+        }
+    }
+
+    async fn press_repeated(
+        &self,
+        (modifiers, key): &Combo,
+        hold: Option<Duration>,
+        times: u32,
+        interval: Duration,
+    ) -> Result<()> {
+        for i in 0..times {
+            if i > 0 {
+                tokio::time::sleep(interval).await;
+            }
+            let Some(duration) = hold else {
+                let mut kb = self
+                    .keyboard
+                    .lock()
+                    .expect("Shared keyboard mutex poisoned");
+                let kb = kb.as_mut().ok_or_else(|| anyhow!("No keyboard"))?;
+                if modifiers.is_empty() {
+                    kb.tap_key(*key)?;
+                } else {
+                    kb.key_combo(modifiers, *key)?;
+                }
+                continue;
+            };
+
+            // Don't keep the keyboard locked while the key is down
+            {
+                let mut kb = self
+                    .keyboard
+                    .lock()
+                    .expect("Shared keyboard mutex poisoned");
+                let kb = kb.as_mut().ok_or_else(|| anyhow!("No keyboard"))?;
+                // Registered first, under the keyboard lock, so an abort either
+                // runs before the press or sees the key and releases it
+                self.in_flight.hold(modifiers, *key);
+                if let Err(e) = kb.press_combo(modifiers, *key) {
+                    self.in_flight.unhold(modifiers, *key);
+                    return Err(e);
+                }
+            }
+            tokio::time::sleep(duration).await;
+            if !self.in_flight.unhold(modifiers, *key) {
+                return Ok(());
+            }
+            let mut kb = self
+                .keyboard
+                .lock()
+                .expect("Shared keyboard mutex poisoned");
+            let kb = kb.as_mut().ok_or_else(|| anyhow!("No keyboard"))?;
+            kb.release_combo(modifiers, *key)?;
+        }
+        Ok(())
+    }
+
+    fn press(&self, key: &str, modifiers: &[String]) -> Result<()> {
+        let mut kb = self
+            .keyboard
+            .lock()
+            .expect("Shared keyboard mutex poisoned");
+        let kb = kb
+            .as_mut()
+            .ok_or_else(|| anyhow!("No virtual keyboard available"))?;
+        CommandProcessor::press_keys_internal(kb, key, modifiers)
+    }
+
+    /// Run steps in order; an error stops the whole macro
+    fn run_steps<'a>(
+        &'a self,
+        name: &'a str,
+        steps: &'a [MacroStep],
+        depth: usize,
+    ) -> BoxFuture<'a, Result<()>> {
+        async move {
+            for (step_idx, step) in steps.iter().enumerate() {
+                self.play_step_audio(name, step_idx, step);
+
+                if let Some(effect) = &step.effect {
+                    if let Err(e) = self.run_effect(effect).await {
+                        warn!("❌ Macro '{}' step {} failed: {}", name, step_idx, e);
+                    }
+                }
+
+                // Execute key press (only if action is specified)
+                if !step.action.is_empty() {
+                    match self.action_map.get(&step.action) {
+                        Some(binding) => {
+                            if let Some(key) = &binding.primary_key {
+                                if let Err(e) = self.press(key, &binding.modifiers) {
+                                    warn!("❌ Macro '{}' couldn't press {}: {}", name, key, e);
+                                }
+                            }
+                        }
+                        None => warn!("⚠️ Unknown action in macro: {}", step.action),
+                    }
+                }
+
+                if let Some(flow) = &step.flow {
+                    self.run_flow(name, flow, depth).await?;
+                }
+
+                if step.delay > 0 {
+                    tokio::time::sleep(Duration::from_millis(step.delay)).await;
+                }
+            }
+            Ok(())
+        }
+        .boxed()
+    }
+
+    async fn run_flow(&self, name: &str, flow: &StepFlow, depth: usize) -> Result<()> {
+        match flow {
+            StepFlow::Call { name: callee } => {
+                if depth >= MAX_CALL_DEPTH {
+                    bail!(
+                        "calls nested deeper than {} at '{}'",
+                        MAX_CALL_DEPTH,
+                        callee
+                    );
+                }
+                match self.commands.iter().find(|c| c.name() == callee) {
+                    Some(Command::Macro(m)) => {
+                        // Claimed like a top-level run, so it can't start twice
+                        // and an abort reports it
+                        let claim = self
+                            .in_flight
+                            .claim(&m.name)
+                            .ok_or_else(|| anyhow!("'{}' is already running", m.name))?;
+                        debug!("📜 Macro '{}' calls '{}'", name, m.name);
+                        tokio::select! {
+                            biased;
+                            _ = claim.token.cancelled() => bail!("'{}' was aborted", m.name),
+                            result = self.run_steps(&m.name, &m.steps, depth + 1) => result?,
+                        }
+                    }
+                    Some(Command::Action { key, modifiers, .. }) => self.press(key, modifiers)?,
+                    None => bail!("no command named '{}'", callee),
+                }
+            }
+            StepFlow::Repeat { times, steps } => {
+                for _ in 0..*times {
+                    self.run_steps(name, steps, depth).await?;
+                }
+            }
+            StepFlow::WaitFor {
+                phrases,
+                timeout_ms,
+            } => {
+                info!("⏸️ Macro '{}' waiting for {:?}", name, phrases);
+                let heard = self.waiters.wait(name, phrases);
+                match tokio::time::timeout(Duration::from_millis(*timeout_ms), heard).await {
+                    Ok(Ok(())) => info!("▶️ Macro '{}' resumed", name),
+                    _ => bail!("none of {:?} heard within {} ms", phrases, timeout_ms),
+                }
+            }
+            StepFlow::If {
+                condition,
+                then,
+                otherwise,
+            } => {
+                let branch = if self.holds(condition) {
+                    then
+                } else {
+                    otherwise
+                };
+                self.run_steps(name, branch, depth).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn run_effect(&self, effect: &StepEffect) -> Result<()> {
+        match effect {
+            StepEffect::Speak { text } => {
+                let tts = self.tts.as_ref().ok_or_else(|| anyhow!("No TTS engine"))?;
+                tts.speak(text).await?;
+            }
+            StepEffect::Player { command } => {
+                let pm = self
+                    .player_manager
+                    .as_ref()
+                    .ok_or_else(|| anyhow!("No media player"))?;
+                let player = pm.player();
+                let player = player.read().await;
+                match command {
+                    PlayerCommand::PlayPause => player.play_pause().await?,
+                    PlayerCommand::Pause => player.pause().await?,
+                    PlayerCommand::Play => player.play().await?,
+                    PlayerCommand::Next => player.next_track().await?,
+                    PlayerCommand::Previous => player.previous_track().await?,
+                    PlayerCommand::Stop => player.stop().await?,
+                    PlayerCommand::VolumeUp => player.volume_up().await?,
+                    PlayerCommand::VolumeDown => player.volume_down().await?,
+                }
+            }
+            StepEffect::Type { text } => {
+                let mut kb = self
+                    .keyboard
+                    .lock()
+                    .expect("Shared keyboard mutex poisoned");
+                kb.as_mut()
+                    .ok_or_else(|| anyhow!("No virtual keyboard available"))?
+                    .type_text(text)?;
+            }
+            StepEffect::Volume { level } => {
+                let engine = self
+                    .sound_engine
+                    .as_ref()
+                    .ok_or_else(|| anyhow!("No sound engine"))?;
+                engine.set_volume(*level)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn holds(&self, condition: &StepCondition) -> bool {
+        match condition {
+            StepCondition::Toggle { name, on } => {
+                let toggles = self.toggles.lock().expect("Toggle mutex poisoned");
+                toggles.get(name).and_then(|t| t.state) == Some(*on)
+            }
+            StepCondition::Profile { name } => self
+                .active_profile
+                .as_ref()
+                .is_some_and(|p| p.eq_ignore_ascii_case(name)),
+        }
+    }
+
+    fn play_step_audio(&self, name: &str, step_idx: usize, step: &MacroStep) {
+        // === Audio Fallback Chain (matching Python) ===
+        // 1. Check audio_pool
+        // 2. If empty, check audio_feedback_file (legacy single file)
+        // 3. If empty, check audio_feedback (LAL ID)
+        let Some(engine) = &self.sound_engine else {
+            return;
+        };
+        let mut audio_pool = step.audio_pool.clone();
+
+        // Legacy fallback: if no pool, use single file
+        if audio_pool.is_empty() {
+            if let Some(ref legacy_file) = step.audio_feedback_file {
+                if legacy_file != "(Sound Pool)" {
+                    audio_pool.push(legacy_file.clone());
+                }
+            }
+        }
+
+        // LAL ID fallback: if still empty and ID exists
+        if audio_pool.is_empty() {
+            if let Some(ref audio_id) = step.audio_feedback {
+                if let Some(mgr) = &self.lal_manager {
+                    if let Some(path) = mgr.get_audio(audio_id) {
+                        let path_str = path.to_string_lossy().to_string();
+                        audio_pool.push(path_str);
+                    } else {
+                        warn!("⚠️ LAL Audio ID not found: {}", audio_id);
+                    }
+                }
+            }
+        }
+
+        if audio_pool.is_empty() {
+            return;
+        }
+        let pool_id = format!("{}_step_{}", name, step_idx);
+        let mut resolved_paths = Vec::new();
+
+        for p in audio_pool {
+            let mut final_path = PathBuf::from(&p);
+
+            // 1. Check if path exists directly (or relative to CWD)
+            if !final_path.exists() {
+                // 2. Check Custom Audio Dir
+                if let Some(ref custom_dir) = self.custom_audio_dir {
+                    let custom_path = custom_dir.join(&p);
+                    if custom_path.exists() {
+                        final_path = custom_path;
+                    }
+                }
+            }
+
+            // 3. Directory Randomization (Task 7.5)
+            if final_path.is_dir() {
+                if let Ok(entries) = std::fs::read_dir(&final_path) {
+                    let files: Vec<_> = entries
+                        .filter_map(|e| e.ok())
+                        .map(|e| e.path())
+                        .filter(|p| {
+                            p.is_file()
+                                && p.extension().is_some_and(|ext| {
+                                    let ext = ext.to_string_lossy();
+                                    matches!(ext.as_ref(), "wav" | "mp3" | "ogg" | "flac")
+                                })
+                        })
+                        .collect();
+
+                    if !files.is_empty() {
+                        use rand::seq::SliceRandom;
+                        if let Some(picked) = files.choose(&mut rand::thread_rng()) {
+                            final_path = picked.clone();
+                            debug!("🎲 Randomly picked {} from {}", final_path.display(), p);
+                        }
+                    }
+                }
+            }
+
+            if final_path.exists() && final_path.is_file() {
+                resolved_paths.push(final_path);
+            } else {
+                warn!("⚠️ Audio file not found: {}", p);
+            }
+        }
+
+        if !resolved_paths.is_empty() {
+            let _ = engine.play_pool(&pool_id, resolved_paths, step.playback_mode);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::MAX_STEP_REPEAT;
+
+    fn runner(commands: Vec<Command>, profile: &str) -> MacroRunner {
+        let keyboard = Arc::new(Mutex::new(None));
+        MacroRunner {
+            in_flight: InFlight::new(keyboard.clone()),
+            keyboard,
+            action_map: HashMap::new(),
+            commands,
+            toggles: Arc::new(Mutex::new(HashMap::new())),
+            active_profile: Some(profile.to_string()),
+            waiters: SpeechWaiters::default(),
+            sound_engine: None,
+            player_manager: None,
+            tts: None,
+            lal_manager: None,
+            custom_audio_dir: None,
+        }
+    }
+
+    fn flow(flow: StepFlow) -> MacroStep {
+        MacroStep {
+            flow: Some(flow),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_old_steps_still_load() {
+        let json = r#"{"name": "Dock", "triggers": ["dock"],
+            "steps": [{"action": "LandingGearToggle", "delay": 500}]}"#;
+        let m: Macro = serde_json::from_str(json).unwrap();
+        assert_eq!(m.steps[0].flow, None);
+
+        let json = r#"{"name": "Dock", "triggers": ["dock"], "steps": [
+            {"flow": {"kind": "if", "condition": {"state": "profile", "name": "Elite"},
+                      "then": [{"flow": {"kind": "wait_for", "phrases": ["go"]}}]}}]}"#;
+        let m: Macro = serde_json::from_str(json).unwrap();
+        assert_eq!(m.wait_phrases(), vec!["go"]);
+        assert_eq!(
+            serde_json::to_value(&m).unwrap()["steps"][0]["flow"]["kind"],
+            "if"
+        );
+    }
+
+    #[test]
+    fn test_docking_procedure_loads() {
+        let json = r#"{"name": "Docking Procedure", "triggers": ["docking procedure"], "steps": [
+            {"effect": {"kind": "player", "command": "pause"}},
+            {"effect": {"kind": "speak", "text": "Requesting docking clearance"}},
+            {"action": "UI_Left", "delay": 200},
+            {"effect": {"kind": "type", "text": "o7"}},
+            {"effect": {"kind": "volume", "level": 0.5}},
+            {"effect": {"kind": "player", "command": "resume"}}]}"#;
+        let m: Macro = serde_json::from_str(json).unwrap();
+        let effects: Vec<_> = m.steps.iter().map(|s| s.effect.clone()).collect();
+        assert_eq!(
+            effects[0],
+            Some(StepEffect::Player {
+                command: PlayerCommand::Pause
+            })
+        );
+        assert_eq!(effects[2], None);
+        // Pausing and resuming are distinct, so a paused player isn't restarted by "pause"
+        assert_eq!(
+            effects[5],
+            Some(StepEffect::Player {
+                command: PlayerCommand::Play
+            })
+        );
+        assert_eq!(m.steps[2].action, "UI_Left");
+    }
+
+    #[test]
+    fn test_repeat_clamped() {
+        let json = r#"{"name": "Spam", "triggers": ["spam"], "steps": [
+            {"flow": {"kind": "repeat", "times": 4000000000, "steps": [
+                {"flow": {"kind": "repeat", "times": 3, "steps": []}}]}}]}"#;
+        let mut m: Macro = serde_json::from_str(json).unwrap();
+        assert!(m.clamp_repeats());
+        let Some(StepFlow::Repeat { times, steps }) = &m.steps[0].flow else {
+            panic!("Expected a repeat step");
+        };
+        assert_eq!(*times, MAX_STEP_REPEAT);
+        assert_eq!(
+            steps[0].flow,
+            Some(StepFlow::Repeat {
+                times: 3,
+                steps: Vec::new()
+            })
+        );
+        assert!(!m.clamp_repeats());
+    }
+
+    #[tokio::test]
+    async fn test_branch_call_and_wait() {
+        let wait = Command::Macro(Macro {
+            name: "Wait".to_string(),
+            steps: vec![flow(StepFlow::WaitFor {
+                phrases: vec!["Go".to_string()],
+                timeout_ms: 5_000,
+            })],
+            ..Default::default()
+        });
+        let main = Macro {
+            name: "Dock".to_string(),
+            steps: vec![flow(StepFlow::If {
+                condition: StepCondition::Profile {
+                    name: "elite".to_string(),
+                },
+                then: vec![flow(StepFlow::Call {
+                    name: "Wait".to_string(),
+                })],
+                otherwise: Vec::new(),
+            })],
+            ..Default::default()
+        };
+
+        // Other profile: nothing to wait for
+        let other = runner(vec![wait.clone()], "X4");
+        other.run(Command::Macro(main.clone())).await;
+        assert_eq!(other.waiters.resume("go"), None);
+
+        let elite = runner(vec![wait], "Elite");
+        let waiters = elite.waiters.clone();
+        let in_flight = elite.in_flight.clone();
+        let task = tokio::spawn(async move { elite.run(Command::Macro(main)).await });
+        let mut resumed = None;
+        for _ in 0..50 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            if !waiters.is_waiting() {
+                continue;
+            }
+            // The called macro holds its name while it runs
+            assert!(in_flight.is_running("Dock") && in_flight.is_running("Wait"));
+            resumed = waiters.resume("go");
+            if resumed.is_some() {
+                break;
+            }
+        }
+        assert_eq!(resumed.as_deref(), Some("Wait"));
+        task.await.unwrap();
+        assert!(!in_flight.is_running("Wait"));
+    }
+
+    #[tokio::test]
+    async fn test_call_cycle_stops() {
+        let looping = Macro {
+            name: "Loop".to_string(),
+            steps: vec![flow(StepFlow::Repeat {
+                times: 2,
+                steps: vec![flow(StepFlow::Call {
+                    name: "Loop".to_string(),
+                })],
+            })],
+            ..Default::default()
+        };
+        let runner = runner(vec![Command::Macro(looping.clone())], "Elite");
+        // Called from outside, the first call claims "Loop" and the nested one is refused
+        let result = runner.run_steps("Outer", &looping.steps, 0).await;
+        assert!(result.unwrap_err().to_string().contains("already running"));
+        assert!(!runner.in_flight.is_running("Loop"));
+    }
+
+    #[tokio::test]
+    async fn test_abort_running_macro() {
+        let waiting = Macro {
+            name: "Dock".to_string(),
+            steps: vec![flow(StepFlow::WaitFor {
+                phrases: vec!["go".to_string()],
+                timeout_ms: 60_000,
+            })],
+            ..Default::default()
+        };
+        let runner = runner(Vec::new(), "Elite");
+        let in_flight = runner.in_flight.clone();
+        let claim = in_flight.claim("Dock").unwrap();
+        assert!(in_flight.claim("Dock").is_none());
+
+        let task = tokio::spawn(async move { runner.run_claimed(&waiting, &claim).await });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(in_flight.abort_all().commands, vec!["Dock".to_string()]);
+        assert!(!task.await.unwrap());
+
+        // The claim was dropped with the run, so the macro can start again
+        assert!(!in_flight.is_running("Dock"));
+        assert!(in_flight.claim("Dock").is_some());
+
+        let nothing = Aborted::default();
+        assert!(nothing.is_empty());
+        assert_eq!(nothing.to_string(), "Nothing to abort");
+        let keys_only = Aborted {
+            commands: Vec::new(),
+            released_keys: 1,
+        };
+        assert!(!keys_only.is_empty());
+        assert_eq!(keys_only.to_string(), "Released 1 held key(s)");
+    }
+}
@@ -3,6 +3,9 @@
 //! Contains the central command processing, text normalization,
 //! selection handling, and AI integration logic.
 
+pub mod command_modifiers;
+pub mod macro_runner;
 pub mod ollama;
 pub mod text_normalizer;
+pub mod trigger_template;
 // pub mod selection_handler; // TODO: Phase 5
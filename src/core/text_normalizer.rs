@@ -19,6 +19,7 @@ impl TextNormalizer {
 
         // Basic number words
         for (word, num) in [
+            ("zero", 0),
             ("one", 1),
             ("two", 2),
             ("three", 3),
@@ -39,6 +40,14 @@ impl TextNormalizer {
             ("eighteen", 18),
             ("nineteen", 19),
             ("twenty", 20),
+            ("thirty", 30),
+            ("forty", 40),
+            ("fifty", 50),
+            ("sixty", 60),
+            ("seventy", 70),
+            ("eighty", 80),
+            ("ninety", 90),
+            ("hundred", 100),
         ] {
             number_words.insert(word, num);
         }
@@ -113,23 +122,75 @@ impl TextNormalizer {
         final_text.replace("  ", " ").trim().to_string()
     }
 
-    /// Parse a spoken number (1-99) from text
+    /// Parse a spoken number (0-100) from text
+    ///
+    /// Accepts digits, single words ("twelve") and compounds ("twenty five",
+    /// "forty-two", "one hundred").
     pub fn parse_number(&self, text: &str) -> Option<u32> {
-        let text_lower = text.to_lowercase();
+        let text_lower = text.trim().to_lowercase();
 
         // Try direct number
         if let Ok(num) = text_lower.parse::<u32>() {
-            if num > 0 && num <= 99 {
-                return Some(num);
-            }
+            return (num <= 100).then_some(num);
         }
 
-        // Try word
-        if let Some(&num) = self.number_words.get(text_lower.as_str()) {
-            return Some(num);
+        // Try words
+        let words: Vec<&str> = text_lower
+            .split(|c: char| c.is_whitespace() || c == '-')
+            .filter(|w| !w.is_empty())
+            .collect();
+        let word = |w: &str| self.number_words.get(w).copied();
+        match words.as_slice() {
+            [single] => word(single),
+            ["one" | "a", "hundred"] => Some(100),
+            [tens, unit] => {
+                let (tens, unit) = (word(tens)?, word(unit)?);
+                ((20..100).contains(&tens) && tens.is_multiple_of(10) && (1..=9).contains(&unit))
+                    .then_some(tens + unit)
+            }
+            _ => None,
         }
+    }
+}
 
-        None
+/// Spell out a number (0-100) the way it is spoken, e.g. "forty two"
+pub fn spell_number(num: u32) -> String {
+    const ONES: [&str; 20] = [
+        "zero",
+        "one",
+        "two",
+        "three",
+        "four",
+        "five",
+        "six",
+        "seven",
+        "eight",
+        "nine",
+        "ten",
+        "eleven",
+        "twelve",
+        "thirteen",
+        "fourteen",
+        "fifteen",
+        "sixteen",
+        "seventeen",
+        "eighteen",
+        "nineteen",
+    ];
+    const TENS: [&str; 10] = [
+        "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+    ];
+
+    match num {
+        0..=19 => ONES[num as usize].to_string(),
+        20..=99 if num.is_multiple_of(10) => TENS[(num / 10) as usize].to_string(),
+        20..=99 => format!(
+            "{} {}",
+            TENS[(num / 10) as usize],
+            ONES[(num % 10) as usize]
+        ),
+        100 => "one hundred".to_string(),
+        _ => num.to_string(),
     }
 }
 
@@ -174,5 +235,18 @@ mod tests {
         assert_eq!(normalizer.parse_number("five"), Some(5));
         assert_eq!(normalizer.parse_number("12"), Some(12));
         assert_eq!(normalizer.parse_number("invalid"), None);
+        assert_eq!(normalizer.parse_number("forty-two"), Some(42));
+        assert_eq!(normalizer.parse_number("seventy five"), Some(75));
+        assert_eq!(normalizer.parse_number("one hundred"), Some(100));
+        assert_eq!(normalizer.parse_number("twelve five"), None);
+        assert_eq!(normalizer.parse_number("250"), None);
+    }
+
+    #[test]
+    fn test_spell_number_round_trips() {
+        let normalizer = TextNormalizer::new(HashMap::new());
+        for num in 0..=100 {
+            assert_eq!(normalizer.parse_number(&spell_number(num)), Some(num));
+        }
     }
 }
@@ -0,0 +1,328 @@
+//! Trigger templates with typed slots
+//!
+//! A trigger such as `select weapon group {n:1-4}` or `set throttle {percent}`
+//! matches a spoken number in place of each slot ("select weapon group three",
+//! "set throttle seventy five percent") and captures it. The captured values
+//! are substituted into `{name}` placeholders of the command that fires (see
+//! [`crate::commands::Command::bind`]), so one templated binding replaces a
+//! family of copy-pasted ones.
+//!
+//! Slot syntax:
+//! - `{n}` / `{n:number}`: any number from 0 to 100
+//! - `{n:1-4}`: a number in the inclusive range
+//! - `{percent}` / `{n:percent}`: 0 to 100, optionally followed by "percent"
+
+use super::text_normalizer::{spell_number, TextNormalizer};
+use anyhow::{Context, Result};
+use lazy_static::lazy_static;
+use std::collections::{BTreeMap, HashMap};
+
+lazy_static! {
+    static ref NUMBERS: TextNormalizer = TextNormalizer::new(HashMap::new());
+}
+
+/// Values captured by a template match, by slot name
+pub type SlotValues = BTreeMap<String, u32>;
+
+/// Most words a spoken number takes ("seventy five", "one hundred")
+const MAX_NUMBER_WORDS: usize = 2;
+
+/// What a slot accepts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlotKind {
+    /// A number within the inclusive range
+    Number { min: u32, max: u32 },
+    /// 0-100, optionally followed by "percent"
+    Percent,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Word(String),
+    Slot { name: String, kind: SlotKind },
+}
+
+/// A parsed trigger: literal words and typed slots
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TriggerTemplate {
+    parts: Vec<Part>,
+}
+
+/// Where a template matched inside an utterance
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateMatch {
+    /// Token range of the utterance covered by the match
+    pub start: usize,
+    pub end: usize,
+    pub values: SlotValues,
+}
+
+impl TriggerTemplate {
+    /// Whether `trigger` uses slot syntax at all
+    pub fn is_template(trigger: &str) -> bool {
+        trigger.contains('{')
+    }
+
+    /// Parse a trigger (a trigger without slots is all literal words)
+    pub fn parse(trigger: &str) -> Result<Self> {
+        let mut parts = Vec::new();
+        for token in trigger.split_whitespace() {
+            if let Some(inner) = token.strip_prefix('{') {
+                let inner = inner
+                    .strip_suffix('}')
+                    .with_context(|| format!("Unclosed slot '{}' in '{}'", token, trigger))?;
+                parts.push(parse_slot(inner).with_context(|| format!("In trigger '{}'", trigger))?);
+            } else if token.contains(['{', '}']) {
+                anyhow::bail!("Slot '{}' must be a separate word in '{}'", token, trigger);
+            } else {
+                // Same word splitting as utterances get from `tokenize`
+                parts.extend(tokenize(token).into_iter().map(Part::Word));
+            }
+        }
+        if parts.is_empty() {
+            anyhow::bail!("Empty trigger");
+        }
+        Ok(Self { parts })
+    }
+
+    /// Whether the template has any slots
+    pub fn has_slots(&self) -> bool {
+        self.parts.iter().any(|p| matches!(p, Part::Slot { .. }))
+    }
+
+    /// Number of literal words (how specific the template is)
+    pub fn literal_words(&self) -> usize {
+        self.parts
+            .iter()
+            .filter(|p| matches!(p, Part::Word(_)))
+            .count()
+    }
+
+    /// Find the first place the template matches within `tokens`
+    ///
+    /// `tokens` should come from [`tokenize`].
+    pub fn find_in(&self, tokens: &[String]) -> Option<TemplateMatch> {
+        (0..tokens.len()).find_map(|start| {
+            let mut values = SlotValues::new();
+            self.match_from(0, tokens, start, &mut values)
+                .map(|end| TemplateMatch { start, end, values })
+        })
+    }
+
+    /// Match parts `part..` against tokens `pos..`, returning the end position
+    fn match_from(
+        &self,
+        part: usize,
+        tokens: &[String],
+        pos: usize,
+        values: &mut SlotValues,
+    ) -> Option<usize> {
+        let Some(current) = self.parts.get(part) else {
+            return Some(pos);
+        };
+
+        match current {
+            Part::Word(word) => {
+                if tokens.get(pos) == Some(word) {
+                    self.match_from(part + 1, tokens, pos + 1, values)
+                } else {
+                    None
+                }
+            }
+            Part::Slot { name, kind } => {
+                // Prefer the longer reading ("twenty five" over "twenty")
+                for len in (1..=MAX_NUMBER_WORDS).rev() {
+                    let Some(words) = tokens.get(pos..pos + len) else {
+                        continue;
+                    };
+                    let Some(value) = NUMBERS.parse_number(&words.join(" ")) else {
+                        continue;
+                    };
+                    if !kind.accepts(value) {
+                        continue;
+                    }
+
+                    let mut next = pos + len;
+                    if *kind == SlotKind::Percent {
+                        next += percent_suffix(&tokens[next..]);
+                    }
+                    values.insert(name.clone(), value);
+                    if let Some(end) = self.match_from(part + 1, tokens, next, values) {
+                        return Some(end);
+                    }
+                    values.remove(name);
+                }
+                None
+            }
+        }
+    }
+
+    /// Every concrete phrase the template matches (numbers spelled out), for
+    /// recognizer grammars. Stops after `limit` phrases.
+    pub fn expand(&self, limit: usize) -> Vec<String> {
+        let mut phrases = vec![String::new()];
+        for part in &self.parts {
+            let options: Vec<String> = match part {
+                Part::Word(word) => vec![word.clone()],
+                Part::Slot { kind, .. } => {
+                    let (min, max) = kind.range();
+                    (min..=max).map(spell_number).collect()
+                }
+            };
+            phrases = phrases
+                .iter()
+                .flat_map(|prefix| {
+                    options.iter().map(move |option| {
+                        if prefix.is_empty() {
+                            option.clone()
+                        } else {
+                            format!("{} {}", prefix, option)
+                        }
+                    })
+                })
+                .take(limit)
+                .collect();
+        }
+        phrases
+    }
+}
+
+impl SlotKind {
+    fn range(self) -> (u32, u32) {
+        match self {
+            SlotKind::Number { min, max } => (min, max),
+            SlotKind::Percent => (0, 100),
+        }
+    }
+
+    fn accepts(self, value: u32) -> bool {
+        let (min, max) = self.range();
+        (min..=max).contains(&value)
+    }
+}
+
+/// Parse the inside of `{...}`
+fn parse_slot(inner: &str) -> Result<Part> {
+    let (name, kind) = match inner.split_once(':') {
+        Some((name, kind)) => (name.trim(), kind.trim()),
+        // `{percent}` / `{number}` name the type, anything else is a number
+        None => (inner.trim(), inner.trim()),
+    };
+    if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        anyhow::bail!("Invalid slot name '{}'", name);
+    }
+
+    let kind = match kind {
+        "percent" => SlotKind::Percent,
+        _ if kind == name || kind == "number" => SlotKind::Number { min: 0, max: 100 },
+        range => {
+            let (min, max) = range
+                .split_once('-')
+                .and_then(|(a, b)| Some((a.trim().parse().ok()?, b.trim().parse().ok()?)))
+                .with_context(|| format!("Unknown slot type '{}'", range))?;
+            if min > max || max > 100 {
+                anyhow::bail!("Invalid slot range '{}' (must be within 0-100)", range);
+            }
+            SlotKind::Number { min, max }
+        }
+    };
+    Ok(Part::Slot {
+        name: name.to_string(),
+        kind,
+    })
+}
+
+/// Tokens taken by an optional "percent" after a number
+fn percent_suffix(tokens: &[String]) -> usize {
+    match tokens {
+        [first, ..] if first == "percent" || first == "%" => 1,
+        [first, second, ..] if first == "per" && second == "cent" => 2,
+        _ => 0,
+    }
+}
+
+/// Split an utterance into lowercase words for template matching
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .replace('%', " % ")
+        .split_whitespace()
+        .map(|w| {
+            w.trim_matches(|c: char| c.is_ascii_punctuation() && c != '%')
+                .to_string()
+        })
+        .filter(|w| !w.is_empty())
+        .collect()
+}
+
+/// Replace `{name}` placeholders in `text` with captured values
+pub fn fill_slots(text: &str, values: &SlotValues) -> String {
+    values.iter().fold(text.to_string(), |text, (name, value)| {
+        text.replace(&format!("{{{}}}", name), &value.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find(template: &str, text: &str) -> Option<SlotValues> {
+        TriggerTemplate::parse(template)
+            .unwrap()
+            .find_in(&tokenize(text))
+            .map(|m| m.values)
+    }
+
+    fn values(pairs: &[(&str, u32)]) -> SlotValues {
+        pairs.iter().map(|(k, v)| (k.to_string(), *v)).collect()
+    }
+
+    #[test]
+    fn test_number_slots() {
+        let template = "select weapon group {n:1-4}";
+        assert_eq!(
+            find(template, "Select weapon group three."),
+            Some(values(&[("n", 3)]))
+        );
+        assert_eq!(
+            find(template, "okay select weapon group 2 now"),
+            Some(values(&[("n", 2)]))
+        );
+        assert_eq!(find(template, "select weapon group five"), None);
+        assert_eq!(find(template, "select weapon group"), None);
+    }
+
+    #[test]
+    fn test_percent_slot() {
+        let template = "set throttle {percent}";
+        assert_eq!(
+            find(template, "set throttle seventy five percent"),
+            Some(values(&[("percent", 75)]))
+        );
+        assert_eq!(
+            find(template, "set throttle 50%"),
+            Some(values(&[("percent", 50)]))
+        );
+        assert_eq!(
+            find("throttle {t:percent} now", "throttle twenty now"),
+            Some(values(&[("t", 20)]))
+        );
+    }
+
+    #[test]
+    fn test_parse_errors_and_expansion() {
+        assert!(TriggerTemplate::parse("group {n:4-1}").is_err());
+        assert!(TriggerTemplate::parse("group {n:colour}").is_err());
+        assert!(TriggerTemplate::parse("group{n}").is_err());
+
+        let template = TriggerTemplate::parse("group {n:1-3}").unwrap();
+        assert!(template.has_slots());
+        assert_eq!(
+            template.expand(100),
+            vec!["group one", "group two", "group three"]
+        );
+        assert_eq!(
+            fill_slots("Weapon Group {n}", &values(&[("n", 3)])),
+            "Weapon Group 3"
+        );
+    }
+}
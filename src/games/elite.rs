@@ -10,7 +10,7 @@ use std::fs;
 use std::path::Path;
 use tracing::{debug, info};
 
-use super::{GameProfile, KeyBinding};
+use super::{GameProfile, KeyBinding, TagState};
 use crate::commands::{Macro, MacroStep};
 
 /// Initialize default virtual tags and voice commands for Elite Dangerous
@@ -48,14 +48,11 @@ pub fn init_defaults(profile: &mut GameProfile) {
     // Voice Commands: Friendly Name -> [Triggers]
     let voice_commands = vec![
         ("Boost", vec!["boost", "boost engines", "afterburner"]),
-        ("Landing Gear", vec!["landing gear", "gear", "deploy gear"]),
+        ("Landing Gear", vec!["landing gear", "gear"]),
         ("Cargo Scoop", vec!["cargo scoop", "scoop", "utility scoop"]),
         ("Lights", vec!["lights", "ship lights", "headlights"]),
         ("Galaxy Map", vec!["galaxy map", "open map", "star map"]),
-        (
-            "Hardpoints",
-            vec!["hard points", "weapons", "deploy weapons"],
-        ),
+        ("Hardpoints", vec!["hard points", "weapons"]),
         (
             "Frame Shift Drive",
             vec!["engage", "warp", "jump", "hyperspace"],
@@ -69,6 +66,40 @@ pub fn init_defaults(profile: &mut GameProfile) {
         );
     }
 
+    // Toggles: "deploy gear" must not retract gear that is already down
+    let toggles = vec![
+        (
+            "Landing Gear",
+            TagState::toggle(
+                &["deploy gear", "gear down", "deploy landing gear"],
+                &["retract gear", "gear up", "retract landing gear"],
+            ),
+        ),
+        (
+            "Hardpoints",
+            TagState::toggle(
+                &["deploy weapons", "deploy hardpoints", "weapons hot"],
+                &["retract weapons", "retract hardpoints", "weapons cold"],
+            ),
+        ),
+        (
+            "Cargo Scoop",
+            TagState::toggle(
+                &["open cargo scoop", "deploy cargo scoop"],
+                &["close cargo scoop", "retract cargo scoop"],
+            ),
+        ),
+        ("Lights", TagState::toggle(&["lights on"], &["lights off"])),
+        (
+            "Flight Assist",
+            TagState::toggle(&["flight assist on"], &["flight assist off"]),
+        ),
+    ];
+
+    for (friendly, state) in toggles {
+        profile.tag_states.insert(friendly.to_string(), state);
+    }
+
     // Process names for auto-detection
     profile.process_names = vec![
         "EliteDangerous64.exe".into(),
@@ -120,6 +151,7 @@ pub fn init_defaults(profile: &mut GameProfile) {
                 ..Default::default()
             },
         ],
+        ..Default::default()
     });
 }
 
@@ -271,3 +303,56 @@ fn normalize_ed_key(key: &str) -> String {
         other => other.to_uppercase(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::{CommandProcessor, ProcessResult};
+    use crate::games::GameType;
+
+    #[tokio::test]
+    async fn test_landing_gear_toggle() {
+        let mut profile = GameProfile::new("Test Elite", GameType::EliteDangerous);
+        profile.raw_bindings.insert(
+            "LandingGearToggle".to_string(),
+            KeyBinding {
+                action: "LandingGearToggle".to_string(),
+                primary_key: Some("L".to_string()),
+                secondary_key: None,
+                modifiers: vec![],
+            },
+        );
+        assert!(profile
+            .voice_phrases()
+            .contains(&"retract gear".to_string()));
+
+        let mut processor = CommandProcessor::new().unwrap();
+        for cmd in profile.get_processor_commands() {
+            processor.add_command(cmd);
+        }
+        processor.set_toggles(profile.tag_states.clone());
+
+        // Gear known to be down: deploying it again must not press the key
+        assert!(processor.sync_toggle("Landing Gear", Some(true)));
+        match processor.process("deploy gear").await {
+            ProcessResult::AlreadySet { action, on } => {
+                assert_eq!(action, "Landing Gear");
+                assert!(on);
+            }
+            other => panic!("Expected AlreadySet, got {:?}", other),
+        }
+        assert_eq!(processor.toggle_state("Landing Gear"), Some(true));
+        assert!(!processor.sync_toggle("Boost", Some(true)));
+    }
+
+    #[test]
+    fn test_profiles_without_tag_states_still_load() {
+        let profile = GameProfile::new("Test Elite", GameType::EliteDangerous);
+        let mut json = serde_json::to_value(&profile).unwrap();
+        json.as_object_mut().unwrap().remove("tag_states");
+
+        let loaded: GameProfile = serde_json::from_value(json).unwrap();
+        assert!(loaded.tag_states.is_empty());
+        assert_eq!(loaded.virtual_tags, profile.virtual_tags);
+    }
+}
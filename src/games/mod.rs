@@ -10,9 +10,13 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
-use crate::commands::{Command, Macro};
+use crate::commands::{Command, Macro, MacroStep, MAX_STEP_REPEAT};
+use crate::core::trigger_template::TriggerTemplate;
+
+/// Most phrases one templated trigger contributes to the recognizer grammar
+const MAX_TEMPLATE_PHRASES: usize = 500;
 
 /// A key binding from a game's config
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,6 +27,59 @@ pub struct KeyBinding {
     pub modifiers: Vec<String>,
 }
 
+/// How a virtual tag's key behaves in the game
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TagKind {
+    /// Each press does something once (boost, open a panel)
+    #[default]
+    Momentary,
+    /// Each press flips an on/off state (landing gear, lights)
+    Toggle,
+}
+
+/// Optional state for a virtual tag
+///
+/// Lets "deploy gear" be a no-op while the gear is believed to be down instead
+/// of retracting it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TagState {
+    #[serde(default)]
+    pub kind: TagKind,
+    /// Believed current state of a toggle (None = unknown)
+    #[serde(default)]
+    pub state: Option<bool>,
+    /// Triggers that mean "turn on" ("deploy gear")
+    #[serde(default)]
+    pub on_triggers: Vec<String>,
+    /// Triggers that mean "turn off" ("retract gear")
+    #[serde(default)]
+    pub off_triggers: Vec<String>,
+}
+
+impl TagState {
+    /// A toggle with separate on/off phrases and an unknown initial state
+    pub fn toggle(on_triggers: &[&str], off_triggers: &[&str]) -> Self {
+        Self {
+            kind: TagKind::Toggle,
+            state: None,
+            on_triggers: on_triggers.iter().map(|s| s.to_string()).collect(),
+            off_triggers: off_triggers.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    /// The state `trigger` asks for, if it is one of the on/off phrases
+    pub fn requested_state(&self, trigger: &str) -> Option<bool> {
+        let is = |t: &String| t.eq_ignore_ascii_case(trigger);
+        if self.on_triggers.iter().any(is) {
+            Some(true)
+        } else if self.off_triggers.iter().any(is) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum GameType {
     EliteDangerous,
@@ -44,6 +101,10 @@ pub struct GameProfile {
     pub macros: Vec<Macro>,
     /// Friendly Name -> Raw Tags mapping (e.g., "Lights" -> ["ShipSpotLightToggle", "Headlights"])
     pub virtual_tags: HashMap<String, Vec<String>>,
+    /// Friendly Name -> toggle behaviour and on/off phrases (tags without an
+    /// entry are momentary)
+    #[serde(default)]
+    pub tag_states: HashMap<String, TagState>,
     /// Process names to look for (e.g., ["EliteDangerous64.exe"])
     pub process_names: Vec<String>,
     /// Path-based discriminators to check in cmdline (e.g., ["steamapps", "compatdata"])
@@ -63,6 +124,7 @@ impl GameProfile {
             voice_commands: HashMap::new(),
             macros: Vec::new(),
             virtual_tags: HashMap::new(),
+            tag_states: HashMap::new(),
             process_names: Vec::new(),
             path_discriminators: Vec::new(),
             enabled: false,
@@ -118,13 +180,42 @@ impl GameProfile {
         let mut commands = Vec::new();
         let action_map = self.resolve_actions();
 
+        // Toggles may only have on/off phrases, without plain triggers
+        let toggle_only = self
+            .tag_states
+            .keys()
+            .filter(|name| !self.voice_commands.contains_key(*name))
+            .map(|name| (name, &[] as &[String]));
+
         // Add actions
-        for (friendly_name, triggers) in &self.voice_commands {
+        let plain = self
+            .voice_commands
+            .iter()
+            .map(|(name, triggers)| (name, triggers.as_slice()));
+        for (friendly_name, triggers) in plain.chain(toggle_only) {
+            let triggers = self.triggers_with_states(friendly_name, triggers);
+            if triggers.is_empty() {
+                continue;
+            }
+            // "Weapon Group {n}" names a family of actions; the slot value picks
+            // the binding when the command runs
+            if friendly_name.contains('{') {
+                commands.push(Command::Macro(Macro {
+                    name: friendly_name.clone(),
+                    triggers,
+                    steps: vec![MacroStep {
+                        action: friendly_name.clone(),
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                }));
+                continue;
+            }
             if let Some(binding) = action_map.get(friendly_name) {
                 if let Some(key) = &binding.primary_key {
                     commands.push(Command::Action {
                         name: friendly_name.clone(),
-                        triggers: triggers.clone(),
+                        triggers,
                         key: key.clone(),
                         modifiers: binding.modifiers.clone(),
                     });
@@ -139,6 +230,48 @@ impl GameProfile {
 
         commands
     }
+
+    /// `triggers` plus the on/off phrases of the tag's state, if any
+    fn triggers_with_states(&self, friendly_name: &str, triggers: &[String]) -> Vec<String> {
+        let mut triggers = triggers.to_vec();
+        if let Some(state) = self.tag_states.get(friendly_name) {
+            triggers.extend(state.on_triggers.iter().cloned());
+            triggers.extend(state.off_triggers.iter().cloned());
+        }
+        triggers
+    }
+
+    /// Every spoken trigger of this profile (voice commands, toggle phrases and
+    /// macros), lowercased and deduplicated, with templated triggers expanded to
+    /// concrete phrases
+    pub fn voice_phrases(&self) -> Vec<String> {
+        let mut phrases: Vec<String> = self
+            .voice_commands
+            .values()
+            .flatten()
+            .chain(
+                self.tag_states
+                    .values()
+                    .flat_map(|s| s.on_triggers.iter().chain(&s.off_triggers)),
+            )
+            .chain(self.macros.iter().flat_map(|m| m.triggers.iter()))
+            .map(String::as_str)
+            .chain(self.macros.iter().flat_map(|m| m.wait_phrases()))
+            .flat_map(|t| {
+                if !TriggerTemplate::is_template(t) {
+                    return vec![t.trim().to_lowercase()];
+                }
+                match TriggerTemplate::parse(t) {
+                    Ok(template) => template.expand(MAX_TEMPLATE_PHRASES),
+                    Err(_) => Vec::new(),
+                }
+            })
+            .filter(|t| !t.is_empty())
+            .collect();
+        phrases.sort();
+        phrases.dedup();
+        phrases
+    }
 }
 
 /// Central manager for game profiles
@@ -188,6 +321,16 @@ impl GameManager {
         if path.exists() {
             let content = fs::read_to_string(path)?;
             self.profiles = serde_json::from_str(&content)?;
+            for profile in &mut self.profiles {
+                for m in &mut profile.macros {
+                    if m.clamp_repeats() {
+                        warn!(
+                            "⚠️ Macro '{}' in '{}' repeats more than {} times, clamped",
+                            m.name, profile.name, MAX_STEP_REPEAT
+                        );
+                    }
+                }
+            }
             info!("📖 Loaded {} profiles", self.profiles.len());
         }
         Ok(())
@@ -206,63 +349,113 @@ impl GameManager {
             .and_then(|idx| self.profiles.get(idx))
     }
 
+    /// Switch the active profile. Returns true if it changed.
+    pub fn set_active_profile(&mut self, index: Option<usize>) -> bool {
+        let index = index.filter(|&i| i < self.profiles.len());
+        if self.active_profile_index == index {
+            return false;
+        }
+        self.active_profile_index = index;
+        match self.get_active_profile() {
+            Some(profile) => info!("🎮 Active profile: {}", profile.name),
+            None => info!("🎮 No active profile"),
+        }
+        true
+    }
+
     /// Automatically detect which game is running and set it as active
     pub fn detect_active_profile(&mut self) -> Option<usize> {
-        self.sys
-            .refresh_processes(sysinfo::ProcessesToUpdate::All, true);
-
-        for (i, profile) in self.profiles.iter().enumerate() {
-            // Check process names
-            for proc_name in &profile.process_names {
-                let proc_name_lower = proc_name.to_lowercase();
-
-                let running = self.sys.processes().values().any(|p| {
-                    let name = p.name().to_string_lossy().to_lowercase();
-                    let exe = p
-                        .exe()
-                        .map(|e| e.to_string_lossy().to_lowercase())
-                        .unwrap_or_default();
-                    let cmdline = p
-                        .cmd()
-                        .iter()
-                        .map(|s| s.to_string_lossy().to_lowercase())
-                        .collect::<Vec<_>>()
-                        .join(" ");
-
-                    // Check if name or exe or cmdline contains the process name
-                    name.contains(&proc_name_lower)
-                        || exe.contains(&proc_name_lower)
-                        || cmdline.contains(&proc_name_lower)
-                });
-
-                if running {
-                    // If we have discriminators, check them too (Red Team - Stamos requirement for security/precision)
-                    if !profile.path_discriminators.is_empty() {
-                        let has_discriminator = self.sys.processes().values().any(|p| {
-                            let cmdline = p
-                                .cmd()
-                                .iter()
-                                .map(|s| s.to_string_lossy().to_lowercase())
-                                .collect::<Vec<_>>()
-                                .join(" ");
-                            profile
-                                .path_discriminators
-                                .iter()
-                                .any(|d| cmdline.contains(&d.to_lowercase()))
-                        });
-
-                        if !has_discriminator {
-                            continue; // Process name matched but path discriminator didn't
-                        }
-                    }
+        let detected = self.find_running_profile();
+        if detected.is_some() {
+            self.set_active_profile(detected);
+        }
+        detected
+    }
+
+    fn find_running_profile(&mut self) -> Option<usize> {
+        running_profile(&mut self.sys, &self.profiles)
+    }
 
-                    debug!("🎯 Auto-detected game: {}", profile.name);
-                    self.active_profile_index = Some(i);
-                    return Some(i);
+    /// Snapshot of the profiles' process matching, for scanning off the async
+    /// runtime (a full process refresh blocks for a noticeable time)
+    pub fn process_detector(&self) -> ProcessDetector {
+        ProcessDetector {
+            sys: sysinfo::System::new(),
+            profiles: self.profiles.clone(),
+        }
+    }
+}
+
+/// Finds which profile's game is running; owns its process table so a scan
+/// can be moved onto a blocking thread
+pub struct ProcessDetector {
+    sys: sysinfo::System,
+    profiles: Vec<GameProfile>,
+}
+
+impl ProcessDetector {
+    /// Name of the first profile whose game is running
+    ///
+    /// Returns the name rather than an index so the result stays valid if the
+    /// manager's profile list changes after the snapshot was taken.
+    pub fn scan(&mut self) -> Option<String> {
+        running_profile(&mut self.sys, &self.profiles).map(|i| self.profiles[i].name.clone())
+    }
+}
+
+fn running_profile(sys: &mut sysinfo::System, profiles: &[GameProfile]) -> Option<usize> {
+    sys.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
+
+    for (i, profile) in profiles.iter().enumerate() {
+        // Check process names
+        for proc_name in &profile.process_names {
+            let proc_name_lower = proc_name.to_lowercase();
+
+            let running = sys.processes().values().any(|p| {
+                let name = p.name().to_string_lossy().to_lowercase();
+                let exe = p
+                    .exe()
+                    .map(|e| e.to_string_lossy().to_lowercase())
+                    .unwrap_or_default();
+                let cmdline = p
+                    .cmd()
+                    .iter()
+                    .map(|s| s.to_string_lossy().to_lowercase())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+
+                // Check if name or exe or cmdline contains the process name
+                name.contains(&proc_name_lower)
+                    || exe.contains(&proc_name_lower)
+                    || cmdline.contains(&proc_name_lower)
+            });
+
+            if running {
+                // If we have discriminators, check them too (Red Team - Stamos requirement for security/precision)
+                if !profile.path_discriminators.is_empty() {
+                    let has_discriminator = sys.processes().values().any(|p| {
+                        let cmdline = p
+                            .cmd()
+                            .iter()
+                            .map(|s| s.to_string_lossy().to_lowercase())
+                            .collect::<Vec<_>>()
+                            .join(" ");
+                        profile
+                            .path_discriminators
+                            .iter()
+                            .any(|d| cmdline.contains(&d.to_lowercase()))
+                    });
+
+                    if !has_discriminator {
+                        continue; // Process name matched but path discriminator didn't
+                    }
                 }
+
+                debug!("🎯 Auto-detected game: {}", profile.name);
+                return Some(i);
             }
         }
-
-        None
     }
+
+    None
 }
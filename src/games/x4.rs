@@ -158,39 +158,11 @@ pub fn init_defaults(profile: &mut GameProfile) {
         ),
         ("Lower Shields", vec!["drop shields".into()]),
         (
-            "Select Primary Weapon Group 1",
-            vec![
-                "primary weapon group one".into(),
-                "group one".into(),
-                "group 1".into(),
-                "select primary one".into(),
-            ],
-        ),
-        (
-            "Select Primary Weapon Group 2",
+            "Select Primary Weapon Group {n}",
             vec![
-                "primary weapon group two".into(),
-                "group two".into(),
-                "group 2".into(),
-                "select primary two".into(),
-            ],
-        ),
-        (
-            "Select Primary Weapon Group 3",
-            vec![
-                "primary weapon group three".into(),
-                "group three".into(),
-                "group 3".into(),
-                "select primary three".into(),
-            ],
-        ),
-        (
-            "Select Primary Weapon Group 4",
-            vec![
-                "primary weapon group four".into(),
-                "group four".into(),
-                "group 4".into(),
-                "select primary four".into(),
+                "primary weapon group {n:1-4}".into(),
+                "group {n:1-4}".into(),
+                "select primary {n:1-4}".into(),
             ],
         ),
         (
@@ -205,31 +177,10 @@ pub fn init_defaults(profile: &mut GameProfile) {
             ],
         ),
         (
-            "Select Secondary Weapon Group 1",
-            vec![
-                "secondary weapon group one".into(),
-                "select secondary one".into(),
-            ],
-        ),
-        (
-            "Select Secondary Weapon Group 2",
-            vec![
-                "secondary weapon group two".into(),
-                "select secondary two".into(),
-            ],
-        ),
-        (
-            "Select Secondary Weapon Group 3",
-            vec![
-                "secondary weapon group three".into(),
-                "select secondary three".into(),
-            ],
-        ),
-        (
-            "Select Secondary Weapon Group 4",
+            "Select Secondary Weapon Group {n}",
             vec![
-                "secondary weapon group four".into(),
-                "select secondary four".into(),
+                "secondary weapon group {n:1-4}".into(),
+                "select secondary {n:1-4}".into(),
             ],
         ),
         (
@@ -360,6 +311,7 @@ pub fn init_defaults(profile: &mut GameProfile) {
                     ..Default::default()
                 },
             ],
+            ..Default::default()
         },
         Macro {
             name: "ScanSurroundings".to_string(),
@@ -376,6 +328,7 @@ pub fn init_defaults(profile: &mut GameProfile) {
                     ..Default::default()
                 },
             ],
+            ..Default::default()
         },
         Macro {
             name: "CombatReady".to_string(),
@@ -401,6 +354,7 @@ pub fn init_defaults(profile: &mut GameProfile) {
                     ..Default::default()
                 },
             ],
+            ..Default::default()
         },
         Macro {
             name: "DockingProcedure".to_string(),
@@ -426,6 +380,7 @@ pub fn init_defaults(profile: &mut GameProfile) {
                     ..Default::default()
                 },
             ],
+            ..Default::default()
         },
         Macro {
             name: "EmergencyRetreat".to_string(),
@@ -446,6 +401,7 @@ pub fn init_defaults(profile: &mut GameProfile) {
                     ..Default::default()
                 },
             ],
+            ..Default::default()
         },
     ];
 
@@ -558,6 +514,7 @@ fn parse_x4_input(input: &str) -> Option<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::commands::Command;
     use crate::games::GameType;
 
     #[test]
@@ -598,4 +555,35 @@ mod tests {
             Some("TAB".to_string())
         );
     }
+
+    #[test]
+    fn test_templated_weapon_group() {
+        let mut profile = GameProfile::new("Test X4", GameType::X4Foundations);
+        profile.raw_bindings.insert(
+            "INPUT_ACTION_SELECT_PRIMARY_WEAPONGROUP_3".to_string(),
+            KeyBinding {
+                action: "INPUT_ACTION_SELECT_PRIMARY_WEAPONGROUP_3".to_string(),
+                primary_key: Some("3".to_string()),
+                secondary_key: None,
+                modifiers: vec![],
+            },
+        );
+        assert!(profile
+            .voice_phrases()
+            .contains(&"primary weapon group three".to_string()));
+
+        let mut processor = crate::commands::CommandProcessor::new().unwrap();
+        for cmd in profile.get_processor_commands() {
+            processor.add_command(cmd);
+        }
+        let actions = profile.resolve_actions();
+
+        let cmd = processor.match_command("group three").unwrap();
+        assert_eq!(cmd.name(), "Select Primary Weapon Group 3");
+        let Command::Macro(m) = cmd else {
+            panic!("expected a macro, got {:?}", cmd);
+        };
+        assert!(actions.contains_key(&m.steps[0].action));
+        assert!(processor.match_command("group seven").is_none());
+    }
 }
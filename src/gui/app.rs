@@ -95,8 +95,6 @@ pub struct TuxTalksApp {
     pub(crate) search_results: Vec<crate::players::SearchResult>,
     /// Is searching running
     pub(crate) _searching: bool,
-    /// Timestamp until which transcriptions should be ignored (mute window during TTS)
-    pub(crate) tts_mute_until: Option<std::time::Instant>,
     /// Last command execution time (rate limiting - Stamos guardrail)
     pub(crate) last_command_time: Option<std::time::Instant>,
     /// Command audit log (last 100 commands - Stamos guardrail)
@@ -106,7 +104,11 @@ pub struct TuxTalksApp {
     /// Selection timeout (10s)
     pub(crate) selection_timeout: Option<std::time::Instant>,
     /// Pending high-risk command confirmation
-    pub(crate) pending_confirmation: Option<(String, crate::commands::Command)>,
+    pub(crate) pending_confirmation: Option<(
+        String,
+        crate::commands::CommandMatch,
+        crate::core::command_modifiers::CommandModifiers,
+    )>,
     /// Confirmation timeout
     pub(crate) confirmation_timeout: Option<std::time::Instant>,
     /// Command mode state (Jony - wake word triggers this)
@@ -164,6 +166,9 @@ impl TuxTalksApp {
         processor.set_player_manager(player_manager.clone());
         processor.set_ollama_handler(ollama.clone());
         processor.set_lal_manager(lal_manager.clone());
+        processor.set_compound_gap(std::time::Duration::from_millis(
+            config.compound_command_gap_ms,
+        ));
 
         let mut app = Self {
             current_tab: Tab::Home,
@@ -206,7 +211,6 @@ impl TuxTalksApp {
             },
             search_results: Vec::new(),
             _searching: false,
-            tts_mute_until: None,
             last_command_time: None,
             command_audit_log: Vec::new(),
             pending_ipc_resp: None,
@@ -221,6 +225,12 @@ impl TuxTalksApp {
 
         app.scan_available_models();
 
+        // Mute capture while we speak or play effects, plus a tail for room echo
+        crate::audio::PlaybackMonitor::global().set_tail(std::time::Duration::from_millis(
+            config.playback_mute_tail_ms,
+        ));
+        crate::audio::PlaybackMonitor::global().set_mute_effects(config.mute_during_effects);
+
         // Initialize TTS in background based on config
         let init_task = Task::perform(
             crate::tts::create_engine(config.clone(), Some(sound_engine.clone())),
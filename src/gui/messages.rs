@@ -94,6 +94,8 @@ pub enum Message {
     SelectTrainingPhrase(String),
     ResetFingerprint,
     TrainingRecordingCompleted(Result<std::path::PathBuf, String>),
+    EvaluateTraining,
+    TrainingEvaluated(Result<crate::voice_fingerprint::EvaluationReport, String>),
 
     // Packs
     InstallPack(String),
@@ -22,9 +22,6 @@ pub use app::TuxTalksApp;
 pub use messages::Message;
 pub use state::Tab;
 
-// ASR Pause Signal (Static to avoid closure capture issues)
-pub static ASR_PAUSED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
-
 // Re-export speak wrapper
 pub async fn msg_speak(client: std::sync::Arc<dyn crate::tts::TtsEngine>, text: String) -> Message {
     let _ = client.speak(&text).await;
@@ -49,6 +46,7 @@ impl TuxTalksApp {
             }
             Message::SpeechdConnected(engine) => {
                 info!("🔊 TTS Connected");
+                self.processor.set_tts(engine.clone());
                 self.tts = Some(engine);
                 self.status = "Ready".to_string();
                 // NOTE (Jony): Do NOT announce here. Wait for user to click Start Listening.
@@ -85,10 +83,22 @@ impl TuxTalksApp {
                     self.processor = crate::commands::CommandProcessor::new()
                         .expect("Failed to create CommandProcessor");
                     self.processor.set_sound_engine(self.sound_engine.clone());
+                    self.processor
+                        .set_player_manager(self.player_manager.clone());
+                    if let Some(tts) = &self.tts {
+                        self.processor.set_tts(tts.clone());
+                    }
+                    self.processor
+                        .set_compound_gap(std::time::Duration::from_millis(
+                            self.config.compound_command_gap_ms,
+                        ));
                     for cmd in commands {
                         self.processor.add_command(cmd);
                     }
                     self.processor.set_action_map(profile.resolve_actions());
+                    self.processor.set_toggles(profile.tag_states.clone());
+                    self.processor
+                        .set_active_profile(Some(profile.name.clone()));
 
                     // Wire up Ollama if enabled
                     if self.config.ollama_enabled {
@@ -100,9 +110,6 @@ impl TuxTalksApp {
 
                 // Announce "I am ready" when listening starts
                 if let Some(ref tts) = self.tts {
-                    ASR_PAUSED.store(true, std::sync::atomic::Ordering::SeqCst);
-                    self.tts_mute_until =
-                        Some(std::time::Instant::now() + std::time::Duration::from_millis(2500));
                     return Task::perform(msg_speak(tts.clone(), "I am ready".to_string()), |m| m);
                 }
             }
@@ -120,22 +127,6 @@ impl TuxTalksApp {
                 .iter()
                 .any(|p| lower.contains(p));
 
-                // Only block if ASR is paused (Stamos Hardlock)
-                if ASR_PAUSED.load(std::sync::atomic::Ordering::SeqCst) {
-                    debug!("🔇 Dropping transcription as ASR is paused: '{}'", text);
-                    return Task::none();
-                }
-
-                if let Some(mute_until) = self.tts_mute_until {
-                    if std::time::Instant::now() < mute_until {
-                        info!(
-                            "🔇 Ignoring transcription during TTS mute window: '{}'",
-                            text
-                        );
-                        return Task::none();
-                    }
-                }
-
                 // Rate Limiting (Red Team Audit: Alex Stamos)
                 let now = std::time::Instant::now();
                 if let Some(last) = self.last_command_time {
@@ -165,9 +156,6 @@ impl TuxTalksApp {
                                 info!("📌 Voice selection: {} (idx {})", item.display, idx);
                                 self.selection_timeout = None;
 
-                                // Reset asr_paused to allow TTS feedback
-                                ASR_PAUSED.store(true, std::sync::atomic::Ordering::SeqCst);
-
                                 // If it was an IPC request, we need to send the response back
                                 if let Some(resp_tx) = self.pending_ipc_resp.take() {
                                     let _ = resp_tx.send((idx as i32, false));
@@ -186,9 +174,6 @@ impl TuxTalksApp {
                                     self.status = format!("Playing: {}", item.display);
                                     let response = format!("Playing {}", item.display);
 
-                                    // Reset asr_paused to allow TTS feedback
-                                    ASR_PAUSED.store(true, std::sync::atomic::Ordering::SeqCst);
-
                                     return Task::batch(vec![
                                         Task::perform(
                                             async move {
@@ -239,7 +224,6 @@ impl TuxTalksApp {
                                 );
 
                                 if let Some(tts) = &self.tts {
-                                    ASR_PAUSED.store(true, std::sync::atomic::Ordering::SeqCst);
                                     return Task::perform(msg_speak(tts.clone(), prompt), |m| m);
                                 }
                                 return Task::none();
@@ -253,7 +237,6 @@ impl TuxTalksApp {
                                     // Stop current playback if any (Chisholm requirement)
                                     let _ = self.sound_engine.stop();
 
-                                    ASR_PAUSED.store(true, std::sync::atomic::Ordering::SeqCst);
                                     return Task::perform(
                                         msg_speak(tts.clone(), "Selection cancelled".to_string()),
                                         |m| m,
@@ -280,7 +263,7 @@ impl TuxTalksApp {
                 }
 
                 // Check if we are in an active confirmation phase (Stamos requirement)
-                if let Some((_name, _cmd)) = self.pending_confirmation.clone() {
+                if self.pending_confirmation.is_some() {
                     let mut is_timed_out = false;
                     if let Some(timeout) = self.confirmation_timeout {
                         if std::time::Instant::now() > timeout {
@@ -296,11 +279,14 @@ impl TuxTalksApp {
                             || text == "proceed"
                         {
                             return self.update(Message::ConfirmCommand);
-                        } else if text == "cancel"
-                            || text == "no"
-                            || text == "stop"
-                            || text == "abort"
-                        {
+                        } else if crate::commands::ABORT_PHRASES.contains(&text.as_str()) {
+                            // Cancels the prompt and, as ever, stops running macros
+                            let aborted = self.processor.in_flight().abort_all();
+                            if !aborted.is_empty() {
+                                info!("🛑 {}", aborted);
+                            }
+                            return self.update(Message::CancelConfirmation);
+                        } else if text == "cancel" || text == "no" || text == "stop" {
                             return self.update(Message::CancelConfirmation);
                         }
                         // If we didn't match confirm/cancel, we just fall through but maybe log?
@@ -340,8 +326,11 @@ impl TuxTalksApp {
                     "six", "seven", "eight", "nine", "ten", "0", "1", "2", "3", "4", "5", "6", "7",
                     "8", "9",
                 ];
-                let is_bypass =
-                    is_selection_active && bypass_phrases.iter().any(|p| lower.contains(p));
+                // A macro waiting for a phrase hears it without the wake word,
+                // however long after command mode closed
+                let is_bypass = (is_selection_active
+                    && bypass_phrases.iter().any(|p| lower.contains(p)))
+                    || self.processor.awaiting_speech();
 
                 if !self.config.wake_word.is_empty() && !is_bypass {
                     let wake_word = self.config.wake_word.to_lowercase();
@@ -357,11 +346,6 @@ impl TuxTalksApp {
                             );
 
                             if let Some(ref tts) = self.tts {
-                                ASR_PAUSED.store(true, std::sync::atomic::Ordering::SeqCst);
-                                self.tts_mute_until = Some(
-                                    std::time::Instant::now()
-                                        + std::time::Duration::from_millis(1500),
-                                );
                                 return Task::perform(
                                     msg_speak(tts.clone(), "Yes?".to_string()),
                                     |m| m,
@@ -405,11 +389,6 @@ impl TuxTalksApp {
                             self.status = "Listening for command...".to_string();
 
                             if let Some(ref tts) = self.tts {
-                                ASR_PAUSED.store(true, std::sync::atomic::Ordering::SeqCst);
-                                self.tts_mute_until = Some(
-                                    std::time::Instant::now()
-                                        + std::time::Duration::from_millis(1500),
-                                );
                                 return Task::perform(
                                     msg_speak(tts.clone(), "Yes?".to_string()),
                                     |m| m,
@@ -538,7 +517,6 @@ impl TuxTalksApp {
 
                         // TTS Feedback
                         if let Some(tts) = &self.tts {
-                            ASR_PAUSED.store(true, std::sync::atomic::Ordering::SeqCst);
                             let text_to_speak = format!("Executing {}", cmd_name);
                             return Task::perform(msg_speak(tts.clone(), text_to_speak), |m| m);
                         }
@@ -568,7 +546,6 @@ impl TuxTalksApp {
 
                         // TTS Feedback
                         if let Some(tts) = &self.tts {
-                            ASR_PAUSED.store(true, std::sync::atomic::Ordering::SeqCst);
                             let text_to_speak = format!("Executing {}", action);
                             return Task::perform(msg_speak(tts.clone(), text_to_speak), |m| m);
                         }
@@ -588,14 +565,17 @@ impl TuxTalksApp {
 
                         if let Some(tts) = &self.tts {
                             let prompt = self.selection_handler.speak_options_text();
-                            ASR_PAUSED.store(true, std::sync::atomic::Ordering::SeqCst);
                             return Task::perform(msg_speak(tts.clone(), prompt), |m| m);
                         }
                         return Task::none();
                     }
-                    ProcessResult::ConfirmationRequired { action, command } => {
+                    ProcessResult::ConfirmationRequired {
+                        action,
+                        command,
+                        modifiers,
+                    } => {
                         info!("⚠️ Confirmation required for: {}", action);
-                        self.pending_confirmation = Some((action.clone(), command));
+                        self.pending_confirmation = Some((action.clone(), command, modifiers));
                         self.confirmation_id += 1;
                         self.confirmation_timeout =
                             Some(std::time::Instant::now() + std::time::Duration::from_secs(30));
@@ -603,7 +583,41 @@ impl TuxTalksApp {
 
                         if let Some(tts) = &self.tts {
                             let prompt = format!("Dangerous command detected: {}. Say confirm to proceed or cancel to abort.", action);
-                            ASR_PAUSED.store(true, std::sync::atomic::Ordering::SeqCst);
+                            return Task::perform(msg_speak(tts.clone(), prompt), |m| m);
+                        }
+                        return Task::none();
+                    }
+                    ProcessResult::AlreadySet { action, on } => {
+                        let message =
+                            format!("{} is already {}", action, crate::commands::on_off(on));
+                        info!("✋ {}", message);
+                        self.status = message.clone();
+
+                        if let Some(tts) = &self.tts {
+                            return Task::perform(msg_speak(tts.clone(), message), |m| m);
+                        }
+                    }
+                    ProcessResult::Declined { reason } => {
+                        info!("🤷 {}", reason);
+                        self.status = reason.clone();
+
+                        if let Some(tts) = &self.tts {
+                            return Task::perform(msg_speak(tts.clone(), reason), |m| m);
+                        }
+                    }
+                    ProcessResult::CommandChoiceRequired {
+                        query, candidates, ..
+                    } => {
+                        // Nothing runs; the user repeats the command they meant
+                        let names: Vec<String> = candidates
+                            .iter()
+                            .map(|c| c.command.name().replace('_', " "))
+                            .collect();
+                        info!("🤔 '{}' matches several commands: {:?}", query, names);
+                        self.status = format!("Ambiguous: {}", names.join(" / "));
+
+                        if let Some(tts) = &self.tts {
+                            let prompt = format!("Did you mean {}?", names.join(" or "));
                             return Task::perform(msg_speak(tts.clone(), prompt), |m| m);
                         }
                         return Task::none();
@@ -611,7 +625,6 @@ impl TuxTalksApp {
                     ProcessResult::NotFound => {
                         debug!("No command matched");
                         if let Some(tts) = &self.tts {
-                            ASR_PAUSED.store(true, std::sync::atomic::Ordering::SeqCst);
                             return Task::perform(
                                 msg_speak(tts.clone(), "I didn't catch that command.".to_string()),
                                 |m| m,
@@ -655,7 +668,6 @@ impl TuxTalksApp {
                 if let Some(tts) = &self.tts {
                     let prompt =
                         format!("{}. {}", title, self.selection_handler.speak_options_text());
-                    ASR_PAUSED.store(true, std::sync::atomic::Ordering::SeqCst);
                     return Task::batch(vec![
                         Task::perform(msg_speak(tts.clone(), prompt), |m| m),
                         Task::perform(selection_timeout_task(sid), |m| m),
@@ -705,8 +717,15 @@ impl TuxTalksApp {
                         .clone()
                         .unwrap_or_else(|| "test".to_string());
                     let vf = self.voice_fingerprint.clone();
+                    let device =
+                        crate::audio::DeviceSelector::from_args(None, &self.config.audio_device);
+                    let dsp = self.config.dsp.clone();
                     return Task::perform(
-                        async move { vf.record_sample(&phrase).await.map_err(|e| e.to_string()) },
+                        async move {
+                            vf.record_sample(&phrase, device, dsp)
+                                .await
+                                .map_err(|e| e.to_string())
+                        },
                         Message::TrainingRecordingCompleted,
                     );
                 } else {
@@ -740,6 +759,36 @@ impl TuxTalksApp {
             Message::NewVocabularyInputChanged(val) => {
                 self.new_vocab_input = val;
             }
+            Message::EvaluateTraining => {
+                self.training_state.is_evaluating = true;
+                self.status = "Evaluating training samples...".to_string();
+                let vf = self.voice_fingerprint.clone();
+                let config = self.config.clone();
+                return Task::perform(
+                    async move {
+                        let mut engine = crate::asr::create_engine(config)?;
+                        vf.evaluate(engine.as_mut()).await
+                    },
+                    |res| Message::TrainingEvaluated(res.map_err(|e| e.to_string())),
+                );
+            }
+            Message::TrainingEvaluated(res) => {
+                self.training_state.is_evaluating = false;
+                match res {
+                    Ok(report) => {
+                        self.status = format!(
+                            "Recognition accuracy: {:.0}% ({} corrections learned)",
+                            report.overall_accuracy() * 100.0,
+                            report.corrections_added
+                        );
+                        self.training_state.report = Some(report);
+                    }
+                    Err(e) => {
+                        warn!("Training evaluation failed: {}", e);
+                        self.status = format!("Evaluation Error: {}", e);
+                    }
+                }
+            }
             Message::ResetFingerprint => {
                 self.voice_fingerprint.clear_patterns();
                 self.status = "Voice fingerprint reset".to_string();
@@ -762,44 +811,28 @@ impl TuxTalksApp {
                 return Task::none();
             }
             Message::ConfirmCommand => {
-                if let Some((name, command)) = self.pending_confirmation.take() {
+                if let Some((name, command, modifiers)) = self.pending_confirmation.take() {
                     info!("✅ Executing confirmed command: {}", name);
                     self.status = format!("Executed: {}", name);
                     self.confirmation_timeout = None;
 
-                    let shared_kb = self.processor.keyboard.clone();
-                    let action_map = self.processor.get_action_map();
-                    let engine = self.processor.sound_engine.clone();
-                    let lal = self.processor.lal_manager.clone();
-                    let custom_audio = if self.config.custom_audio_dir.is_empty() {
-                        None
-                    } else {
-                        Some(std::path::PathBuf::from(&self.config.custom_audio_dir))
-                    };
-
+                    // Through the processor, so toggle state and history are kept
+                    let processor = self.processor.clone();
                     return Task::perform(
-                        crate::commands::CommandProcessor::execute_command_async(
-                            shared_kb,
-                            action_map,
-                            engine,
-                            lal,
-                            custom_audio,
-                            command,
-                        ),
+                        async move { processor.run_confirmed(command, modifiers).await },
                         |_| Message::None,
                     );
                 }
                 return Task::none();
             }
             Message::CancelConfirmation => {
-                if let Some((name, _)) = self.pending_confirmation.take() {
+                if let Some((name, ..)) = self.pending_confirmation.take() {
                     info!("❌ Command cancelled: {}", name);
                     self.status = format!("Cancelled: {}", name);
                     self.confirmation_timeout = None;
                     self.confirmation_id += 1; // Invalidate any flying timeout tasks
 
                     if let Some(tts) = &self.tts {
-                        ASR_PAUSED.store(true, std::sync::atomic::Ordering::SeqCst);
                         return Task::perform(
                             msg_speak(tts.clone(), "Command cancelled".into()),
                             |m| m,
@@ -810,7 +843,7 @@ impl TuxTalksApp {
             }
             Message::ConfirmationTimeout(id) => {
                 if self.pending_confirmation.is_some() && id == self.confirmation_id {
-                    if let Some((name, _)) = self.pending_confirmation.take() {
+                    if let Some((name, ..)) = self.pending_confirmation.take() {
                         info!("⌛ Confirmation timed out (ID: {}) for: {}", id, name);
                         self.status = format!("Timeout: {}", name);
                         self.confirmation_timeout = None;
@@ -822,11 +855,6 @@ impl TuxTalksApp {
                 self.training_state.current_phrase = Some(phrase);
             }
             Message::SpeechFinished => {
-                ASR_PAUSED.store(false, std::sync::atomic::Ordering::SeqCst);
-                // 🛡️ ASR Squelch: Ignore acoustic tail-end feedback
-                self.tts_mute_until =
-                    Some(std::time::Instant::now() + std::time::Duration::from_millis(1500));
-
                 // 🛡️ Selection Persistence: Reset timer ID after system finishes speaking
                 if self.selection_handler.is_active() {
                     self.selection_id += 1;
@@ -877,16 +905,19 @@ impl TuxTalksApp {
                 iced::stream::channel(10, |mut output| async move {
                     let config = crate::config::Config::load().unwrap_or_default();
 
-                    let mut audio_rx = match audio::start_capture(None) {
-                        Ok(rx) => rx,
-                        Err(e) => {
-                            warn!("Failed to start audio capture: {}", e);
-                            let _ = output
-                                .send(Message::Transcription("Error: No Audio".into()))
-                                .await;
-                            return;
-                        }
-                    };
+                    let device = audio::DeviceSelector::from_args(None, &config.audio_device);
+                    // Capture stops when the subscription ends and drops the handle
+                    let (_capture, mut audio_rx) =
+                        match audio::AudioCapture::start(device, config.dsp.clone()) {
+                            Ok(started) => started,
+                            Err(e) => {
+                                warn!("Failed to start audio capture: {}", e);
+                                let _ = output
+                                    .send(Message::Transcription("Error: No Audio".into()))
+                                    .await;
+                                return;
+                            }
+                        };
 
                     let mut asr = match crate::asr::create_engine(config) {
                         Ok(asr) => asr,
@@ -899,33 +930,19 @@ impl TuxTalksApp {
                         }
                     };
 
-                    loop {
-                        if ASR_PAUSED.load(std::sync::atomic::Ordering::SeqCst) {
-                            asr.pause();
-                            // 🛡️ Explicit Squelch: Clear any audio buffer while talking
-                            while audio_rx.try_recv().is_ok() {}
-                            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
-                            continue;
-                        } else if asr.is_paused() {
-                            asr.resume();
-                        }
-
-                        if let Some(samples) = audio_rx.recv().await {
-                            match asr.process(&samples) {
-                                Ok(Some(result)) => {
-                                    let _ = output.send(Message::Transcription(result.text)).await;
-                                }
-                                Ok(None) => {}
-                                Err(e) => {
-                                    warn!("ASR error: {}", e);
-                                    let _ = output
-                                        .send(Message::Transcription(format!("ASR Error: {}", e)))
-                                        .await;
-                                    break;
-                                }
+                    while let Some(samples) = audio_rx.recv().await {
+                        match asr.process(&samples) {
+                            Ok(Some(crate::asr::AsrEvent::Final(result))) => {
+                                let _ = output.send(Message::Transcription(result.text)).await;
+                            }
+                            Ok(Some(crate::asr::AsrEvent::Partial(_))) | Ok(None) => {}
+                            Err(e) => {
+                                warn!("ASR error: {}", e);
+                                let _ = output
+                                    .send(Message::Transcription(format!("ASR Error: {}", e)))
+                                    .await;
+                                break;
                             }
-                        } else {
-                            break;
                         }
                     }
                 })
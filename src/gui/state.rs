@@ -58,5 +58,7 @@ impl Default for SpeechState {
 pub struct TrainingState {
     pub is_recording: bool,
     pub current_phrase: Option<String>,
-    pub progress: f32,
+    pub is_evaluating: bool,
+    /// Result of the last evaluation run
+    pub report: Option<crate::voice_fingerprint::EvaluationReport>,
 }
@@ -22,6 +22,7 @@ pub fn view(app: &TuxTalksApp) -> Element<'_, Message> {
         "Text-to-Speech (TTS)",
         vec![
             "piper".to_string(),
+            "wyoming".to_string(),
             "speechd_ng".to_string(),
             "system".to_string(),
         ],
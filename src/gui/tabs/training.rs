@@ -75,12 +75,36 @@ pub fn view(app: &TuxTalksApp) -> Element<'_, Message> {
         );
     }
 
-    // Progress bar (mockup)
-    if app.training_state.progress > 0.0 {
-        content = content.push(text(format!(
-            "Training progress: {:.0}%",
-            app.training_state.progress
-        )));
+    // Evaluation: replay the recorded clips through the ASR engine
+    let evaluate_button = if app.training_state.is_evaluating {
+        button("Evaluating...").style(button::secondary)
+    } else {
+        button("Evaluate Recordings")
+            .style(button::primary)
+            .on_press(Message::EvaluateTraining)
+    };
+    content = content.push(evaluate_button);
+
+    if let Some(report) = &app.training_state.report {
+        let mut results = Column::new().spacing(5);
+        results = results.push(
+            text(format!(
+                "Overall accuracy: {:.0}% ({} corrections learned)",
+                report.overall_accuracy() * 100.0,
+                report.corrections_added
+            ))
+            .size(18),
+        );
+        for phrase in &report.phrases {
+            results = results.push(text(format!(
+                "\"{}\": {}/{} ({:.0}%)",
+                phrase.phrase,
+                phrase.correct,
+                phrase.total,
+                phrase.accuracy() * 100.0
+            )));
+        }
+        content = content.push(container(results).padding(15).style(container::rounded_box));
     }
 
     content.into()
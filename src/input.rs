@@ -9,7 +9,7 @@ use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::{Duration, Instant};
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 /// Virtual keyboard for simulating key presses
 pub struct VirtualKeyboard {
@@ -101,6 +101,18 @@ impl VirtualKeyboard {
             // Gaming common
             Key::KEY_INSERT,
             Key::KEY_PAUSE,
+            // Punctuation (typed text)
+            Key::KEY_MINUS,
+            Key::KEY_EQUAL,
+            Key::KEY_LEFTBRACE,
+            Key::KEY_RIGHTBRACE,
+            Key::KEY_BACKSLASH,
+            Key::KEY_SEMICOLON,
+            Key::KEY_APOSTROPHE,
+            Key::KEY_GRAVE,
+            Key::KEY_COMMA,
+            Key::KEY_DOT,
+            Key::KEY_SLASH,
         ] {
             keys.insert(key);
         }
@@ -145,6 +157,38 @@ impl VirtualKeyboard {
         Ok(())
     }
 
+    /// Hold a key combination down (modifiers first) until `release_combo`
+    pub fn press_combo(&mut self, modifiers: &[Key], key: Key) -> Result<()> {
+        for modifier in modifiers {
+            self.press_key(*modifier)?;
+            thread::sleep(Duration::from_millis(5));
+        }
+        self.press_key(key)
+    }
+
+    /// Release a combination held with `press_combo` (modifiers last)
+    pub fn release_combo(&mut self, modifiers: &[Key], key: Key) -> Result<()> {
+        self.release_key(key)?;
+        for modifier in modifiers.iter().rev() {
+            thread::sleep(Duration::from_millis(5));
+            self.release_key(*modifier)?;
+        }
+        Ok(())
+    }
+
+    /// Type literal text (US layout), skipping characters with no key
+    pub fn type_text(&mut self, text: &str) -> Result<()> {
+        debug!("Typing {} characters", text.chars().count());
+        for c in text.chars() {
+            match char_key(c) {
+                Some((key, true)) => self.key_combo(&[Key::KEY_LEFTSHIFT], key)?,
+                Some((key, false)) => self.tap_key(key)?,
+                None => warn!("⚠️ Can't type '{}', skipping", c),
+            }
+        }
+        Ok(())
+    }
+
     /// Type a key combination (e.g., Ctrl+C)
     pub fn key_combo(&mut self, modifiers: &[Key], key: Key) -> Result<()> {
         for modifier in modifiers {
@@ -293,6 +337,45 @@ impl InputListener {
     }
 }
 
+/// Key for a character on a US layout, and whether Shift is needed
+pub fn char_key(c: char) -> Option<(Key, bool)> {
+    if c.is_ascii_alphanumeric() {
+        return parse_key(&c.to_string()).map(|key| (key, c.is_ascii_uppercase()));
+    }
+    // Shifted digits, indexed by their digit
+    if let Some(digit) = ")!@#$%^&*(".find(c) {
+        return parse_key(&digit.to_string()).map(|key| (key, true));
+    }
+    Some(match c {
+        ' ' => (Key::KEY_SPACE, false),
+        '\n' => (Key::KEY_ENTER, false),
+        '\t' => (Key::KEY_TAB, false),
+        '-' => (Key::KEY_MINUS, false),
+        '_' => (Key::KEY_MINUS, true),
+        '=' => (Key::KEY_EQUAL, false),
+        '+' => (Key::KEY_EQUAL, true),
+        '[' => (Key::KEY_LEFTBRACE, false),
+        '{' => (Key::KEY_LEFTBRACE, true),
+        ']' => (Key::KEY_RIGHTBRACE, false),
+        '}' => (Key::KEY_RIGHTBRACE, true),
+        '\\' => (Key::KEY_BACKSLASH, false),
+        '|' => (Key::KEY_BACKSLASH, true),
+        ';' => (Key::KEY_SEMICOLON, false),
+        ':' => (Key::KEY_SEMICOLON, true),
+        '\'' => (Key::KEY_APOSTROPHE, false),
+        '"' => (Key::KEY_APOSTROPHE, true),
+        '`' => (Key::KEY_GRAVE, false),
+        '~' => (Key::KEY_GRAVE, true),
+        ',' => (Key::KEY_COMMA, false),
+        '<' => (Key::KEY_COMMA, true),
+        '.' => (Key::KEY_DOT, false),
+        '>' => (Key::KEY_DOT, true),
+        '/' => (Key::KEY_SLASH, false),
+        '?' => (Key::KEY_SLASH, true),
+        _ => return None,
+    })
+}
+
 /// Parse a key name string to evdev Key
 pub fn parse_key(name: &str) -> Option<Key> {
     let name_lower = name.to_lowercase();
@@ -410,3 +493,19 @@ pub fn parse_key(name: &str) -> Option<Key> {
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_char_key() {
+        assert_eq!(char_key('a'), Some((Key::KEY_A, false)));
+        assert_eq!(char_key('D'), Some((Key::KEY_D, true)));
+        assert_eq!(char_key('7'), Some((Key::KEY_7, false)));
+        assert_eq!(char_key('!'), Some((Key::KEY_1, true)));
+        assert_eq!(char_key('?'), Some((Key::KEY_SLASH, true)));
+        assert_eq!(char_key(' '), Some((Key::KEY_SPACE, false)));
+        assert_eq!(char_key('é'), None);
+    }
+}
@@ -9,7 +9,7 @@ use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::Duration;
 use tracing::{debug, warn};
 
-use super::{socket_path, IpcRequest, IpcResponse};
+use super::{socket_path, DaemonStatus, IpcRequest, IpcResponse};
 
 /// IPC Client for launcher
 pub struct IpcClient;
@@ -87,7 +87,7 @@ impl IpcClient {
     }
 
     /// Request daemon status
-    pub fn get_status() -> Result<Option<(bool, bool, Option<String>)>> {
+    pub fn get_status() -> Result<Option<DaemonStatus>> {
         let path = socket_path();
 
         let mut stream = UnixStream::connect(&path)?;
@@ -110,12 +110,20 @@ impl IpcClient {
                 listening,
                 paused,
                 active_profile,
+                asr_engine,
+                wyoming_server,
             } => {
                 if resp_seq != seq_id {
                     warn!("⚠️ IPC sequence ID mismatch");
                     return Ok(None);
                 }
-                Ok(Some((listening, paused, active_profile)))
+                Ok(Some(DaemonStatus {
+                    listening,
+                    paused,
+                    active_profile,
+                    asr_engine,
+                    wyoming_server,
+                }))
             }
             _ => Ok(None),
         }
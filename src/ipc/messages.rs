@@ -4,6 +4,9 @@
 
 use serde::{Deserialize, Serialize};
 
+/// Control action that stops running macros and releases held keys
+pub const CONTROL_ABORT: &str = "abort";
+
 /// Request types sent from client to server
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
@@ -21,7 +24,7 @@ pub enum IpcRequest {
     #[serde(rename = "status_request")]
     StatusRequest { seq_id: u64 },
 
-    /// Control command (pause, resume, stop)
+    /// Control command (pause, resume, stop, abort)
     #[serde(rename = "control")]
     Control { seq_id: u64, action: String },
 
@@ -51,6 +54,12 @@ pub enum IpcResponse {
         listening: bool,
         paused: bool,
         active_profile: Option<String>,
+        /// ASR engine currently receiving audio (absent from older daemons)
+        #[serde(default)]
+        asr_engine: Option<String>,
+        /// Wyoming server state: "running", "external", "restarting", ...
+        #[serde(default)]
+        wyoming_server: Option<String>,
     },
 
     /// Acknowledgment
@@ -62,6 +71,16 @@ pub enum IpcResponse {
     },
 }
 
+/// Daemon state reported in status responses
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DaemonStatus {
+    pub listening: bool,
+    pub paused: bool,
+    pub active_profile: Option<String>,
+    pub asr_engine: Option<String>,
+    pub wyoming_server: Option<String>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -91,4 +110,13 @@ mod tests {
         assert!(json.contains("selection_response"));
         assert!(json.contains("\"index\":2"));
     }
+
+    #[test]
+    fn test_status_response_without_asr_engine() {
+        let json = r#"{"type":"status_response","seq_id":3,"listening":true,"paused":false,"active_profile":null}"#;
+        match serde_json::from_str::<IpcResponse>(json).unwrap() {
+            IpcResponse::StatusResponse { asr_engine, .. } => assert_eq!(asr_engine, None),
+            other => panic!("unexpected response {:?}", other),
+        }
+    }
 }
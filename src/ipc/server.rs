@@ -21,16 +21,22 @@ lazy_static! {
     static ref LAST_IPC_TIME: Mutex<Instant> = Mutex::new(Instant::now() - Duration::from_secs(1));
 }
 
-use super::{socket_path, IpcRequest, IpcResponse};
+use super::{socket_path, DaemonStatus, IpcRequest, IpcResponse};
 
 /// Callback type for handling selection requests (seq_id, title, items, page) -> (index, cancelled)
 pub type SelectionCallback =
     Box<dyn Fn(u64, String, Vec<String>, usize) -> (i32, bool) + Send + Sync>;
 
+/// Handler for control actions; returns the reply message, or `None` for
+/// actions it doesn't handle
+pub type ControlHandler = dyn Fn(&str) -> Option<String> + Send + Sync;
+
 /// IPC Server for daemon
 pub struct IpcServer {
     running: Arc<AtomicBool>,
     thread_handle: Option<thread::JoinHandle<()>>,
+    status: Arc<Mutex<DaemonStatus>>,
+    control: Option<Arc<ControlHandler>>,
 }
 
 impl IpcServer {
@@ -39,9 +45,27 @@ impl IpcServer {
         Self {
             running: Arc::new(AtomicBool::new(false)),
             thread_handle: None,
+            status: Arc::new(Mutex::new(DaemonStatus {
+                listening: true,
+                ..Default::default()
+            })),
+            control: None,
         }
     }
 
+    /// Shared status answered to status requests; the owner keeps it current
+    pub fn status(&self) -> Arc<Mutex<DaemonStatus>> {
+        self.status.clone()
+    }
+
+    /// Handle control actions (call before `start`)
+    pub fn set_control_handler<F>(&mut self, handler: F)
+    where
+        F: Fn(&str) -> Option<String> + Send + Sync + 'static,
+    {
+        self.control = Some(Arc::new(handler));
+    }
+
     /// Start the server with a selection callback
     pub fn start<F>(&mut self, callback: F) -> Result<()>
     where
@@ -74,6 +98,8 @@ impl IpcServer {
         self.running.store(true, Ordering::SeqCst);
         let running = self.running.clone();
         let callback = Arc::new(callback);
+        let status = self.status.clone();
+        let control = self.control.clone();
 
         info!("🔌 IPC server listening on {:?}", path);
 
@@ -82,8 +108,10 @@ impl IpcServer {
                 match listener.accept() {
                     Ok((stream, _)) => {
                         let cb = callback.clone();
+                        let status = status.clone();
+                        let control = control.clone();
                         thread::spawn(move || {
-                            if let Err(e) = handle_client(stream, cb) {
+                            if let Err(e) = handle_client(stream, cb, &status, control.as_deref()) {
                                 warn!("IPC client error: {}", e);
                             }
                         });
@@ -133,7 +161,12 @@ impl Drop for IpcServer {
 }
 
 /// Handle a single client connection
-fn handle_client<F>(mut stream: UnixStream, callback: Arc<F>) -> Result<()>
+fn handle_client<F>(
+    mut stream: UnixStream,
+    callback: Arc<F>,
+    status: &Mutex<DaemonStatus>,
+    control: Option<&ControlHandler>,
+) -> Result<()>
 where
     F: Fn(u64, String, Vec<String>, usize) -> (i32, bool),
 {
@@ -177,21 +210,29 @@ where
                 child_index: None,
             }
         }
-        IpcRequest::StatusRequest { seq_id } => IpcResponse::StatusResponse {
-            seq_id,
-            listening: true,
-            paused: false,
-            active_profile: None,
-        },
+        IpcRequest::StatusRequest { seq_id } => {
+            let status = status.lock().expect("IPC status mutex poisoned").clone();
+            IpcResponse::StatusResponse {
+                seq_id,
+                listening: status.listening,
+                paused: status.paused,
+                active_profile: status.active_profile,
+                asr_engine: status.asr_engine,
+                wyoming_server: status.wyoming_server,
+            }
+        }
         IpcRequest::Control { seq_id, action } => {
             info!("📡 IPC control: {}", action);
             if let Err(e) = crate::audit::log(&format!("IPC Control Executed: {}", action)) {
                 warn!("Failed to write audit log: {}", e);
             }
+            let message = control
+                .and_then(|handle| handle(&action))
+                .unwrap_or_else(|| format!("Executed: {}", action));
             IpcResponse::Ack {
                 seq_id,
                 success: true,
-                message: Some(format!("Executed: {}", action)),
+                message: Some(message),
             }
         }
         IpcRequest::ReloadConfig { seq_id } => {
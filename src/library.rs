@@ -236,6 +236,25 @@ impl LocalLibrary {
         Ok(results)
     }
 
+    /// Artist, album, genre and playlist names, for recognizer grammars
+    pub fn vocabulary(&self, limit: u32) -> Result<Vec<String>> {
+        let conn = Connection::open(&self.db_path)?;
+        let mut stmt = conn.prepare(
+            "SELECT name FROM (
+                SELECT artist AS name FROM tracks
+                UNION SELECT album FROM tracks
+                UNION SELECT genre FROM tracks
+                UNION SELECT name FROM playlists
+             ) WHERE name IS NOT NULL AND name != '' LIMIT ?",
+        )?;
+        let rows = stmt.query_map([limit], |row| row.get::<_, String>(0))?;
+        let mut names = Vec::new();
+        for row in rows {
+            names.push(row?);
+        }
+        Ok(names)
+    }
+
     /// Check if an artist exists (Wendy Chisholm requirement)
     pub fn artist_exists(&self, artist: &str) -> bool {
         if let Ok(conn) = Connection::open(&self.db_path) {
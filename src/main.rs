@@ -3,13 +3,14 @@
 //! A Rust implementation of the TuxTalks voice assistant.
 
 use anyhow::Result;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tracing::{debug, info, warn, Level};
 use tracing_subscriber::FmtSubscriber;
 use tuxtalks::asr;
 use tuxtalks::commands::CommandProcessor;
+use tuxtalks::core::command_modifiers::CommandModifiers;
 use tuxtalks::input::{parse_key, InputListener, PttMode};
 use tuxtalks::{audio, games, tts};
 
@@ -24,6 +25,10 @@ struct Args {
     #[arg(short, long)]
     device: Option<usize>,
 
+    /// Audio input device name (case-insensitive substring, overrides config)
+    #[arg(long, value_name = "NAME", conflicts_with = "device")]
+    device_name: Option<String>,
+
     /// Use speechd-ng for TTS feedback
     #[arg(long)]
     speechd: bool,
@@ -31,6 +36,46 @@ struct Args {
     /// Wake word (overrides config)
     #[arg(short, long)]
     wake_word: Option<String>,
+
+    /// Replay a WAV/FLAC file (or a directory of them) instead of the microphone
+    #[arg(long, value_name = "PATH")]
+    input_file: Option<std::path::PathBuf>,
+
+    /// Replay --input-file as fast as possible instead of in real time
+    #[arg(long, requires = "input_file")]
+    fast: bool,
+
+    #[command(subcommand)]
+    command: Option<Commands>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Commands {
+    /// Measure ASR accuracy and latency on a corpus of transcribed recordings
+    BenchAsr(BenchArgs),
+}
+
+#[derive(clap::Args, Debug)]
+struct BenchArgs {
+    /// Directory of WAV/FLAC files, each with a sidecar .txt transcript
+    corpus: std::path::PathBuf,
+
+    /// Engine to test: vosk, vosk=MODEL_PATH, wyoming or wyoming=HOST:PORT
+    /// (repeatable; defaults to the configured engine)
+    #[arg(short, long = "engine", value_name = "SPEC")]
+    engines: Vec<String>,
+
+    /// Game profile whose commands and grammar to use (demo bindings otherwise)
+    #[arg(short, long)]
+    profile: Option<String>,
+
+    /// Recognize with an open vocabulary instead of the command grammar
+    #[arg(long)]
+    no_grammar: bool,
+
+    /// Write the full report as JSON to FILE ("-" for stdout)
+    #[arg(long, value_name = "FILE")]
+    json: Option<std::path::PathBuf>,
 }
 
 #[derive(Debug, PartialEq)]
@@ -47,10 +92,185 @@ enum AssistantState {
     ConfirmationMode {
         started_at: Instant,
         action: String,
-        command: tuxtalks::commands::Command,
+        command: tuxtalks::commands::CommandMatch,
+        modifiers: CommandModifiers,
+    },
+    CommandChoiceMode {
+        started_at: Instant,
+        query: String,
+        candidates: Vec<tuxtalks::commands::CommandMatch>,
+        modifiers: CommandModifiers,
     },
 }
 
+/// Run a confirmed or chosen command like any other match, so toggle state
+/// and history stay up to date
+async fn run_chosen(
+    processor: &CommandProcessor,
+    command: tuxtalks::commands::CommandMatch,
+    modifiers: CommandModifiers,
+) {
+    match processor.run_confirmed(command, modifiers).await {
+        tuxtalks::commands::ProcessResult::Success(name) => {
+            info!("✅ Command Executed: {}", name);
+        }
+        tuxtalks::commands::ProcessResult::AlreadySet { action, on } => {
+            info!(
+                "✋ {} is already {}",
+                action,
+                tuxtalks::commands::on_off(on)
+            );
+        }
+        other => warn!("❓ Chosen command did not run: {:?}", other),
+    }
+}
+
+/// Which of `count` numbered options was spoken ("two", "second", "2")
+fn spoken_choice(text: &str, count: usize) -> Option<usize> {
+    const CHOICES: [[&str; 3]; 3] = [
+        ["one", "first", "1"],
+        ["two", "second", "2"],
+        ["three", "third", "3"],
+    ];
+    CHOICES
+        .iter()
+        .take(count)
+        .position(|words| text.split_whitespace().any(|w| words.contains(&w)))
+}
+
+/// Spoken question for tied commands: "Did you mean 1: Gear, or 2: Gear Up?"
+fn command_choice_prompt(candidates: &[tuxtalks::commands::CommandMatch]) -> String {
+    let options: Vec<String> = candidates
+        .iter()
+        .take(3)
+        .enumerate()
+        .map(|(i, c)| format!("{}: {}", i + 1, c.command.name().replace('_', " ")))
+        .collect();
+    format!("Did you mean {}?", options.join(", or "))
+}
+
+/// How often to check which game is running
+const PROFILE_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// How long a profile picked by wake word overrides auto-detection
+const PROFILE_PIN_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+
+/// Audio of the current utterance kept for the full recognizer, so a command
+/// spoken in the same breath as the wake word isn't cut off
+const WAKE_PREROLL_SAMPLES: usize = 16000 * 3;
+
+/// Most library names added to the ASR grammar, to keep recognizer rebuilds quick
+const LIBRARY_VOCABULARY_LIMIT: u32 = 5000;
+
+/// The command part of a transcript that may still carry the wake phrase
+/// ("mango deploy gear" once the spotter has handed its audio over)
+fn after_wake_phrase<'a>(text: &'a str, phrases: &[String]) -> &'a str {
+    phrases
+        .iter()
+        .find_map(|p| text.find(p.as_str()).map(|i| text[i + p.len()..].trim()))
+        .unwrap_or(text)
+}
+
+/// Load a game profile's commands into the processor and rebuild the ASR grammar
+/// (demo bindings and the base grammar when no profile is active)
+fn apply_profile(
+    processor: &mut CommandProcessor,
+    asr: Option<&mut Box<dyn asr::AsrEngine>>,
+    config: &tuxtalks::config::Config,
+    profile: Option<&games::GameProfile>,
+    library: &[String],
+) {
+    processor.clear_commands();
+    match profile {
+        Some(profile) => {
+            let commands = profile.get_processor_commands();
+            info!(
+                "🚀 Loading {} commands from profile '{}'",
+                commands.len(),
+                profile.name
+            );
+            for cmd in commands {
+                processor.add_command(cmd);
+            }
+            processor.set_action_map(profile.resolve_actions());
+            processor.set_toggles(profile.tag_states.clone());
+            processor.set_active_profile(Some(profile.name.clone()));
+        }
+        None => {
+            processor.add_demo_bindings();
+            processor.set_action_map(Default::default());
+            processor.set_toggles(Default::default());
+            processor.set_active_profile(None);
+        }
+    }
+
+    if let Some(engine) = asr {
+        let grammar = asr::grammar::build_grammar(config, profile, library);
+        if let Err(e) = engine.set_grammar(&grammar) {
+            warn!("⚠️ Failed to rebuild ASR grammar: {}", e);
+        }
+    }
+}
+
+/// `tuxtalks bench-asr`: run the corpus through each engine and print the results
+async fn run_bench(args: BenchArgs, config: &tuxtalks::config::Config) -> Result<()> {
+    let corpus = asr::bench::load_corpus(&args.corpus)?;
+
+    let game_manager;
+    let profile = match &args.profile {
+        Some(name) => {
+            game_manager = games::GameManager::new()?;
+            let profile = game_manager
+                .profiles
+                .iter()
+                .find(|p| p.name.eq_ignore_ascii_case(name));
+            if profile.is_none() {
+                let names: Vec<&str> = game_manager
+                    .profiles
+                    .iter()
+                    .map(|p| p.name.as_str())
+                    .collect();
+                anyhow::bail!("Unknown profile '{}' (have: {})", name, names.join(", "));
+            }
+            profile
+        }
+        None => None,
+    };
+
+    let mut processor = CommandProcessor::new()?;
+    let specs = if args.engines.is_empty() {
+        vec![config.asr_engine.clone()]
+    } else {
+        args.engines
+    };
+
+    let mut reports = Vec::new();
+    for spec in &specs {
+        let mut engine = asr::bench::engine_from_spec(spec, config)?;
+        let grammar = if args.no_grammar {
+            None
+        } else {
+            Some(&mut engine)
+        };
+        apply_profile(&mut processor, grammar, config, profile, &[]);
+        reports.push(asr::bench::run(spec, engine.as_mut(), &corpus, &processor).await?);
+    }
+
+    match args.json.as_deref() {
+        Some(path) if path == std::path::Path::new("-") => {
+            println!("{}", serde_json::to_string_pretty(&reports)?);
+            return Ok(());
+        }
+        Some(path) => {
+            std::fs::write(path, serde_json::to_string_pretty(&reports)?)?;
+            info!("💾 Benchmark report written to {}", path.display());
+        }
+        None => {}
+    }
+    print!("{}", asr::bench::format_table(&reports));
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Args::parse();
@@ -88,19 +308,52 @@ async fn main() -> Result<()> {
         Ok(())
     }
 
+    // Load configuration
+    let app_config = tuxtalks::config::Config::load().unwrap_or_default();
+    info!(
+        "⚙️ Configuration loaded (Wake Word: '{}')",
+        app_config.wake_word
+    );
+
+    if let Some(Commands::BenchAsr(bench)) = args.command {
+        return run_bench(bench, &app_config).await;
+    }
+
     // Initialize audio capture
     // Initialize audio capture (Non-fatal for invalid devices/CI)
-    let mut audio_rx = match audio::start_capture(args.device) {
-        Ok(rx) => {
+    let capture = match &args.input_file {
+        Some(path) => {
+            let pacing = if args.fast {
+                audio::Pacing::Fast
+            } else {
+                audio::Pacing::Realtime
+            };
+            audio::start_file_source(path, pacing).map(|rx| (None, rx))
+        }
+        None => {
+            let name = args
+                .device_name
+                .as_deref()
+                .unwrap_or(&app_config.audio_device);
+            audio::AudioCapture::start(
+                audio::DeviceSelector::from_args(args.device, name),
+                app_config.dsp.clone(),
+            )
+            .map(|(handle, rx)| (Some(handle), rx))
+        }
+    };
+    // The capture handle keeps the microphone stream open until the daemon exits
+    let (_audio_capture, mut audio_rx) = match capture {
+        Ok((handle, rx)) => {
             info!("🎙️ Audio capture started");
-            Some(rx)
+            (handle, Some(rx))
         }
         Err(e) => {
             warn!(
                 "⚠️ Audio capture failed (running in headless/text-only mode): {}",
                 e
             );
-            None
+            (None, None)
         }
     };
 
@@ -110,14 +363,21 @@ async fn main() -> Result<()> {
     // Initialize game manager
     let mut game_manager = games::GameManager::new()?;
 
-    // Load configuration
-    let app_config = tuxtalks::config::Config::load().unwrap_or_default();
-    info!(
-        "⚙️ Configuration loaded (Wake Word: '{}')",
-        app_config.wake_word
-    );
+    // Keep a local Wyoming server alive when the ASR chain uses it
+    let wyoming_server = if app_config.wyoming_auto_start
+        && app_config.asr_engine_chain().iter().any(|e| e == "wyoming")
+    {
+        match asr::wyoming_manager::WyomingSupervisor::start(&app_config) {
+            Ok(supervisor) => Some(supervisor),
+            Err(e) => {
+                warn!("⚠️ Wyoming server supervision unavailable: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
 
-    // Initialize ASR
     // Initialize ASR (Non-fatal for headless)
     let mut asr = match asr::create_engine(app_config.clone()) {
         Ok(engine) => Some(engine),
@@ -132,6 +392,13 @@ async fn main() -> Result<()> {
     // 1. Local Library (needed for player manager)
     let library_path = std::path::PathBuf::from(&app_config.library_db_path);
     let library = Arc::new(tuxtalks::library::LocalLibrary::new(library_path)?);
+    // Library names keep media requests recognizable under a profile's grammar
+    let library_vocabulary = library
+        .vocabulary(LIBRARY_VOCABULARY_LIMIT)
+        .unwrap_or_else(|e| {
+            warn!("⚠️ Could not read library names for the ASR grammar: {}", e);
+            Vec::new()
+        });
 
     // 2. Player Manager
     let player_manager = Arc::new(tuxtalks::player_manager::PlayerManager::new(
@@ -156,26 +423,42 @@ async fn main() -> Result<()> {
     }
     processor.set_ollama_handler(ollama_handler);
 
-    if let Some(idx) = game_manager.detect_active_profile() {
-        let profile = &game_manager.profiles[idx];
-        info!("🎯 Auto-detected active game: {}", profile.name);
-
-        let commands = profile.get_processor_commands();
-        for cmd in commands {
-            processor.add_command(cmd);
-        }
-        processor.set_action_map(profile.resolve_actions());
+    let detected = game_manager.detect_active_profile();
+    let mut last_detected = detected.map(|idx| game_manager.profiles[idx].name.clone());
+    if let Some(idx) = detected {
+        info!(
+            "🎯 Auto-detected active game: {}",
+            game_manager.profiles[idx].name
+        );
     } else {
         info!("💡 No active game detected, using demo bindings");
-        processor.add_demo_bindings();
     }
+    apply_profile(
+        &mut processor,
+        asr.as_mut(),
+        &app_config,
+        game_manager.get_active_profile(),
+        &library_vocabulary,
+    );
+
+    processor.set_compound_gap(Duration::from_millis(app_config.compound_command_gap_ms));
+
+    // Mute capture while we speak or play effects, plus a tail for room echo
+    audio::PlaybackMonitor::global()
+        .set_tail(Duration::from_millis(app_config.playback_mute_tail_ms));
+    audio::PlaybackMonitor::global().set_mute_effects(app_config.mute_during_effects);
 
     // Initialize Sound Engine
     let sound_engine = Arc::new(audio::SoundEngine::new().expect("Failed to init sound engine"));
     processor.set_sound_engine(sound_engine.clone());
 
-    // Initialize TTS
-    let tts_engine = tts::create_engine(app_config.clone(), None).await.ok();
+    // Initialize TTS (Piper plays through the shared sound engine)
+    let tts_engine = tts::create_engine(app_config.clone(), Some(sound_engine.clone()))
+        .await
+        .ok();
+    if let Some(tts) = &tts_engine {
+        processor.set_tts(tts.clone());
+    }
     // NOTE (Jony): Do NOT announce on daemon startup.
     // Announcement only happens in GUI when user clicks Start Listening.
 
@@ -216,10 +499,17 @@ async fn main() -> Result<()> {
         (-1, true) // Always cancel for now to avoid blocking
     };
 
+    // "abort" from the launcher stops runaway macros even mid-command
+    let in_flight = processor.in_flight();
+    ipc_server.set_control_handler(move |action| {
+        (action == tuxtalks::ipc::CONTROL_ABORT).then(|| in_flight.abort_all().to_string())
+    });
+
     match ipc_server.start(callback) {
         Ok(_) => info!("🔗 IPC Server started"),
         Err(e) => warn!("⚠️ Failed to start IPC server: {}", e),
     }
+    let ipc_status = ipc_server.status();
 
     let ptt_mode = match app_config.ptt_mode.to_uppercase().as_str() {
         "TOGGLE" => PttMode::Toggle,
@@ -246,23 +536,235 @@ async fn main() -> Result<()> {
         .to_lowercase();
     let command_timeout = Duration::from_secs(app_config.command_timeout);
 
+    // Keyword spotter for idle listening (full ASR only runs after a wake word)
+    let mut wake_config = app_config.clone();
+    wake_config.wake_word = wake_word.clone();
+    let wake_bindings = wake_config.wake_word_bindings();
+    let wake_phrases: Vec<String> = wake_bindings
+        .iter()
+        .map(|b| b.phrase.to_lowercase())
+        .collect();
+    let mut wake_spotter = match asr::wake::create_spotter(&wake_config) {
+        Ok(spotter) => spotter,
+        Err(e) => {
+            warn!(
+                "⚠️ Wake word spotter unavailable, matching transcripts instead: {}",
+                e
+            );
+            None
+        }
+    };
+    // Set when a wake word picked a profile; auto-detection leaves it alone
+    // until the running game changes or the pin expires
+    let mut pinned_until: Option<Instant> = None;
+    let mut preroll: std::collections::VecDeque<i16> = std::collections::VecDeque::new();
+
     // Main loop
     info!("✅ TuxTalks ready - say '{}' or use PTT", wake_word);
     let mut timeout_check = tokio::time::interval(Duration::from_millis(500));
+    // Process scans block, so they run on the blocking pool and report back
+    let (profile_tx, mut profile_rx) = tokio::sync::mpsc::channel::<Option<String>>(1);
+    let mut detector = game_manager.process_detector();
+    tokio::spawn(async move {
+        let mut profile_check = tokio::time::interval(PROFILE_CHECK_INTERVAL);
+        loop {
+            profile_check.tick().await;
+            let Ok((returned, found)) = tokio::task::spawn_blocking(move || {
+                let found = detector.scan();
+                (detector, found)
+            })
+            .await
+            else {
+                break;
+            };
+            detector = returned;
+            if profile_tx.send(found).await.is_err() {
+                break;
+            }
+        }
+    });
+    let mut vad = audio::VoiceActivityDetector::default();
+    let mut early_fired = false;
 
     loop {
         tokio::select! {
             // Handle audio samples (if active)
-            Some(samples) = async {
+            chunk = async {
                 match &mut audio_rx {
                     Some(rx) => rx.recv().await,
                     None => std::future::pending().await,
                 }
             } => {
+                let Some(samples) = chunk else {
+                    audio_rx = None;
+                    if args.input_file.is_some() {
+                        info!("🏁 Input file finished, shutting down");
+                        return Ok(());
+                    }
+                    warn!("⚠️ Audio capture stopped");
+                    continue;
+                };
+                let vad_event = vad.process(&samples);
+                match &vad_event {
+                    audio::VadEvent::UtteranceStart(audio) => {
+                        preroll.clear();
+                        preroll.extend(audio.iter());
+                    }
+                    audio::VadEvent::Speech => preroll.extend(samples.iter()),
+                    _ => preroll.clear(),
+                }
+                if preroll.len() > WAKE_PREROLL_SAMPLES {
+                    preroll.drain(..preroll.len() - WAKE_PREROLL_SAMPLES);
+                }
+                if let audio::VadEvent::UtteranceStart(_) = vad_event {
+                    // New utterance: a pending early fire can't suppress its final
+                    early_fired = false;
+                    processor.reset_partial();
+                }
+
+                // Keep command mode open while the user is still talking
+                let speaking = matches!(
+                    vad_event,
+                    audio::VadEvent::UtteranceStart(_) | audio::VadEvent::Speech
+                );
+                if speaking && matches!(state, AssistantState::CommandMode { .. }) {
+                    state = AssistantState::CommandMode { started_at: Instant::now() };
+                }
+
+                // A final the recognizer produced from the wake word pre-roll
+                let mut handover = None;
+
+                // Idle: only the keyword spotter hears the audio
+                if let Some(spotter) = &mut wake_spotter {
+                    if state == AssistantState::Listening && !listener.is_ptt_active() {
+                        let phrase = match spotter.process(&samples) {
+                            Ok(Some(phrase)) => phrase,
+                            Ok(None) => continue,
+                            Err(e) => {
+                                warn!("⚠️ Wake word spotter error: {}", e);
+                                continue;
+                            }
+                        };
+                        info!("🔔 Wake word detected: '{}'", phrase);
+                        let _ = flush_audit_log(&format!("Wake Word Detected: {}", phrase)); // Alex
+
+                        let binding = wake_bindings.iter().find(|b| b.phrase == phrase).cloned().unwrap_or_default();
+                        if let Some(name) = &binding.profile {
+                            match game_manager.profiles.iter().position(|p| p.name.eq_ignore_ascii_case(name)) {
+                                Some(idx) => {
+                                    pinned_until = Some(Instant::now() + PROFILE_PIN_TIMEOUT);
+                                    if game_manager.set_active_profile(Some(idx)) {
+                                        apply_profile(&mut processor, asr.as_mut(), &app_config, game_manager.get_active_profile(), &library_vocabulary);
+                                    }
+                                }
+                                None => warn!("⚠️ Wake word '{}' refers to unknown profile '{}'", phrase, name),
+                            }
+                        }
+
+                        // Start the full recognizer from a clean slate, handing it this
+                        // utterance so far in case the command follows in the same breath
+                        if let Some(ref mut asr_engine) = asr {
+                            asr_engine.reset();
+                            if speaking {
+                                let heard: Vec<i16> = preroll.iter().copied().collect();
+                                match asr_engine.process(&heard) {
+                                    // The whole command fit in the pre-roll
+                                    Ok(Some(event @ asr::AsrEvent::Final(_))) => handover = Some(event),
+                                    Ok(_) => {}
+                                    Err(e) => warn!("⚠️ ASR error: {}", e),
+                                }
+                            }
+                        }
+                        preroll.clear();
+                        processor.reset_partial();
+                        early_fired = false;
+
+                        if let Some(action) = &binding.action {
+                            match processor.process(action).await {
+                                tuxtalks::commands::ProcessResult::Success(cmd)
+                                | tuxtalks::commands::ProcessResult::SuccessWithCorrection { action: cmd, .. } => {
+                                    info!("✅ Command Executed: {}", cmd);
+                                    let _ = flush_audit_log(&format!("Executed: {} (wake word)", cmd));
+                                }
+                                other => warn!("❓ Wake word action '{}' did not run: {:?}", action, other),
+                            }
+                            continue;
+                        }
+
+                        info!("🔔 Entering command mode...");
+                        state = AssistantState::CommandMode { started_at: Instant::now() };
+                        // Speaking now would mute capture over the rest of the command
+                        if !speaking {
+                            if let Some(ref engine) = tts_engine {
+                                let _ = engine.speak("Yes?").await; // Jony
+                            }
+                        }
+                        if handover.is_none() {
+                            continue;
+                        }
+                    }
+                }
+
                 if let Some(engine) = &mut asr {
-                    if let Some(result) = engine.process(&samples)? {
+                    // The pre-roll already went through the recognizer, this chunk included
+                    let event = match handover {
+                        Some(event) => Some(event),
+                        None => match engine.process(&samples) {
+                            Ok(event) => event,
+                            Err(e) => {
+                                warn!("⚠️ ASR error: {}", e);
+                                continue;
+                            }
+                        },
+                    };
+
+                    // Early fire: exact triggers from a stable partial (no endpointing wait)
+                    if let Some(asr::AsrEvent::Partial(partial)) = &event {
+                        let partial = partial.to_lowercase();
+                        let candidate = match state {
+                            AssistantState::CommandMode { .. } => Some(after_wake_phrase(&partial, &wake_phrases)),
+                            AssistantState::Listening if listener.is_ptt_active() => Some(partial.as_str()),
+                            AssistantState::Listening => partial.strip_prefix(wake_word.as_str()).map(str::trim),
+                            _ => None,
+                        };
+                        if let Some(name) = candidate.and_then(|c| processor.process_partial(c)) {
+                            info!("✅ Command Executed (early): {}", name);
+                            let _ = flush_audit_log(&format!("Executed: {} (partial)", name));
+                            early_fired = true;
+                            state = AssistantState::CommandMode { started_at: Instant::now() };
+                        }
+                        continue;
+                    }
+
+                    if let Some(asr::AsrEvent::Final(result)) = event {
                         if result.text.is_empty() { continue; }
 
+                        // The command was already executed from a partial of this utterance
+                        if early_fired {
+                            early_fired = false;
+                            // "next target five times": the partial only pressed it once
+                            if let Some(name) = processor.finish_early_fire(&result.text).await {
+                                info!("✅ Command Executed: {} (completed)", name);
+                                let _ = flush_audit_log(&format!("Executed: {} (completed)", name));
+                            }
+                            let remainder = processor.early_fire_remainder(&result.text);
+                            processor.reset_partial();
+                            // "deploy gear and lights on": the rest still needs to run
+                            if let Some(remainder) = remainder {
+                                match processor.process(&remainder).await {
+                                    tuxtalks::commands::ProcessResult::Success(cmd)
+                                    | tuxtalks::commands::ProcessResult::SuccessWithCorrection { action: cmd, .. } => {
+                                        info!("✅ Command Executed: {}", cmd);
+                                        let _ = flush_audit_log(&format!("Executed: {}", cmd));
+                                    }
+                                    other => warn!("❓ '{}' after early fire did not run: {:?}", remainder, other),
+                                }
+                            } else {
+                                debug!("⏩ Skipping final '{}' (handled by early fire)", result.text);
+                            }
+                            continue;
+                        }
+
                     let normalized = result.text.to_lowercase();
                     debug!("📝 Raw ASR: '{}' (confidence: {:.2})", normalized, result.confidence);
 
@@ -270,10 +772,26 @@ async fn main() -> Result<()> {
                     let ptt_active = listener.is_ptt_active();
                     let mut cmd_to_run = None;
 
+                    // "abort" answers an open prompt with "cancel", but it is also the
+                    // emergency stop, so running macros and held keys stop as well
+                    let prompt_open = !matches!(state, AssistantState::Listening | AssistantState::CommandMode { .. });
+                    if prompt_open && tuxtalks::commands::ABORT_PHRASES.contains(&normalized.as_str()) {
+                        info!("❌ Prompt cancelled");
+                        state = AssistantState::Listening;
+                        let aborted = processor.in_flight().abort_all();
+                        if !aborted.is_empty() {
+                            info!("🛑 {}", aborted);
+                            if let Some(ref engine) = tts_engine {
+                                let _ = engine.speak(&aborted.to_string()).await;
+                            }
+                        }
+                        continue;
+                    }
+
                     match state {
                         AssistantState::Listening => {
                             // 1. Guardrail: ASR Confidence Gate (Wendy)
-                            if result.confidence < 0.5 {
+                            if result.confidence < asr::MIN_CONFIDENCE {
                                 warn!("🔇 Rejecting low-confidence transcription: '{}' ({:.2})", normalized, result.confidence);
                                 continue;
                             }
@@ -307,9 +825,7 @@ async fn main() -> Result<()> {
                                     info!("🔔 Wake word detected! Entering command mode...");
                                     state = AssistantState::CommandMode { started_at: Instant::now() };
                                     if let Some(ref engine) = tts_engine {
-                                        if let Some(ref mut asr_engine) = asr { asr_engine.pause(); }
                                         let _ = engine.speak("Yes?").await; // Jony
-                                        if let Some(ref mut asr_engine) = asr { asr_engine.resume(); }
                                     }
                                 } else {
                                     info!("🔔 Wake word + Command: '{}'", remainder);
@@ -319,9 +835,7 @@ async fn main() -> Result<()> {
 
                                     // Acknowledge look-forward (Wendy/Jony UX)
                                     if let Some(ref engine) = tts_engine {
-                                        if let Some(ref mut asr_engine) = asr { asr_engine.pause(); }
                                         let _ = engine.speak("Acknowledged.").await;
-                                        if let Some(ref mut asr_engine) = asr { asr_engine.resume(); }
                                     }
                                 }
                             } else if ptt_active {
@@ -332,8 +846,10 @@ async fn main() -> Result<()> {
                             }
                         }
                         AssistantState::CommandMode { .. } => {
-                            info!("🎯 Command Mode: '{}'", normalized);
-                            cmd_to_run = Some(normalized);
+                            let command = after_wake_phrase(&normalized, &wake_phrases);
+                            if command.is_empty() { continue; }
+                            info!("🎯 Command Mode: '{}'", command);
+                            cmd_to_run = Some(command.to_string());
                             // Refresh timer
                             state = AssistantState::CommandMode { started_at: Instant::now() };
                         }
@@ -373,35 +889,65 @@ async fn main() -> Result<()> {
                             }
                             continue; // Skip the general cmd processing
                         }
-                        AssistantState::ConfirmationMode { ref action, ref command, .. } => {
+                        AssistantState::ConfirmationMode { ref action, ref command, modifiers, .. } => {
                              info!("🛡️ Confirmation Mode: '{}'", normalized);
                              if normalized == "confirm" || normalized == "yes" || normalized == "do it" {
                                  info!("✅ Command confirmed: {}", action);
                                  let _ = flush_audit_log(&format!("Confirmed & Executed: {}", action));
-                                 let shared_kb = processor.keyboard.clone();
-                                 let action_map = processor.get_action_map();
-                                 let sound_engine = processor.sound_engine.clone();
-                                 let lal = processor.lal_manager.clone();
-
-                                 tokio::spawn(tuxtalks::commands::CommandProcessor::execute_command_async(
-                                     shared_kb,
-                                     action_map,
-                                     sound_engine,
-                                     lal,
-                                     None,
-                                     command.clone(),
-                                 ));
+                                 run_chosen(&processor, command.clone(), modifiers).await;
                                  state = AssistantState::Listening;
-                             } else if normalized == "cancel" || normalized == "no" || normalized == "abort" {
+                             } else if normalized == "cancel" || normalized == "no" {
                                  info!("❌ Command cancelled");
                                  state = AssistantState::Listening;
                              }
                              continue;
                         }
+                        AssistantState::CommandChoiceMode { ref candidates, modifiers, .. } => {
+                            info!("🔢 Command Choice: '{}'", normalized);
+                            if normalized.contains("cancel") || normalized == "no" {
+                                info!("🚫 Command choice cancelled");
+                                state = AssistantState::Listening;
+                            } else if let Some(idx) = spoken_choice(&normalized, candidates.len()) {
+                                let command = candidates[idx].clone();
+                                let name = command.command.name().to_string();
+                                info!("✅ Command chosen: {}", name);
+                                if tuxtalks::commands::is_dangerous(&name) {
+                                    if let Some(ref engine) = tts_engine {
+                                        let _ = engine.speak(&format!("Dangerous command detected: {}. Say confirm to proceed or cancel to abort.", name)).await;
+                                    }
+                                    state = AssistantState::ConfirmationMode {
+                                        started_at: Instant::now(),
+                                        action: name,
+                                        command,
+                                        modifiers,
+                                    };
+                                } else {
+                                    let _ = flush_audit_log(&format!("Executed: {} (chosen)", name));
+                                    state = AssistantState::Listening;
+                                    run_chosen(&processor, command, modifiers).await;
+                                }
+                            } else {
+                                warn!("❓ Invalid choice: '{}'. Please say a number 1-{}", normalized, candidates.len().min(3));
+                            }
+                            continue;
+                        }
                     }
 
                     if let Some(cmd_to_run) = cmd_to_run {
-                        match processor.process(&cmd_to_run).await {
+                        // Runner-up hypotheses get the same wake word treatment as the top one
+                        let mut hypotheses = vec![asr::AsrAlternative {
+                            text: cmd_to_run.clone(),
+                            confidence: result.confidence,
+                        }];
+                        hypotheses.extend(result.alternatives.iter().map(|alt| {
+                            let text = alt.text.to_lowercase();
+                            asr::AsrAlternative {
+                                text: text.strip_prefix(wake_word.as_str()).unwrap_or(&text).trim().to_string(),
+                                confidence: alt.confidence,
+                            }
+                        }));
+
+                        match processor.process_hypotheses(&hypotheses).await {
                             tuxtalks::commands::ProcessResult::Success(cmd) => {
                                 info!("✅ Command Executed: {}", cmd);
                                 let _ = flush_audit_log(&format!("Executed: {}", cmd));
@@ -464,25 +1010,84 @@ async fn main() -> Result<()> {
                                     _ => warn!("IPC selection failed (unexpected response)"),
                                 }
                              }
-                             tuxtalks::commands::ProcessResult::ConfirmationRequired { action, command } => {
+                             tuxtalks::commands::ProcessResult::ConfirmationRequired { action, command, modifiers } => {
                                  info!("⚠️ Confirmation required for: {}", action);
                                  if let Some(ref engine) = tts_engine {
-                                     if let Some(ref mut asr_engine) = asr { asr_engine.pause(); }
                                      let _ = engine.speak(&format!("Dangerous command detected: {}. Say confirm to proceed or cancel to abort.", action)).await;
-                                     if let Some(ref mut asr_engine) = asr { asr_engine.resume(); }
                                  }
                                  state = AssistantState::ConfirmationMode {
                                      started_at: Instant::now(),
                                      action,
                                      command,
+                                     modifiers,
+                                 };
+                             }
+                             tuxtalks::commands::ProcessResult::AlreadySet { action, on } => {
+                                 info!("✋ {} is already {}", action, tuxtalks::commands::on_off(on));
+                                 if let Some(ref engine) = tts_engine {
+                                     let _ = engine.speak(&format!("{} is already {}", action, tuxtalks::commands::on_off(on))).await;
+                                 }
+                             }
+                             tuxtalks::commands::ProcessResult::Declined { reason } => {
+                                 info!("🤷 {}", reason);
+                                 if let Some(ref engine) = tts_engine {
+                                     let _ = engine.speak(&reason).await;
+                                 }
+                             }
+                             tuxtalks::commands::ProcessResult::CommandChoiceRequired { query, candidates, modifiers } => {
+                                 info!("🤔 '{}' matches several commands", query);
+                                 let names: Vec<String> = candidates.iter().map(|c| c.command.name().to_string()).collect();
+
+                                 // A GUI picker if one is running, otherwise ask by voice
+                                 let chosen = match tuxtalks::ipc::client::IpcClient::send_selection_request(
+                                     &format!("Which command for: {}", query),
+                                     names,
+                                     0,
+                                     std::time::Duration::from_secs(30)
+                                 ) {
+                                     Ok(Some((idx, false))) => candidates.get(idx as usize).cloned(),
+                                     Ok(_) => {
+                                         info!("Command choice cancelled");
+                                         None
+                                     }
+                                     Err(_) => {
+                                         warn!("❌ GUI not reachable via IPC, asking by voice.");
+                                         if let Some(ref engine) = tts_engine {
+                                             let _ = engine.speak(&command_choice_prompt(&candidates)).await;
+                                         }
+                                         state = AssistantState::CommandChoiceMode {
+                                             started_at: Instant::now(),
+                                             query,
+                                             candidates: candidates.clone(),
+                                             modifiers,
+                                         };
+                                         None
+                                     }
                                  };
+
+                                 if let Some(command) = chosen {
+                                     let name = command.command.name().to_string();
+                                     info!("✅ Command chosen: {}", name);
+                                     if tuxtalks::commands::is_dangerous(&name) {
+                                         if let Some(ref engine) = tts_engine {
+                                             let _ = engine.speak(&format!("Dangerous command detected: {}. Say confirm to proceed or cancel to abort.", name)).await;
+                                         }
+                                         state = AssistantState::ConfirmationMode {
+                                             started_at: Instant::now(),
+                                             action: name,
+                                             command,
+                                             modifiers,
+                                         };
+                                     } else {
+                                         let _ = flush_audit_log(&format!("Executed: {} (chosen)", name));
+                                         run_chosen(&processor, command, modifiers).await;
+                                     }
+                                 }
                              }
                              tuxtalks::commands::ProcessResult::NotFound => {
                                 warn!("❓ Unknown command: {}", cmd_to_run);
                                 if let Some(ref engine) = tts_engine {
-                                    if let Some(ref mut asr_engine) = asr { asr_engine.pause(); }
                                     let _ = engine.speak("I didn't catch that command.").await;
-                                    if let Some(ref mut asr_engine) = asr { asr_engine.resume(); }
                                 }
                             }
                         }
@@ -490,6 +1095,28 @@ async fn main() -> Result<()> {
                 }
                 }
             }
+            // Follow game launches/exits
+            Some(detected) = profile_rx.recv() => {
+                let game_changed = detected != last_detected;
+                last_detected = detected;
+                if pinned_until.is_some_and(|until| game_changed || Instant::now() >= until) {
+                    info!("📌 Wake word profile released, following running game again");
+                    pinned_until = None;
+                }
+                let index = last_detected
+                    .as_ref()
+                    .and_then(|name| game_manager.profiles.iter().position(|p| &p.name == name));
+                // As at startup, a game exiting leaves its profile active
+                if pinned_until.is_none() && index.is_some() && game_manager.set_active_profile(index) {
+                    apply_profile(
+                        &mut processor,
+                        asr.as_mut(),
+                        &app_config,
+                        game_manager.get_active_profile(),
+                        &library_vocabulary,
+                    );
+                }
+            }
             // Handle listener commands (shortcuts)
             Some(cmd_text) = listener_rx.recv() => {
                 info!("🔑 Shortcut Triggered: {}", cmd_text);
@@ -521,36 +1148,46 @@ async fn main() -> Result<()> {
             }
             // Periodic timeout check (background)
             _ = timeout_check.tick() => {
+                if let Ok(mut status) = ipc_status.lock() {
+                    status.paused = asr.as_ref().is_some_and(|e| e.is_paused());
+                    status.active_profile = game_manager.get_active_profile().map(|p| p.name.clone());
+                    status.asr_engine = asr.as_ref().map(|e| e.name().to_string());
+                    status.wyoming_server = wyoming_server.as_ref().map(|w| w.state().as_str().to_string());
+                }
+                // A macro waiting for a phrase (which may take longer than
+                // command_timeout) keeps full recognition running
+                if processor.awaiting_speech() && matches!(state, AssistantState::Listening | AssistantState::CommandMode { .. }) {
+                    state = AssistantState::CommandMode { started_at: Instant::now() };
+                }
                 match state {
-                    AssistantState::CommandMode { started_at } => {
-                        if started_at.elapsed() > command_timeout {
-                            info!("⏱ Command mode timed out");
-                            state = AssistantState::Listening;
-                        }
+                    AssistantState::CommandMode { started_at } if started_at.elapsed() > command_timeout => {
+                        info!("⏱ Command mode timed out");
+                        state = AssistantState::Listening;
                     }
-                    AssistantState::SelectionMode { started_at, ref query, ref results } => {
-                        if started_at.elapsed() > Duration::from_secs(15) {
-                            info!("⏱ Selection mode timed out - auto-playing best match for '{}'", query);
+                    AssistantState::SelectionMode { started_at, ref query, ref results } if started_at.elapsed() > Duration::from_secs(15) => {
+                        info!("⏱ Selection mode timed out - auto-playing best match for '{}'", query);
 
-                            if let Some(selected) = results.first() {
-                                let player_arc = player_manager.player();
-                                let player = player_arc.read().await;
-                                let _ = match selected.result_type {
-                                    tuxtalks::players::SearchResultType::Artist => player.play_artist(&selected.value).await,
-                                    tuxtalks::players::SearchResultType::Album => player.play_album(&selected.value).await,
-                                    tuxtalks::players::SearchResultType::Song => player.play_song(&selected.value).await,
-                                    tuxtalks::players::SearchResultType::Playlist => player.play_playlist(&selected.value, false).await,
-                                    tuxtalks::players::SearchResultType::Genre => player.play_genre(&selected.value).await,
-                                };
-                            }
-                            state = AssistantState::Listening;
+                        if let Some(selected) = results.first() {
+                            let player_arc = player_manager.player();
+                            let player = player_arc.read().await;
+                            let _ = match selected.result_type {
+                                tuxtalks::players::SearchResultType::Artist => player.play_artist(&selected.value).await,
+                                tuxtalks::players::SearchResultType::Album => player.play_album(&selected.value).await,
+                                tuxtalks::players::SearchResultType::Song => player.play_song(&selected.value).await,
+                                tuxtalks::players::SearchResultType::Playlist => player.play_playlist(&selected.value, false).await,
+                                tuxtalks::players::SearchResultType::Genre => player.play_genre(&selected.value).await,
+                            };
                         }
+                        state = AssistantState::Listening;
                     }
-                    AssistantState::ConfirmationMode { started_at, .. } => {
-                        if started_at.elapsed() > Duration::from_secs(10) {
-                            info!("⏱ Confirmation mode timed out");
-                            state = AssistantState::Listening;
-                        }
+                    AssistantState::ConfirmationMode { started_at, .. } if started_at.elapsed() > Duration::from_secs(10) => {
+                        info!("⏱ Confirmation mode timed out");
+                        state = AssistantState::Listening;
+                    }
+                    // Never guess between tied commands
+                    AssistantState::CommandChoiceMode { started_at, ref query, .. } if started_at.elapsed() > Duration::from_secs(15) => {
+                        info!("⏱ Command choice for '{}' timed out, nothing executed", query);
+                        state = AssistantState::Listening;
                     }
                     _ => {}
                 }
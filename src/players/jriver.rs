@@ -820,6 +820,17 @@ impl MediaPlayer for JRiverPlayer {
         Ok(())
     }
 
+    async fn pause(&self) -> Result<()> {
+        // State=1 pauses; without it MCWS toggles
+        self.send_command("Playback/Pause", "State=1").await?;
+        Ok(())
+    }
+
+    async fn play(&self) -> Result<()> {
+        self.send_command("Playback/Play", "").await?;
+        Ok(())
+    }
+
     async fn next_track(&self) -> Result<()> {
         self.send_command("Playback/Next", "").await?;
         // Brief delay for track change, then log what's playing (P1.2)
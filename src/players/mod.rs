@@ -73,6 +73,12 @@ pub trait MediaPlayer: Send + Sync {
     /// Toggle play/pause
     async fn play_pause(&self) -> Result<()>;
 
+    /// Pause playback (stays paused if it already is)
+    async fn pause(&self) -> Result<()>;
+
+    /// Start or resume playback (keeps playing if it already is)
+    async fn play(&self) -> Result<()>;
+
     /// Skip to the next track
     async fn next_track(&self) -> Result<()>;
 
@@ -127,6 +127,20 @@ impl MediaPlayer for MprisPlayer {
         Ok(())
     }
 
+    async fn pause(&self) -> Result<()> {
+        let conn = ZBusConnection::session().await?;
+        let proxy = Self::get_proxy(&conn, &self.service_name).await?;
+        proxy.pause().await?;
+        Ok(())
+    }
+
+    async fn play(&self) -> Result<()> {
+        let conn = ZBusConnection::session().await?;
+        let proxy = Self::get_proxy(&conn, &self.service_name).await?;
+        proxy.play().await?;
+        Ok(())
+    }
+
     async fn next_track(&self) -> Result<()> {
         let conn = ZBusConnection::session().await?;
         let proxy = Self::get_proxy(&conn, &self.service_name).await?;
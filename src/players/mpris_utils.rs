@@ -6,6 +6,8 @@ use zbus::proxy;
 )]
 pub trait MprisPlayer {
     fn play_pause(&self) -> zbus::Result<()>;
+    fn play(&self) -> zbus::Result<()>;
+    fn pause(&self) -> zbus::Result<()>;
     fn next(&self) -> zbus::Result<()>;
     fn previous(&self) -> zbus::Result<()>;
     fn stop(&self) -> zbus::Result<()>;
@@ -154,6 +154,20 @@ impl MediaPlayer for StrawberryPlayer {
         Ok(())
     }
 
+    async fn pause(&self) -> Result<()> {
+        let conn = ZBusConnection::session().await?;
+        let proxy = Self::get_proxy(&conn).await?;
+        proxy.pause().await?;
+        Ok(())
+    }
+
+    async fn play(&self) -> Result<()> {
+        let conn = ZBusConnection::session().await?;
+        let proxy = Self::get_proxy(&conn).await?;
+        proxy.play().await?;
+        Ok(())
+    }
+
     async fn next_track(&self) -> Result<()> {
         let conn = ZBusConnection::session().await?;
         let proxy = Self::get_proxy(&conn).await?;
@@ -6,16 +6,18 @@ use crate::config::Config;
 use anyhow::Result;
 use async_trait::async_trait;
 use std::sync::Arc;
+use std::time::Duration;
 use tracing::{info, warn};
 
 pub mod piper;
 pub mod speechd;
 pub mod system;
+pub mod wyoming;
 
 /// Trait for TTS engines
 #[async_trait]
 pub trait TtsEngine: Send + Sync + std::fmt::Debug {
-    /// Speak the given text
+    /// Speak the given text, returning once the audio has finished playing
     async fn speak(&self, text: &str) -> Result<()>;
 
     /// Get the engine name
@@ -42,6 +44,14 @@ pub async fn create_engine(
             let client = speechd::SpeechdEngine::connect().await?;
             Arc::new(client)
         }
+        "wyoming" => {
+            let mut w = wyoming::WyomingTtsEngine::new(&config);
+            if let Some(se) = sound_engine {
+                w.set_sound_engine(se);
+            }
+            info!("  - Using Wyoming TTS");
+            Arc::new(w)
+        }
         "system" => {
             info!("  - Using System TTS Fallback");
             Arc::new(system::SystemEngine::new())
@@ -55,5 +65,29 @@ pub async fn create_engine(
         }
     };
     info!("✅ TTS engine '{}' initialized", engine.name());
-    Ok(engine)
+    Ok(Arc::new(Monitored(engine)))
+}
+
+/// Rough time to say `text` out loud, for backends that don't report when
+/// they finish (about 15 characters a second plus a margin for the tail)
+pub(crate) fn estimated_duration(text: &str) -> Duration {
+    Duration::from_millis(300 + text.chars().count() as u64 * 1000 / 15)
+}
+
+/// Raises the playback signal while the wrapped engine speaks, so capture
+/// mutes itself whichever backend is producing the audio. Every backend's
+/// `speak()` returns only once the audio has finished.
+#[derive(Debug)]
+struct Monitored(Arc<dyn TtsEngine>);
+
+#[async_trait]
+impl TtsEngine for Monitored {
+    async fn speak(&self, text: &str) -> Result<()> {
+        let _playing = crate::audio::PlaybackMonitor::global().begin();
+        self.0.speak(text).await
+    }
+
+    fn name(&self) -> &str {
+        self.0.name()
+    }
 }
@@ -50,6 +50,9 @@ impl SpeechdEngine {
 impl TtsEngine for SpeechdEngine {
     async fn speak(&self, text: &str) -> Result<()> {
         self.proxy.speak(text).await?;
+        // speechd-ng queues the text and returns straight away, with no signal
+        // when it finishes, so wait out roughly how long it takes to say
+        tokio::time::sleep(super::estimated_duration(text)).await;
         Ok(())
     }
 
@@ -3,7 +3,7 @@
 use super::TtsEngine;
 use anyhow::Result;
 use async_trait::async_trait;
-use std::process::Command;
+use tokio::process::Command;
 use tracing::debug;
 
 #[derive(Debug)]
@@ -26,12 +26,19 @@ impl TtsEngine for SystemEngine {
     async fn speak(&self, text: &str) -> Result<()> {
         debug!("System speaking: {}", text);
 
-        // Try spd-say (speech-dispatcher) or espeak-ng
-        if Command::new("spd-say").arg(text).spawn().is_ok() {
+        // Try spd-say (speech-dispatcher) or espeak-ng, waiting until the speech
+        // ends so the playback signal covers all of it
+        if Command::new("spd-say")
+            .arg("--wait")
+            .arg(text)
+            .status()
+            .await
+            .is_ok()
+        {
             return Ok(());
         }
 
-        if Command::new("espeak-ng").arg(text).spawn().is_ok() {
+        if Command::new("espeak-ng").arg(text).status().await.is_ok() {
             return Ok(());
         }
 
@@ -0,0 +1,234 @@
+//! Wyoming TTS backend (wyoming-piper and similar)
+//!
+//! Sends a `synthesize` event and plays the `audio-start`/`audio-chunk`/
+//! `audio-stop` stream that comes back, so the voice can run on another
+//! machine and be shared between several clients.
+
+use super::TtsEngine;
+use crate::asr::wyoming::{read_event, write_event};
+use crate::config::Config;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tracing::{debug, info};
+
+/// Timeout for opening a TCP connection to the server
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Timeout for the whole synthesis (long sentences on a slow box take a while)
+const SYNTHESIZE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Synthesized audio as returned by the server
+#[derive(Debug, Clone, PartialEq)]
+pub struct SynthesizedAudio {
+    pub samples: Vec<i16>,
+    pub rate: u32,
+    pub channels: u16,
+}
+
+#[derive(Debug)]
+pub struct WyomingTtsEngine {
+    host: String,
+    port: u16,
+    /// Voice name passed to the server (empty = server default)
+    voice: String,
+    sound_engine: Option<Arc<crate::audio::SoundEngine>>,
+}
+
+impl WyomingTtsEngine {
+    pub fn new(config: &Config) -> Self {
+        let host = if config.wyoming_tts_host.is_empty() {
+            config.wyoming_host.clone()
+        } else {
+            config.wyoming_tts_host.clone()
+        };
+        Self {
+            host,
+            port: config.wyoming_tts_port,
+            voice: config.wyoming_tts_voice.clone(),
+            sound_engine: None,
+        }
+    }
+
+    pub fn set_sound_engine(&mut self, engine: Arc<crate::audio::SoundEngine>) {
+        self.sound_engine = Some(engine);
+    }
+
+    /// Ask the server to synthesize `text` and collect the returned PCM
+    pub async fn synthesize(&self, text: &str) -> Result<SynthesizedAudio> {
+        tokio::time::timeout(SYNTHESIZE_TIMEOUT, self.synthesize_inner(text))
+            .await
+            .context("Timed out waiting for Wyoming TTS audio")?
+    }
+
+    async fn synthesize_inner(&self, text: &str) -> Result<SynthesizedAudio> {
+        let stream = tokio::time::timeout(
+            CONNECT_TIMEOUT,
+            TcpStream::connect((self.host.as_str(), self.port)),
+        )
+        .await
+        .context("Timed out connecting to Wyoming TTS server")?
+        .context("Failed to connect to Wyoming TTS server")?;
+        let (reader, mut writer) = stream.into_split();
+        let mut reader = BufReader::new(reader);
+
+        let mut data = serde_json::json!({ "text": text });
+        if !self.voice.is_empty() {
+            data["voice"] = serde_json::json!({ "name": self.voice });
+        }
+        write_event(&mut writer, "synthesize", Some(data), None).await?;
+        writer.flush().await?;
+
+        let mut audio = SynthesizedAudio {
+            samples: Vec::new(),
+            rate: 22050,
+            channels: 1,
+        };
+        loop {
+            let event = read_event(&mut reader)
+                .await?
+                .context("Wyoming TTS server closed the connection mid-stream")?;
+            match event.event_type.as_str() {
+                "audio-start" | "audio-chunk" => {
+                    let field = |name: &str| event.data.get(name).and_then(|v| v.as_u64());
+                    if let Some(width) = field("width") {
+                        if width != 2 {
+                            anyhow::bail!("Unsupported sample width {} from Wyoming TTS", width);
+                        }
+                    }
+                    if let Some(rate) = field("rate") {
+                        audio.rate = rate as u32;
+                    }
+                    if let Some(channels) = field("channels") {
+                        audio.channels = channels as u16;
+                    }
+                    audio.samples.extend(
+                        event
+                            .payload
+                            .chunks_exact(2)
+                            .map(|b| i16::from_le_bytes([b[0], b[1]])),
+                    );
+                }
+                "audio-stop" => return Ok(audio),
+                "error" => {
+                    let message = event
+                        .data
+                        .get("text")
+                        .and_then(|t| t.as_str())
+                        .unwrap_or("unknown error");
+                    anyhow::bail!("Wyoming TTS error: {}", message);
+                }
+                other => debug!("Wyoming TTS: ignoring '{}' event", other),
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl TtsEngine for WyomingTtsEngine {
+    async fn speak(&self, text: &str) -> Result<()> {
+        info!("📢 Wyoming speaking: '{}'", text);
+
+        let audio = self.synthesize(text).await?;
+        if audio.samples.is_empty() {
+            debug!("Wyoming TTS returned no audio");
+            return Ok(());
+        }
+
+        let sound_engine = self.sound_engine.clone();
+        tokio::task::spawn_blocking(move || -> Result<()> {
+            let SynthesizedAudio {
+                samples,
+                rate,
+                channels,
+            } = audio;
+            if let Some(engine) = &sound_engine {
+                debug!("✅ Playing Wyoming TTS audio via SoundEngine");
+                engine.play_pcm_sync(samples, rate, channels)?;
+            } else {
+                debug!("📢 Playing Wyoming TTS audio via direct rodio fallback");
+                if let Ok((_stream, stream_handle)) = rodio::OutputStream::try_default() {
+                    if let Ok(sink) = rodio::Sink::try_new(&stream_handle) {
+                        sink.append(rodio::buffer::SamplesBuffer::new(channels, rate, samples));
+                        sink.sleep_until_end();
+                    }
+                }
+            }
+            Ok(())
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("Task join error: {}", e))??;
+
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "wyoming"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn test_synthesize_collects_audio_stream() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (reader, mut writer) = stream.into_split();
+            let mut reader = BufReader::new(reader);
+
+            let request = read_event(&mut reader).await.unwrap().unwrap();
+            assert_eq!(request.event_type, "synthesize");
+            assert_eq!(request.data["text"], "hello");
+            assert_eq!(request.data["voice"]["name"], "en_GB-cori-high");
+
+            let format = serde_json::json!({ "rate": 16000, "width": 2, "channels": 1 });
+            write_event(&mut writer, "audio-start", Some(format.clone()), None)
+                .await
+                .unwrap();
+            for chunk in [[1i16, 2], [3, 4]] {
+                let bytes: Vec<u8> = chunk.iter().flat_map(|s| s.to_le_bytes()).collect();
+                write_event(
+                    &mut writer,
+                    "audio-chunk",
+                    Some(format.clone()),
+                    Some(&bytes),
+                )
+                .await
+                .unwrap();
+            }
+            write_event(&mut writer, "audio-stop", None, None)
+                .await
+                .unwrap();
+        });
+
+        let config = Config {
+            wyoming_tts_host: "127.0.0.1".to_string(),
+            wyoming_tts_port: port,
+            wyoming_tts_voice: "en_GB-cori-high".to_string(),
+            ..Default::default()
+        };
+        let audio = WyomingTtsEngine::new(&config)
+            .synthesize("hello")
+            .await
+            .unwrap();
+        server.await.unwrap();
+
+        assert_eq!(
+            audio,
+            SynthesizedAudio {
+                samples: vec![1, 2, 3, 4],
+                rate: 16000,
+                channels: 1,
+            }
+        );
+    }
+}
@@ -1,3 +1,7 @@
+use crate::asr::offline::{normalize_transcript, transcribe_clip};
+use crate::asr::AsrEngine;
+use crate::audio::file_source::{decode_file, write_wav};
+use crate::audio::{AudioCapture, DeviceSelector, DspConfig, VadEvent, VoiceActivityDetector};
 use anyhow::{Context, Result};
 use chrono::Local;
 use serde::{Deserialize, Serialize};
@@ -6,7 +10,14 @@ use std::fs;
 use std::path::PathBuf;
 use std::sync::Mutex;
 use std::time::Duration;
-use tracing::info;
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
+
+/// Index of recorded clips and their phrases, inside `training/`
+const SAMPLES_FILE: &str = "samples.json";
+
+/// How long to wait for the user to start speaking
+const SPEECH_WAIT_TIMEOUT: Duration = Duration::from_secs(5);
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct VoicePattern {
@@ -24,6 +35,52 @@ pub struct FingerprintData {
     pub metadata: HashMap<String, String>,
 }
 
+/// A recorded training clip and the phrase the user was asked to say
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TrainingSample {
+    /// File name inside `training/`
+    pub file: String,
+    pub phrase: String,
+    pub recorded_at: String, // ISO timestamp
+}
+
+/// Recognition results for one training phrase
+#[derive(Debug, Clone, Default)]
+pub struct PhraseAccuracy {
+    pub phrase: String,
+    pub correct: u32,
+    pub total: u32,
+}
+
+impl PhraseAccuracy {
+    pub fn accuracy(&self) -> f32 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        self.correct as f32 / self.total as f32
+    }
+}
+
+/// Outcome of running the training clips through the ASR engine
+#[derive(Debug, Clone, Default)]
+pub struct EvaluationReport {
+    /// Per-phrase results, in recording order
+    pub phrases: Vec<PhraseAccuracy>,
+    /// Mismatches turned into manual corrections
+    pub corrections_added: usize,
+}
+
+impl EvaluationReport {
+    pub fn overall_accuracy(&self) -> f32 {
+        let total: u32 = self.phrases.iter().map(|p| p.total).sum();
+        if total == 0 {
+            return 0.0;
+        }
+        let correct: u32 = self.phrases.iter().map(|p| p.correct).sum();
+        correct as f32 / total as f32
+    }
+}
+
 pub struct VoiceFingerprint {
     base_path: PathBuf,
     fingerprint_file: PathBuf,
@@ -34,7 +91,11 @@ impl VoiceFingerprint {
     pub fn new() -> Result<Self> {
         let mut base_path = dirs::data_local_dir().context("No data dir")?;
         base_path.push("tuxtalks");
+        Self::with_dir(base_path)
+    }
 
+    /// Fingerprint and training clips stored under `base_path`
+    pub fn with_dir(base_path: PathBuf) -> Result<Self> {
         fs::create_dir_all(&base_path)?;
 
         let mut training_path = base_path.clone();
@@ -291,54 +352,241 @@ impl VoiceFingerprint {
         Ok(())
     }
 
-    /// Simulates recording a voice sample for a specific phrase
-    pub async fn record_sample(&self, phrase: &str) -> Result<PathBuf> {
-        info!("🎙️ Starting recording for phrase: '{}'", phrase);
+    /// Record one utterance of `phrase` from the microphone into `training/`
+    ///
+    /// Uses the same DSP stages as live capture so clips sound like what the
+    /// recognizer normally hears.
+    pub async fn record_sample(
+        &self,
+        phrase: &str,
+        device: DeviceSelector,
+        dsp: DspConfig,
+    ) -> Result<PathBuf> {
+        let (_capture, mut audio) = AudioCapture::start(device, dsp)?;
+        self.record_from(phrase, &mut audio).await
+    }
 
-        // Simulate recording duration
-        tokio::time::sleep(Duration::from_secs(3)).await;
+    /// Record one utterance of `phrase` from a 16 kHz mono stream
+    pub async fn record_from(
+        &self,
+        phrase: &str,
+        audio: &mut mpsc::UnboundedReceiver<Vec<i16>>,
+    ) -> Result<PathBuf> {
+        info!("🎙️ Starting recording for phrase: '{}'", phrase);
+        let samples = capture_utterance(audio).await?;
+        self.save_sample(phrase, &samples)
+    }
 
+    /// Store a clip and remember which phrase it is
+    pub fn save_sample(&self, phrase: &str, samples: &[i16]) -> Result<PathBuf> {
         // Sanitize filename
         let safe_name = phrase
             .replace(" ", "_")
             .replace(|c: char| !c.is_alphanumeric() && c != '_', "");
-        let timestamp = Local::now().format("%Y%m%d_%H%M%S");
+        let timestamp = Local::now().format("%Y%m%d_%H%M%S%3f");
         let filename = format!("{}_{}.wav", safe_name, timestamp);
-        let file_path = self.base_path.join(filename);
+        let file_path = self.base_path.join(&filename);
+
+        write_wav(&file_path, samples)?;
 
-        // Write a mock WAV file (header only + minimal data)
-        self.write_dummy_wav(&file_path)?;
+        let mut samples_index = self.samples()?;
+        samples_index.push(TrainingSample {
+            file: filename,
+            phrase: phrase.to_string(),
+            recorded_at: Local::now().to_rfc3339(),
+        });
+        let content = serde_json::to_string_pretty(&samples_index)?;
+        fs::write(self.base_path.join(SAMPLES_FILE), content)?;
 
         info!("✅ Saved sample to: {:?}", file_path);
         Ok(file_path)
     }
 
-    /// Simulates training the model based on collected samples
-    pub async fn train_model(&self) -> Result<()> {
-        info!("🧠 Starting model training...");
-        tokio::time::sleep(Duration::from_secs(2)).await;
-        info!("✅ Model training complete!");
-        Ok(())
+    /// All recorded training clips
+    pub fn samples(&self) -> Result<Vec<TrainingSample>> {
+        let path = self.base_path.join(SAMPLES_FILE);
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let content = fs::read_to_string(&path)?;
+        Ok(serde_json::from_str(&content)?)
     }
 
-    fn write_dummy_wav(&self, path: &PathBuf) -> Result<()> {
-        // Simple WAV header for 1 second of silence
-        let header: [u8; 44] = [
-            0x52, 0x49, 0x46, 0x46, // RIFF
-            0x24, 0x00, 0x00, 0x00, // ChunkSize
-            0x57, 0x41, 0x56, 0x45, // WAVE
-            0x66, 0x6d, 0x74, 0x20, // fmt
-            0x10, 0x00, 0x00, 0x00, // Subchunk1Size (16 for PCM)
-            0x01, 0x00, // AudioFormat (1 = PCM)
-            0x01, 0x00, // NumChannels (1)
-            0x44, 0xAC, 0x00, 0x00, // SampleRate (44100)
-            0x88, 0x58, 0x01, 0x00, // ByteRate
-            0x02, 0x00, // BlockAlign
-            0x10, 0x00, // BitsPerSample (16)
-            0x64, 0x61, 0x74, 0x61, // data
-            0x00, 0x00, 0x00, 0x00, // Subchunk2Size (0 data)
-        ];
-        fs::write(path, header)?;
-        Ok(())
+    /// Run every stored clip through `engine`, learn from the mismatches and
+    /// report per-phrase accuracy
+    pub async fn evaluate(&self, engine: &mut dyn AsrEngine) -> Result<EvaluationReport> {
+        let samples = self.samples()?;
+        if samples.is_empty() {
+            anyhow::bail!("No training samples recorded yet");
+        }
+        info!("🧠 Evaluating {} training samples...", samples.len());
+
+        let mut report = EvaluationReport::default();
+        for sample in samples {
+            let path = self.base_path.join(&sample.file);
+            let audio = match decode_file(&path) {
+                Ok(audio) => audio,
+                Err(e) => {
+                    warn!("⚠️ Skipping training sample {}: {:#}", sample.file, e);
+                    continue;
+                }
+            };
+
+            let heard = transcribe_clip(engine, &audio).await?.text;
+            let correct = normalize_transcript(&heard) == normalize_transcript(&sample.phrase);
+            if correct {
+                debug!("✅ '{}' recognized", sample.phrase);
+            } else {
+                info!("❌ '{}' heard as '{}'", sample.phrase, heard);
+                if !heard.trim().is_empty() && self.add_manual_correction(&heard, &sample.phrase) {
+                    report.corrections_added += 1;
+                }
+            }
+
+            let entry = match report
+                .phrases
+                .iter()
+                .position(|p| p.phrase == sample.phrase)
+            {
+                Some(idx) => &mut report.phrases[idx],
+                None => {
+                    report.phrases.push(PhraseAccuracy {
+                        phrase: sample.phrase.clone(),
+                        ..Default::default()
+                    });
+                    report.phrases.last_mut().expect("just pushed")
+                }
+            };
+            entry.total += 1;
+            if correct {
+                entry.correct += 1;
+            }
+        }
+
+        info!(
+            "✅ Evaluation complete: {:.0}% accurate, {} corrections learned",
+            report.overall_accuracy() * 100.0,
+            report.corrections_added
+        );
+        Ok(report)
+    }
+}
+
+/// Collect one utterance, waiting up to `SPEECH_WAIT_TIMEOUT` for it to start
+async fn capture_utterance(audio: &mut mpsc::UnboundedReceiver<Vec<i16>>) -> Result<Vec<i16>> {
+    let mut vad = VoiceActivityDetector::default();
+    let mut clip: Vec<i16> = Vec::new();
+    let deadline = tokio::time::Instant::now() + SPEECH_WAIT_TIMEOUT;
+
+    loop {
+        let chunk = if clip.is_empty() {
+            tokio::time::timeout_at(deadline, audio.recv())
+                .await
+                .map_err(|_| anyhow::anyhow!("No speech heard"))?
+        } else {
+            audio.recv().await
+        };
+        let Some(chunk) = chunk else {
+            if clip.is_empty() {
+                anyhow::bail!("Audio stream ended before any speech");
+            }
+            return Ok(clip);
+        };
+
+        match vad.process(&chunk) {
+            VadEvent::Silence => {}
+            VadEvent::UtteranceStart(pre_roll) => clip = pre_roll,
+            VadEvent::Speech => clip.extend_from_slice(&chunk),
+            VadEvent::UtteranceEnd { .. } => {
+                clip.extend_from_slice(&chunk);
+                return Ok(clip);
+            }
+            VadEvent::Discarded => clip.clear(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asr::{AsrEvent, AsrResult};
+
+    /// Engine that "hears" a fixed transcript per clip, keyed by its non-silent length
+    struct ScriptedEngine {
+        transcripts: HashMap<usize, String>,
+        heard: usize,
+        final_sent: bool,
+    }
+
+    impl AsrEngine for ScriptedEngine {
+        fn process(&mut self, samples: &[i16]) -> Result<Option<AsrEvent>> {
+            let voiced = samples.iter().filter(|&&s| s != 0).count();
+            if voiced > 0 {
+                self.heard += voiced;
+                return Ok(None);
+            }
+            if self.final_sent || self.heard == 0 {
+                return Ok(None);
+            }
+            self.final_sent = true;
+            let text = self
+                .transcripts
+                .get(&self.heard)
+                .cloned()
+                .unwrap_or_default();
+            Ok(Some(AsrEvent::Final(AsrResult::new(text, 1.0))))
+        }
+
+        fn reset(&mut self) {
+            self.heard = 0;
+            self.final_sent = false;
+        }
+
+        fn name(&self) -> &str {
+            "scripted"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_record_and_evaluate() {
+        let dir = tempfile::tempdir().unwrap();
+        let vf = VoiceFingerprint::with_dir(dir.path().to_path_buf()).unwrap();
+
+        // Silence, then half a second of "speech", then silence
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        for i in 0..40 {
+            let level = if (5..13).contains(&i) { 5000 } else { 0 };
+            tx.send(vec![level; 1024]).unwrap();
+        }
+        let path = vf.record_from("landing gear", &mut rx).await.unwrap();
+        let recorded = decode_file(&path).unwrap();
+        assert!(recorded.iter().filter(|&&s| s != 0).count() >= 8 * 1024);
+
+        vf.save_sample("landing gear", &[3000; 2048]).unwrap();
+        vf.save_sample("cargo scoop", &[3000; 4096]).unwrap();
+        assert_eq!(vf.samples().unwrap().len(), 3);
+
+        let mut engine = ScriptedEngine {
+            transcripts: HashMap::from([
+                (
+                    recorded.iter().filter(|&&s| s != 0).count(),
+                    "Landing gear.".to_string(),
+                ),
+                (2048, "landing beer".to_string()),
+                (4096, "cargo scoop".to_string()),
+            ]),
+            heard: 0,
+            final_sent: false,
+        };
+        let report = vf.evaluate(&mut engine).await.unwrap();
+
+        let gear = &report.phrases[0];
+        assert_eq!(
+            (gear.phrase.as_str(), gear.correct, gear.total),
+            ("landing gear", 1, 2)
+        );
+        assert_eq!(report.phrases[1].accuracy(), 1.0);
+        assert_eq!(report.corrections_added, 1);
+        assert!(vf.get_all_patterns().contains_key("beer"));
     }
 }
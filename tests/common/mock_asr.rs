@@ -4,7 +4,7 @@
 
 use anyhow::Result;
 use std::sync::{Arc, Mutex};
-use tuxtalks::asr::{AsrEngine, AsrResult};
+use tuxtalks::asr::{AsrEngine, AsrEvent, AsrResult};
 
 /// Mock ASR engine that returns predetermined responses
 pub struct MockAsr {
@@ -30,15 +30,12 @@ impl MockAsr {
 
     /// Create a mock that returns a single phrase
     pub fn with_phrase(text: &str, confidence: f32) -> Self {
-        Self::new(vec![AsrResult {
-            text: text.to_string(),
-            confidence,
-        }])
+        Self::new(vec![AsrResult::new(text, confidence)])
     }
 }
 
 impl AsrEngine for MockAsr {
-    fn process(&mut self, samples: &[i16]) -> Result<Option<AsrResult>> {
+    fn process(&mut self, samples: &[i16]) -> Result<Option<AsrEvent>> {
         // Record received audio
         if let Ok(mut chunks) = self.received_chunks.lock() {
             chunks.push(samples.to_vec());
@@ -53,7 +50,7 @@ impl AsrEngine for MockAsr {
         if self.idx < self.responses.len() {
             let result = self.responses[self.idx].clone();
             self.idx += 1;
-            Ok(Some(result))
+            Ok(Some(AsrEvent::Final(result)))
         } else {
             Ok(None)
         }
@@ -71,6 +68,10 @@ impl AsrEngine for MockAsr {
         self.paused = false;
     }
 
+    fn name(&self) -> &str {
+        "mock"
+    }
+
     fn is_paused(&self) -> bool {
         self.paused
     }
@@ -84,8 +85,10 @@ mod tests {
     fn test_mock_asr_returns_responses() {
         let mut mock = MockAsr::with_phrase("hello world", 0.95);
         let result = mock.process(&[0i16; 100]).unwrap();
-        assert!(result.is_some());
-        assert_eq!(result.unwrap().text, "hello world");
+        match result {
+            Some(AsrEvent::Final(r)) => assert_eq!(r.text, "hello world"),
+            other => panic!("Expected final result, got {:?}", other),
+        }
     }
 
     #[test]
@@ -69,6 +69,40 @@ async fn test_high_risk_confirmation_fuzz() {
         ),
     }
 }
+
+#[tokio::test]
+async fn test_confirmed_command_enters_history() {
+    use tuxtalks::commands::{Command, Macro};
+
+    let mut processor = CommandProcessor::new().expect("Failed to create processor");
+    processor.add_command(Command::Macro(Macro {
+        name: "eject".to_string(),
+        triggers: vec!["eject".to_string()],
+        steps: Vec::new(),
+        ..Default::default()
+    }));
+
+    let (command, modifiers) = match processor.process("eject").await {
+        ProcessResult::ConfirmationRequired {
+            command, modifiers, ..
+        } => (command, modifiers),
+        other => panic!(
+            "Dangerous command did not ask to confirm. Found: {:?}",
+            other
+        ),
+    };
+    match processor.run_confirmed(command, modifiers).await {
+        ProcessResult::Success(name) => assert_eq!(name, "eject"),
+        other => panic!("Confirmed command did not run. Found: {:?}", other),
+    }
+
+    // It is in the history, so undo knows about it
+    match processor.process("undo").await {
+        ProcessResult::Declined { reason } => assert_eq!(reason, "eject can't be undone"),
+        other => panic!("Undo did not see the confirmed command. Found: {:?}", other),
+    }
+}
+
 #[tokio::test]
 async fn test_entity_verification_fuzz() {
     let mut processor = CommandProcessor::new().expect("Failed to create processor");
@@ -126,3 +160,478 @@ async fn test_phonetic_matching_fuzz() {
         ),
     }
 }
+
+#[tokio::test]
+async fn test_partial_early_fire_guards() {
+    // Longer than the partial stability window
+    const STABLE: std::time::Duration = std::time::Duration::from_millis(250);
+    let mut processor = CommandProcessor::new().expect("Failed to create processor");
+
+    use tuxtalks::commands::Command;
+    processor.add_command(Command::Action {
+        name: "Landing Gear".to_string(),
+        triggers: vec!["gear".to_string()],
+        key: "G".to_string(),
+        modifiers: vec![],
+    });
+    processor.add_command(Command::Action {
+        name: "Gear Up".to_string(),
+        triggers: vec!["gear up".to_string()],
+        key: "U".to_string(),
+        modifiers: vec![],
+    });
+    processor.add_command(Command::Action {
+        name: "self destruct".to_string(),
+        triggers: vec!["self destruct".to_string()],
+        key: "X".to_string(),
+        modifiers: vec![],
+    });
+
+    // A single partial is never considered stable
+    processor.reset_partial();
+    assert_eq!(processor.process_partial("self destruct"), None);
+
+    // Dangerous commands never fire early, even when stable
+    tokio::time::sleep(STABLE).await;
+    assert_eq!(processor.process_partial("self destruct"), None);
+
+    // "gear" could still grow into "gear up", so it must wait for the final
+    processor.reset_partial();
+    assert_eq!(processor.process_partial("gear"), None);
+    tokio::time::sleep(STABLE).await;
+    assert_eq!(processor.process_partial("gear"), None);
+
+    // Partials that are not an exact trigger never fire
+    processor.reset_partial();
+    assert_eq!(processor.process_partial("landing"), None);
+    tokio::time::sleep(STABLE).await;
+    assert_eq!(processor.process_partial("landing"), None);
+}
+
+#[tokio::test]
+async fn test_partial_early_fire_once() {
+    use std::time::Duration;
+    use tuxtalks::commands::{Command, Macro};
+
+    let mut processor = CommandProcessor::new().expect("Failed to create processor");
+    processor.add_command(Command::Macro(Macro {
+        name: "Request Docking".to_string(),
+        triggers: vec!["request docking".to_string()],
+        ..Default::default()
+    }));
+
+    processor.reset_partial();
+    assert_eq!(processor.process_partial("request docking"), None);
+    // Stability is measured in time, not in how many partials arrived
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    assert_eq!(
+        processor.process_partial("request docking"),
+        Some("Request Docking".to_string())
+    );
+    // Recorded like any other command, so "undo"/"again" see it
+    assert_eq!(processor.history().len(), 1);
+    // Fires once per utterance
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    assert_eq!(processor.process_partial("request docking"), None);
+
+    // The final of the same utterance has nothing left to run
+    assert_eq!(processor.early_fire_remainder("request docking"), None);
+    assert_eq!(
+        processor.early_fire_remainder("request docking and boost"),
+        Some("boost".to_string())
+    );
+
+    // A modifier in the final completes the command instead of being dropped
+    assert_eq!(processor.finish_early_fire("request docking").await, None);
+    assert_eq!(
+        processor
+            .finish_early_fire("request docking three times")
+            .await,
+        Some("Request Docking".to_string())
+    );
+}
+
+#[tokio::test]
+async fn test_partial_early_fire_respects_toggles() {
+    use std::collections::HashMap;
+    use std::time::Duration;
+    use tuxtalks::commands::{Command, Macro};
+    use tuxtalks::games::TagState;
+
+    let mut processor = CommandProcessor::new().expect("Failed to create processor");
+    processor.add_command(Command::Macro(Macro {
+        name: "Landing Gear".to_string(),
+        triggers: vec!["deploy gear".to_string(), "retract gear".to_string()],
+        ..Default::default()
+    }));
+    processor.set_toggles(HashMap::from([(
+        "Landing Gear".to_string(),
+        TagState::toggle(&["deploy gear"], &["retract gear"]),
+    )]));
+
+    // Already down: left to the final, which reports it instead of pressing
+    assert!(processor.sync_toggle("Landing Gear", Some(true)));
+    processor.reset_partial();
+    assert_eq!(processor.process_partial("deploy gear"), None);
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    assert_eq!(processor.process_partial("deploy gear"), None);
+
+    // Retracting fires early and is remembered
+    processor.reset_partial();
+    assert_eq!(processor.process_partial("retract gear"), None);
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    assert_eq!(
+        processor.process_partial("retract gear"),
+        Some("Landing Gear".to_string())
+    );
+    assert_eq!(processor.toggle_state("Landing Gear"), Some(false));
+}
+
+#[tokio::test]
+async fn test_nbest_rescoring_prefers_exact_alternative() {
+    use tuxtalks::asr::AsrAlternative;
+    use tuxtalks::commands::Command;
+
+    let mut processor = CommandProcessor::new().expect("Failed to create processor");
+    processor.add_command(Command::Action {
+        name: "Landing Gear".to_string(),
+        triggers: vec!["landing gear".to_string()],
+        key: "G".to_string(),
+        modifiers: vec![],
+    });
+
+    let hypotheses = vec![
+        AsrAlternative {
+            text: "land in the rear".to_string(),
+            confidence: 0.6,
+        },
+        AsrAlternative {
+            text: "landing gear".to_string(),
+            confidence: 0.3,
+        },
+    ];
+
+    assert!(processor.match_command(&hypotheses[0].text).is_none());
+    let (cmd, idx) = processor
+        .match_hypotheses(&hypotheses)
+        .expect("Alternative should match");
+    assert_eq!(cmd.name(), "Landing Gear");
+    assert_eq!(idx, 1);
+}
+
+#[tokio::test]
+async fn test_token_aligned_ranking() {
+    use tuxtalks::commands::Command;
+
+    let mut processor = CommandProcessor::new().expect("Failed to create processor");
+    for (name, trigger) in [
+        ("Gear", "gear"),
+        ("Landing Gear", "landing gear"),
+        ("Jump", "jump"),
+        ("Target Nav Beacon", "nav beacon"),
+        ("Boost", "boost"),
+    ] {
+        processor.add_command(Command::Action {
+            name: name.to_string(),
+            triggers: vec![trigger.to_string()],
+            key: "G".to_string(),
+            modifiers: vec![],
+        });
+    }
+
+    // Longer triggers win regardless of profile order
+    let ranked = processor.rank_commands("deploy landing gear");
+    assert_eq!(ranked[0].command.name(), "Landing Gear");
+    assert_eq!(ranked[1].command.name(), "Gear");
+    assert!(ranked[0].score > ranked[1].score);
+
+    assert_eq!(
+        processor
+            .match_command("jump to nav beacon")
+            .map(|c| c.name().to_string()),
+        Some("Target Nav Beacon".to_string())
+    );
+
+    // Triggers only match whole words
+    assert!(processor
+        .rank_commands("boosting")
+        .iter()
+        .all(|m| m.trigger != "boost" || m.score < 0.75));
+}
+
+#[tokio::test]
+async fn test_tied_commands_require_choice() {
+    use tuxtalks::commands::Command;
+
+    let mut processor = CommandProcessor::new().expect("Failed to create processor");
+    for (name, trigger) in [("Fire Missiles", "fire"), ("Fire Extinguisher", "fire")] {
+        processor.add_command(Command::Action {
+            name: name.to_string(),
+            triggers: vec![trigger.to_string()],
+            key: "F".to_string(),
+            modifiers: vec![],
+        });
+    }
+
+    match processor.process("fire").await {
+        ProcessResult::CommandChoiceRequired { candidates, .. } => {
+            let names: Vec<&str> = candidates.iter().map(|c| c.command.name()).collect();
+            assert_eq!(names, ["Fire Missiles", "Fire Extinguisher"]);
+        }
+        other => panic!("Tie did not ask for a choice. Found: {:?}", other),
+    }
+
+    // A shared trigger never fires early either
+    processor.reset_partial();
+    assert_eq!(processor.process_partial("fire"), None);
+    assert_eq!(processor.process_partial("fire"), None);
+}
+
+#[tokio::test]
+async fn test_compound_utterances() {
+    use tuxtalks::commands::Command;
+
+    let mut processor = CommandProcessor::new().expect("Failed to create processor");
+    for (name, trigger) in [
+        ("Landing Gear", "deploy gear"),
+        ("Lights", "lights on"),
+        ("Boost", "boost"),
+        ("Hardpoints", "hardpoints"),
+        ("Search And Destroy", "search and destroy"),
+    ] {
+        processor.add_command(Command::Action {
+            name: name.to_string(),
+            triggers: vec![trigger.to_string()],
+            key: "G".to_string(),
+            modifiers: vec![],
+        });
+    }
+
+    let names = |text: &str| {
+        processor.match_compound(text).map(|cmds| {
+            cmds.iter()
+                .map(|(m, _)| m.command.name().to_string())
+                .collect::<Vec<_>>()
+        })
+    };
+
+    assert_eq!(
+        names("deploy gear and lights on"),
+        Some(vec!["Landing Gear".to_string(), "Lights".to_string()])
+    );
+    assert_eq!(
+        names("boost and then hardpoints"),
+        Some(vec!["Boost".to_string(), "Hardpoints".to_string()])
+    );
+
+    // A trigger containing "and" is one command, not two
+    assert_eq!(names("search and destroy"), None);
+    // Every segment must be a command
+    assert_eq!(names("boost and make it snappy"), None);
+    assert_eq!(names("deploy gear"), None);
+}
+
+#[tokio::test]
+async fn test_spoken_modifiers() {
+    use std::time::Duration;
+    use tuxtalks::commands::Command;
+
+    let mut processor = CommandProcessor::new().expect("Failed to create processor");
+    for (name, trigger) in [
+        ("Next Target", "next target"),
+        ("Boost", "boost"),
+        ("Double Tap", "fire twice"),
+        ("Fire", "fire"),
+    ] {
+        processor.add_command(Command::Action {
+            name: name.to_string(),
+            triggers: vec![trigger.to_string()],
+            key: "T".to_string(),
+            modifiers: vec![],
+        });
+    }
+
+    let cmds = processor
+        .match_compound("next target five times and boost for two seconds")
+        .expect("Both segments should match");
+    assert_eq!(cmds[0].0.command.name(), "Next Target");
+    assert_eq!(cmds[0].1.repeat, 5);
+    assert_eq!(cmds[1].0.command.name(), "Boost");
+    assert_eq!(cmds[1].1.hold, Some(Duration::from_secs(2)));
+
+    // Modifier words that are part of a trigger stay with it
+    let cmds = processor
+        .match_compound("fire twice then boost")
+        .expect("Both segments should match");
+    assert_eq!(cmds[0].0.command.name(), "Double Tap");
+    assert!(cmds[0].1.is_plain());
+}
+
+#[tokio::test]
+async fn test_undo_and_repeat_last_command() {
+    use tuxtalks::commands::{Command, Macro};
+
+    let mut processor = CommandProcessor::new().unwrap();
+    processor.add_command(Command::Macro(Macro {
+        name: "Request Docking".to_string(),
+        triggers: vec!["request docking".to_string()],
+        undo: Some("Cancel Docking".to_string()),
+        ..Default::default()
+    }));
+    processor.add_command(Command::Macro(Macro {
+        name: "Cancel Docking".to_string(),
+        triggers: vec!["cancel docking".to_string()],
+        ..Default::default()
+    }));
+
+    match processor.process("cancel that").await {
+        ProcessResult::Declined { reason } => assert_eq!(reason, "Nothing to undo"),
+        other => panic!("Expected nothing to undo, got {:?}", other),
+    }
+    match processor.process("again").await {
+        ProcessResult::Declined { reason } => assert_eq!(reason, "Nothing to repeat"),
+        other => panic!("Expected nothing to repeat, got {:?}", other),
+    }
+
+    processor.process("request docking").await;
+    // Let the macro finish; a running macro can't be started again
+    tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    match processor.process("again").await {
+        ProcessResult::Success(name) => assert_eq!(name, "Request Docking"),
+        other => panic!("Expected repeat, got {:?}", other),
+    }
+    assert_eq!(processor.history().len(), 2);
+
+    match processor.process("cancel that").await {
+        ProcessResult::Success(msg) => assert_eq!(msg, "Undo Request Docking"),
+        other => panic!("Expected undo, got {:?}", other),
+    }
+    assert_eq!(processor.history().len(), 1);
+    tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+
+    // Without a declared undo the command stays in the history
+    processor.process("cancel docking").await;
+    match processor.process("undo").await {
+        ProcessResult::Declined { reason } => assert_eq!(reason, "Cancel Docking can't be undone"),
+        other => panic!("Expected can't be undone, got {:?}", other),
+    }
+    assert_eq!(processor.history().len(), 2);
+}
+
+#[tokio::test]
+async fn test_abort_running_macro() {
+    use tuxtalks::commands::{Command, Macro, MacroStep, StepFlow};
+
+    let mut processor = CommandProcessor::new().unwrap();
+    processor.add_command(Command::Macro(Macro {
+        name: "Request Docking".to_string(),
+        triggers: vec!["request docking".to_string()],
+        steps: vec![MacroStep {
+            flow: Some(StepFlow::WaitFor {
+                phrases: vec!["granted".to_string()],
+                timeout_ms: 60_000,
+            }),
+            ..Default::default()
+        }],
+        ..Default::default()
+    }));
+
+    match processor.process("belay that").await {
+        ProcessResult::Declined { reason } => assert_eq!(reason, "Nothing to abort"),
+        other => panic!("Expected nothing to abort, got {:?}", other),
+    }
+    assert!(matches!(
+        processor.process("request docking").await,
+        ProcessResult::Success(_)
+    ));
+    // Still waiting: a second start is refused
+    assert!(matches!(
+        processor.process("request docking").await,
+        ProcessResult::NotFound
+    ));
+    match processor.process("belay that").await {
+        ProcessResult::Success(msg) => assert_eq!(msg, "Aborted Request Docking"),
+        other => panic!("Expected abort, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_wait_outlives_command_timeout() {
+    use std::time::Duration;
+    use tuxtalks::commands::{Command, Macro, MacroStep, StepFlow};
+
+    // Command mode closes after this long without speech
+    let command_timeout = Duration::from_secs(1);
+
+    let mut processor = CommandProcessor::new().unwrap();
+    processor.add_command(Command::Macro(Macro {
+        name: "Request Docking".to_string(),
+        triggers: vec!["request docking".to_string()],
+        steps: vec![MacroStep {
+            flow: Some(StepFlow::WaitFor {
+                phrases: vec!["granted".to_string()],
+                timeout_ms: 3 * command_timeout.as_millis() as u64,
+            }),
+            ..Default::default()
+        }],
+        ..Default::default()
+    }));
+
+    assert!(!processor.awaiting_speech());
+    processor.process("request docking").await;
+    tokio::time::sleep(command_timeout + Duration::from_millis(200)).await;
+
+    // Still waiting after command mode would have closed, so listening stays on
+    assert!(processor.awaiting_speech());
+    match processor.process("granted").await {
+        ProcessResult::Success(msg) => assert_eq!(msg, "Request Docking resumed"),
+        other => panic!("Expected resume, got {:?}", other),
+    }
+    assert!(!processor.awaiting_speech());
+}
+
+#[tokio::test]
+async fn test_compound_waits_for_repeated_segment() {
+    use std::time::Duration;
+    use tuxtalks::commands::{Command, Macro, MacroStep};
+
+    let mut processor = CommandProcessor::new().unwrap();
+    processor.add_command(Command::Macro(Macro {
+        name: "Next Target".to_string(),
+        triggers: vec!["next target".to_string()],
+        steps: vec![MacroStep {
+            delay: 100,
+            ..Default::default()
+        }],
+        ..Default::default()
+    }));
+    processor.add_command(Command::Macro(Macro {
+        name: "Boost".to_string(),
+        triggers: vec!["boost".to_string()],
+        ..Default::default()
+    }));
+
+    match processor
+        .process("next target three times then boost")
+        .await
+    {
+        ProcessResult::Success(names) => assert_eq!(names, "Next Target, Boost"),
+        other => panic!("Expected compound, got {:?}", other),
+    }
+    // Boost only starts once all three targets are done (~500 ms)
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    let names: Vec<String> = processor
+        .history()
+        .iter()
+        .map(|c| c.name().to_string())
+        .collect();
+    assert_eq!(names, ["Next Target"]);
+
+    tokio::time::sleep(Duration::from_millis(800)).await;
+    let names: Vec<String> = processor
+        .history()
+        .iter()
+        .map(|c| c.name().to_string())
+        .collect();
+    assert_eq!(names, ["Next Target", "Boost"]);
+}